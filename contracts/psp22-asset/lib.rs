@@ -0,0 +1,447 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # PSP22 Asset
+//!
+//! A [PSP22](https://github.com/w3f/PSPs/blob/master/PSPs/psp-22.md) token backed by an asset of
+//! `pallet_assets`, built on the assets chain extension of the node template runtime.
+//!
+//! The contract keeps no balances of its own: the balances and the total supply are those of the
+//! asset. It moves the assets of holders as their delegate, so a holder approves the contract
+//! once through `Assets::approve_transfer` before transferring through it. The allowances of
+//! PSP22 between holders are kept by the contract, `pallet_assets` only knows the approval of the
+//! contract itself.
+//!
+//! Build with `cargo contract build`.
+
+use ink_env::{DefaultEnvironment, Environment};
+use ink_lang as ink;
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The identifier of an asset of `pallet_assets`.
+pub type AssetId = u32;
+
+/// The assets extension of the runtime, see `runtime/src/chain_extension/assets.rs`.
+#[ink::chain_extension]
+pub trait AssetsExtension {
+	type ErrorCode = AssetsError;
+
+	/// The balance of `owner`.
+	#[ink(extension = 0x0001_0000, returns_result = false, handle_status = false)]
+	fn balance_of(asset: AssetId, owner: AccountId) -> Balance;
+
+	/// The total issuance of `asset`.
+	#[ink(extension = 0x0001_0001, returns_result = false, handle_status = false)]
+	fn total_supply(asset: AssetId) -> Balance;
+
+	/// The amount `spender` may transfer from `owner`.
+	#[ink(extension = 0x0001_0002, returns_result = false, handle_status = false)]
+	fn allowance(asset: AssetId, owner: AccountId, spender: AccountId) -> Balance;
+
+	/// Transfer `value` from the contract to `to`.
+	#[ink(extension = 0x0001_0003, returns_result = false)]
+	fn transfer(asset: AssetId, to: AccountId, value: Balance);
+
+	/// Let `spender` transfer up to `value` from the contract.
+	#[ink(extension = 0x0001_0004, returns_result = false)]
+	fn approve(asset: AssetId, spender: AccountId, value: Balance);
+
+	/// Transfer `value` from `from`, which approved the contract, to `to`.
+	#[ink(extension = 0x0001_0005, returns_result = false)]
+	fn transfer_from(asset: AssetId, from: AccountId, to: AccountId, value: Balance);
+}
+
+/// Why a call of the assets extension failed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum AssetsError {
+	/// The balance of the sender is too low.
+	BalanceLow,
+	/// The asset does not exist.
+	Unknown,
+	/// The contract was not approved to transfer the amount.
+	Unapproved,
+	/// The asset or the account is frozen.
+	Frozen,
+	/// The transfer would leave an account below the minimum balance of the asset.
+	BelowMinimum,
+	/// Any other error of `pallet_assets`.
+	Other,
+}
+
+impl ink_env::chain_extension::FromStatusCode for AssetsError {
+	fn from_status_code(status_code: u32) -> Result<(), Self> {
+		match status_code {
+			0 => Ok(()),
+			1 => Err(AssetsError::BalanceLow),
+			2 => Err(AssetsError::Unknown),
+			3 => Err(AssetsError::Unapproved),
+			4 => Err(AssetsError::Frozen),
+			5 => Err(AssetsError::BelowMinimum),
+			_ => Err(AssetsError::Other),
+		}
+	}
+}
+
+/// The default environment with the chain extension of the node template runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RuntimeEnvironment {}
+
+impl Environment for RuntimeEnvironment {
+	const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+	type AccountId = AccountId;
+	type Balance = Balance;
+	type Hash = <DefaultEnvironment as Environment>::Hash;
+	type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+	type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+	type ChainExtension = AssetsExtension;
+}
+
+#[ink::contract(env = crate::RuntimeEnvironment)]
+mod psp22_asset {
+	use super::{AssetId, AssetsError};
+	use ink_prelude::{string::String, vec::Vec};
+	use ink_storage::collections::HashMap as StorageHashMap;
+
+	/// A PSP22 token for one asset.
+	#[ink(storage)]
+	pub struct Psp22Asset {
+		/// The asset of the token.
+		asset: AssetId,
+		/// The amount a spender may transfer from an owner, by owner and spender.
+		allowances: StorageHashMap<(AccountId, AccountId), Balance>,
+	}
+
+	/// Tokens were transferred.
+	#[ink(event)]
+	pub struct Transfer {
+		#[ink(topic)]
+		from: Option<AccountId>,
+		#[ink(topic)]
+		to: Option<AccountId>,
+		value: Balance,
+	}
+
+	/// The allowance of `spender` on the tokens of `owner` was set to `value`.
+	#[ink(event)]
+	pub struct Approval {
+		#[ink(topic)]
+		owner: AccountId,
+		#[ink(topic)]
+		spender: AccountId,
+		value: Balance,
+	}
+
+	/// The errors of PSP22.
+	#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+	#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+	pub enum PSP22Error {
+		/// Any other error, here a failure of `pallet_assets`.
+		Custom(String),
+		/// The balance of the sender is too low.
+		InsufficientBalance,
+		/// The allowance of the caller is too low.
+		InsufficientAllowance,
+		/// Tokens can not be transferred to the zero address.
+		ZeroRecipientAddress,
+		/// Tokens can not be transferred from the zero address.
+		ZeroSenderAddress,
+		/// The recipient rejected the transfer.
+		SafeTransferCheckFailed(String),
+	}
+
+	impl From<AssetsError> for PSP22Error {
+		fn from(error: AssetsError) -> Self {
+			match error {
+				AssetsError::BalanceLow => PSP22Error::InsufficientBalance,
+				// The holder did not approve the contract in `pallet_assets`.
+				AssetsError::Unapproved => PSP22Error::Custom(String::from("ContractNotApproved")),
+				AssetsError::Unknown => PSP22Error::Custom(String::from("UnknownAsset")),
+				AssetsError::Frozen => PSP22Error::Custom(String::from("Frozen")),
+				AssetsError::BelowMinimum => PSP22Error::Custom(String::from("BelowMinimum")),
+				AssetsError::Other => PSP22Error::Custom(String::from("AssetsError")),
+			}
+		}
+	}
+
+	impl Psp22Asset {
+		/// A token for `asset`.
+		#[ink(constructor)]
+		pub fn new(asset: AssetId) -> Self {
+			Self { asset, allowances: Default::default() }
+		}
+
+		/// The asset of the token.
+		#[ink(message)]
+		pub fn asset(&self) -> AssetId {
+			self.asset
+		}
+
+		/// The total issuance of the asset.
+		#[ink(message, selector = 0x162df8c2)]
+		pub fn total_supply(&self) -> Balance {
+			self.env().extension().total_supply(self.asset)
+		}
+
+		/// The balance of `owner` in the asset.
+		#[ink(message, selector = 0x6568382f)]
+		pub fn balance_of(&self, owner: AccountId) -> Balance {
+			self.env().extension().balance_of(self.asset, owner)
+		}
+
+		/// The amount `spender` may transfer from `owner` through the contract.
+		#[ink(message, selector = 0x4d47d921)]
+		pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+			self.allowances.get(&(owner, spender)).copied().unwrap_or(0)
+		}
+
+		/// Transfer `value` from the caller to `to`.
+		#[ink(message, selector = 0xdb20f9f5)]
+		pub fn transfer(
+			&mut self,
+			to: AccountId,
+			value: Balance,
+			_data: Vec<u8>,
+		) -> Result<(), PSP22Error> {
+			let from = self.env().caller();
+			self.transfer_from_to(from, to, value)
+		}
+
+		/// Transfer `value` from `from` to `to`, spending the allowance of the caller.
+		#[ink(message, selector = 0x54b3c76e)]
+		pub fn transfer_from(
+			&mut self,
+			from: AccountId,
+			to: AccountId,
+			value: Balance,
+			_data: Vec<u8>,
+		) -> Result<(), PSP22Error> {
+			let caller = self.env().caller();
+			let allowance = self.allowance(from, caller);
+			if allowance < value {
+				return Err(PSP22Error::InsufficientAllowance)
+			}
+			self.transfer_from_to(from, to, value)?;
+			self.set_allowance(from, caller, allowance - value);
+			Ok(())
+		}
+
+		/// Let `spender` transfer up to `value` from the caller.
+		#[ink(message, selector = 0xb20f1bbd)]
+		pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<(), PSP22Error> {
+			let owner = self.env().caller();
+			self.set_allowance(owner, spender, value);
+			Ok(())
+		}
+
+		/// Let `spender` transfer `delta_value` more from the caller.
+		#[ink(message, selector = 0x96d6b57a)]
+		pub fn increase_allowance(
+			&mut self,
+			spender: AccountId,
+			delta_value: Balance,
+		) -> Result<(), PSP22Error> {
+			let owner = self.env().caller();
+			let allowance = self.allowance(owner, spender);
+			self.set_allowance(owner, spender, allowance.saturating_add(delta_value));
+			Ok(())
+		}
+
+		/// Let `spender` transfer `delta_value` less from the caller.
+		#[ink(message, selector = 0xfecb57d5)]
+		pub fn decrease_allowance(
+			&mut self,
+			spender: AccountId,
+			delta_value: Balance,
+		) -> Result<(), PSP22Error> {
+			let owner = self.env().caller();
+			let allowance = self.allowance(owner, spender);
+			if allowance < delta_value {
+				return Err(PSP22Error::InsufficientAllowance)
+			}
+			self.set_allowance(owner, spender, allowance - delta_value);
+			Ok(())
+		}
+
+		fn transfer_from_to(
+			&mut self,
+			from: AccountId,
+			to: AccountId,
+			value: Balance,
+		) -> Result<(), PSP22Error> {
+			let zero = AccountId::from([0; 32]);
+			if from == zero {
+				return Err(PSP22Error::ZeroSenderAddress)
+			}
+			if to == zero {
+				return Err(PSP22Error::ZeroRecipientAddress)
+			}
+			if self.balance_of(from) < value {
+				return Err(PSP22Error::InsufficientBalance)
+			}
+			self.env().extension().transfer_from(self.asset, from, to, value)?;
+			self.env().emit_event(Transfer { from: Some(from), to: Some(to), value });
+			Ok(())
+		}
+
+		fn set_allowance(&mut self, owner: AccountId, spender: AccountId, value: Balance) {
+			self.allowances.insert((owner, spender), value);
+			self.env().emit_event(Approval { owner, spender, value });
+		}
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+		use ink_lang as ink;
+		use scale::{Decode, Encode};
+		use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+		const ASSET: AssetId = 1;
+
+		/// The state of `pallet_assets` behind the mocked extension.
+		#[derive(Default)]
+		struct Ledger {
+			balances: HashMap<AccountId, Balance>,
+			approvals: HashMap<(AccountId, AccountId), Balance>,
+		}
+
+		/// One function of the mocked assets extension, sharing the ledger with the others.
+		struct MockExtension {
+			func_id: u32,
+			ledger: Rc<RefCell<Ledger>>,
+		}
+
+		impl ink_env::test::ChainExtension for MockExtension {
+			fn func_id(&self) -> u32 {
+				self.func_id
+			}
+
+			fn call(&mut self, mut input: &[u8], output: &mut Vec<u8>) -> u32 {
+				let mut ledger = self.ledger.borrow_mut();
+				match self.func_id {
+					0x0001_0000 => {
+						let (_, owner) = <(AssetId, AccountId)>::decode(&mut input).unwrap();
+						ledger.balances.get(&owner).copied().unwrap_or(0).encode_to(output);
+					},
+					0x0001_0001 => {
+						ledger.balances.values().sum::<Balance>().encode_to(output);
+					},
+					0x0001_0005 => {
+						let (_, from, to, value) =
+							<(AssetId, AccountId, AccountId, Balance)>::decode(&mut input).unwrap();
+						let contract =
+							ink_env::account_id::<ink_env::DefaultEnvironment>().unwrap();
+						let approved = ledger.approvals.entry((from, contract)).or_default();
+						if *approved < value {
+							return 3
+						}
+						*approved -= value;
+						*ledger.balances.entry(from).or_default() -= value;
+						*ledger.balances.entry(to).or_default() += value;
+					},
+					// The token only reads balances and transfers from, report anything else as
+					// `AssetsError::Other`.
+					_ => return 6,
+				}
+				0
+			}
+		}
+
+		/// Mock the assets extension, with `holdings` of the asset and the contract approved to
+		/// transfer `approved` from every holder.
+		fn mock_assets(holdings: &[(AccountId, Balance)], approved: Balance) {
+			let contract = ink_env::account_id::<ink_env::DefaultEnvironment>().unwrap();
+			let ledger = Rc::new(RefCell::new(Ledger::default()));
+			for (holder, balance) in holdings {
+				ledger.borrow_mut().balances.insert(*holder, *balance);
+				ledger.borrow_mut().approvals.insert((*holder, contract), approved);
+			}
+			for func_id in [0x0001_0000, 0x0001_0001, 0x0001_0005] {
+				ink_env::test::register_chain_extension(MockExtension {
+					func_id,
+					ledger: ledger.clone(),
+				});
+			}
+		}
+
+		fn accounts() -> ink_env::test::DefaultAccounts<ink_env::DefaultEnvironment> {
+			ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().unwrap()
+		}
+
+		fn set_caller(caller: AccountId) {
+			let callee = ink_env::account_id::<ink_env::DefaultEnvironment>().unwrap();
+			ink_env::test::push_execution_context::<ink_env::DefaultEnvironment>(
+				caller,
+				callee,
+				1_000_000,
+				0,
+				ink_env::test::CallData::new(ink_env::call::Selector::new([0; 4])),
+			);
+		}
+
+		#[ink::test]
+		fn balances_are_those_of_the_asset() {
+			let accounts = accounts();
+			mock_assets(&[(accounts.alice, 100), (accounts.bob, 50)], 0);
+			let token = Psp22Asset::new(ASSET);
+
+			assert_eq!(token.total_supply(), 150);
+			assert_eq!(token.balance_of(accounts.alice), 100);
+			assert_eq!(token.balance_of(accounts.charlie), 0);
+		}
+
+		#[ink::test]
+		fn transfers_spend_the_approval_of_the_contract() {
+			let accounts = accounts();
+			mock_assets(&[(accounts.alice, 100)], 30);
+			let mut token = Psp22Asset::new(ASSET);
+
+			assert_eq!(token.transfer(accounts.bob, 20, Vec::new()), Ok(()));
+			assert_eq!(token.balance_of(accounts.alice), 80);
+			assert_eq!(token.balance_of(accounts.bob), 20);
+			assert_eq!(ink_env::test::recorded_events().count(), 1);
+
+			assert_eq!(
+				token.transfer(accounts.bob, 20, Vec::new()),
+				Err(PSP22Error::Custom(String::from("ContractNotApproved")))
+			);
+			assert_eq!(
+				token.transfer(accounts.bob, 200, Vec::new()),
+				Err(PSP22Error::InsufficientBalance)
+			);
+			assert_eq!(
+				token.transfer(AccountId::from([0; 32]), 1, Vec::new()),
+				Err(PSP22Error::ZeroRecipientAddress)
+			);
+		}
+
+		#[ink::test]
+		fn transfer_from_spends_the_allowance() {
+			let accounts = accounts();
+			mock_assets(&[(accounts.alice, 100)], 100);
+			let mut token = Psp22Asset::new(ASSET);
+
+			assert_eq!(token.approve(accounts.bob, 10), Ok(()));
+			assert_eq!(token.increase_allowance(accounts.bob, 5), Ok(()));
+			assert_eq!(token.decrease_allowance(accounts.bob, 3), Ok(()));
+			assert_eq!(token.allowance(accounts.alice, accounts.bob), 12);
+
+			set_caller(accounts.bob);
+			assert_eq!(
+				token.transfer_from(accounts.alice, accounts.charlie, 13, Vec::new()),
+				Err(PSP22Error::InsufficientAllowance)
+			);
+			assert_eq!(
+				token.transfer_from(accounts.alice, accounts.charlie, 12, Vec::new()),
+				Ok(())
+			);
+			assert_eq!(token.balance_of(accounts.charlie), 12);
+			assert_eq!(token.allowance(accounts.alice, accounts.bob), 0);
+		}
+	}
+}
@@ -0,0 +1,69 @@
+//! RPC interface quoting transaction fees in assets.
+
+use codec::Decode;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_template_runtime::{opaque::Block, AssetId, Balance, Hash};
+use pallet_asset_fees_runtime_api::{AssetFeesApi, RuntimeDispatchInfo};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::generic::BlockId;
+use std::sync::Arc;
+
+/// Quote transaction fees in the assets they can be paid in.
+#[rpc]
+pub trait AssetFeesRpcApi {
+	/// The same as `payment_queryInfo`, with the partial fee in `asset`.
+	#[rpc(name = "payment_queryInfoInAsset")]
+	fn query_info_in_asset(
+		&self,
+		extrinsic: Bytes,
+		asset: AssetId,
+		at: Option<Hash>,
+	) -> Result<RuntimeDispatchInfo<Balance>>;
+}
+
+/// Implements [`AssetFeesRpcApi`] by calling into the runtime of `client`.
+pub struct AssetFeesRpc<C> {
+	client: Arc<C>,
+}
+
+impl<C> AssetFeesRpc<C> {
+	/// Quote fees with the runtime of `client`.
+	pub fn new(client: Arc<C>) -> Self {
+		AssetFeesRpc { client }
+	}
+}
+
+fn invalid_params(message: &str) -> RpcError {
+	RpcError { code: ErrorCode::InvalidParams, message: message.into(), data: None }
+}
+
+impl<C> AssetFeesRpcApi for AssetFeesRpc<C>
+where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: AssetFeesApi<Block, Balance, AssetId>,
+{
+	fn query_info_in_asset(
+		&self,
+		extrinsic: Bytes,
+		asset: AssetId,
+		at: Option<Hash>,
+	) -> Result<RuntimeDispatchInfo<Balance>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let len = extrinsic.len() as u32;
+		let uxt = Decode::decode(&mut &*extrinsic)
+			.map_err(|e| invalid_params(&format!("Invalid extrinsic: {}", e)))?;
+
+		self.client
+			.runtime_api()
+			.query_info_in_asset(&at, uxt, len, asset)
+			.map_err(|e| RpcError {
+				code: ErrorCode::InternalError,
+				message: "Unable to query dispatch info".into(),
+				data: Some(e.to_string().into()),
+			})?
+			.ok_or_else(|| invalid_params(&format!("Fees can not be paid in asset {}", asset)))
+	}
+}
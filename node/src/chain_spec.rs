@@ -1,6 +1,7 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	pallet_template::Role, AccountId, AccountIndex, AuraConfig, BalancesConfig, GenesisConfig,
+	GrandpaConfig, IdentityRegistrarsConfig, IndicesConfig, Signature, SudoConfig, SystemConfig,
+	TemplateModuleConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -123,7 +124,7 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 }
 
 /// Configure initial storage state for FRAME modules.
-fn testnet_genesis(
+pub fn testnet_genesis(
 	wasm_binary: &[u8],
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
@@ -146,6 +147,26 @@ fn testnet_genesis(
 		grandpa: GrandpaConfig {
 			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
 		},
+		identity_registrars: IdentityRegistrarsConfig {
+			// The root key judges identities on dev chains.
+			registrars: vec![root_key.clone()],
+		},
+		indices: IndicesConfig {
+			// Endowed accounts get their position as index, e.g. Alice 0 and Bob 1.
+			indices: endowed_accounts
+				.iter()
+				.enumerate()
+				.map(|(index, account)| (index as AccountIndex, account.clone()))
+				.collect(),
+		},
+		template_module: TemplateModuleConfig {
+			// The root key administers the roles, endowed accounts may store values.
+			roles: endowed_accounts
+				.iter()
+				.map(|account| (Role::Writer, account.clone()))
+				.chain(std::iter::once((Role::Admin, root_key.clone())))
+				.collect(),
+		},
 		sudo: SudoConfig {
 			// Assign network admin rights.
 			key: root_key,
@@ -8,6 +8,22 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Index runtime events by account and topic, and serve them over the `index_*` RPCs.
+	#[structopt(long)]
+	pub enable_event_index: bool,
+
+	/// Execute the on-chain Wasm runtime only, never the native runtime. Nodes built with the
+	/// `wasm-only` feature always do.
+	#[structopt(long)]
+	pub wasm_only: bool,
+
+	/// Limit the transactions of a priority lane in the pool, as `<LANE>=<READY>[:<FUTURE>]`
+	/// with the lanes `default`, `template`, `contract` and `oracle`. Without `<FUTURE>` a
+	/// tenth of `<READY>` future transactions are kept. Can be given once per lane, the limits
+	/// apply within the limits of the whole pool.
+	#[structopt(long, value_name = "LANE=READY[:FUTURE]", number_of_values = 1)]
+	pub pool_lane_limit: Vec<crate::lanes::LaneLimit>,
 }
 
 #[derive(Debug, StructOpt)]
@@ -35,7 +51,32 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Decode and print blocks, extrinsics, events and storage of the local database.
+	Inspect(crate::inspect::InspectCmd),
+
+	/// Build, sign and verify extrinsics offline.
+	#[structopt(subcommand)]
+	Tx(crate::tx::TxCmd),
+
+	/// Check a compiled runtime and build the extrinsic upgrading the chain to it.
+	RuntimeUpgrade(crate::runtime_upgrade::RuntimeUpgradeCmd),
+
+	/// Compare the metadata of two runtimes and check whether the transaction version has to be
+	/// bumped.
+	MetadataDiff(crate::metadata_diff::MetadataDiffCmd),
+
 	/// The custom benchmark subcommand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Try some command against runtime state.
+	#[cfg(feature = "try-runtime")]
+	TryRuntime(try_runtime_cli::TryRuntimeCmd),
+
+	/// Try some command against runtime state. Note: `try-runtime` feature must be enabled.
+	#[cfg(not(feature = "try-runtime"))]
+	TryRuntime,
+
+	/// Convert state written by `export-state` into a snapshot for `try-runtime`.
+	StateToSnapshot(crate::snapshot::StateToSnapshotCmd),
 }
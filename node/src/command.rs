@@ -1,10 +1,11 @@
 use crate::{
 	chain_spec,
 	cli::{Cli, Subcommand},
-	service,
+	fork, service,
 };
 use node_template_runtime::Block;
 use sc_cli::{ChainSpec, Role, RuntimeVersion, SubstrateCli};
+use sc_client_api::{execution_extensions::ExecutionStrategies, ExecutionStrategy};
 use sc_service::PartialComponents;
 
 impl SubstrateCli for Cli {
@@ -36,8 +37,11 @@ impl SubstrateCli for Cli {
 		Ok(match id {
 			"dev" => Box::new(chain_spec::development_config()?),
 			"" | "local" => Box::new(chain_spec::local_testnet_config()?),
-			path =>
-				Box::new(chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(path))?),
+			id => match id.strip_prefix("fork:") {
+				Some(state) => Box::new(fork::fork_config(std::path::Path::new(state))?),
+				None =>
+					Box::new(chain_spec::ChainSpec::from_json_file(std::path::PathBuf::from(id))?),
+			},
 		})
 	}
 
@@ -98,22 +102,78 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, backend), task_manager))
 			})
 		},
-		Some(Subcommand::Benchmark(cmd)) =>
-			if cfg!(feature = "runtime-benchmarks") {
-				let runner = cli.create_runner(cmd)?;
+		Some(Subcommand::Inspect(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| {
+				let PartialComponents { client, .. } = service::new_partial(&config)?;
+				cmd.run(client)
+			})
+		},
+		Some(Subcommand::Tx(cmd)) => cmd.run(),
+		Some(Subcommand::RuntimeUpgrade(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents { client, task_manager, .. } = service::new_partial(&config)?;
+				Ok((cmd.run(client, config.wasm_method), task_manager))
+			})
+		},
+		Some(Subcommand::MetadataDiff(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let registry = config.prometheus_config.as_ref().map(|cfg| &cfg.registry);
+				let task_manager =
+					sc_service::TaskManager::new(config.tokio_handle.clone(), registry)
+						.map_err(|e| sc_cli::Error::Service(sc_service::Error::Prometheus(e)))?;
+				Ok((cmd.run(config.wasm_method), task_manager))
+			})
+		},
+		#[cfg(feature = "runtime-benchmarks")]
+		Some(Subcommand::Benchmark(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
 
-				runner.sync_run(|config| cmd.run::<Block, service::ExecutorDispatch>(config))
-			} else {
-				Err("Benchmarking wasn't enabled when building the node. You can enable it with \
-				     `--features runtime-benchmarks`."
-					.into())
-			},
+			runner.sync_run(|config| cmd.run::<Block, service::ExecutorDispatch>(config))
+		},
+		#[cfg(not(feature = "runtime-benchmarks"))]
+		Some(Subcommand::Benchmark(_)) =>
+			Err("Benchmarking wasn't enabled when building the node. You can enable it with \
+			     `--features runtime-benchmarks`."
+				.into()),
+		#[cfg(feature = "try-runtime")]
+		Some(Subcommand::TryRuntime(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				// we don't need any of the components of new_partial, just a runtime, or a task
+				// manager to do `async_run`.
+				let registry = config.prometheus_config.as_ref().map(|cfg| &cfg.registry);
+				let task_manager =
+					sc_service::TaskManager::new(config.tokio_handle.clone(), registry)
+						.map_err(|e| sc_cli::Error::Service(sc_service::Error::Prometheus(e)))?;
+				Ok((cmd.run::<Block, service::ExecutorDispatch>(config), task_manager))
+			})
+		},
+		#[cfg(not(feature = "try-runtime"))]
+		Some(Subcommand::TryRuntime) => Err("TryRuntime wasn't enabled when building the node. \
+				You can enable it with `--features try-runtime`."
+			.into()),
+		Some(Subcommand::StateToSnapshot(cmd)) => cmd.run(),
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
-			runner.run_node_until_exit(|config| async move {
+			let enable_event_index = cli.enable_event_index;
+			let wasm_only = cli.wasm_only;
+			let lane_limits = cli.pool_lane_limit.clone();
+			runner.run_node_until_exit(|mut config| async move {
+				if wasm_only {
+					config.execution_strategies = ExecutionStrategies {
+						syncing: ExecutionStrategy::AlwaysWasm,
+						importing: ExecutionStrategy::AlwaysWasm,
+						block_construction: ExecutionStrategy::AlwaysWasm,
+						offchain_worker: ExecutionStrategy::AlwaysWasm,
+						other: ExecutionStrategy::AlwaysWasm,
+					};
+				}
 				match config.role {
 					Role::Light => service::new_light(config),
-					_ => service::new_full(config),
+					_ => service::new_full(config, enable_event_index, &lane_limits, wasm_only),
 				}
 				.map_err(sc_cli::Error::Service)
 			})
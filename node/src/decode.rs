@@ -0,0 +1,756 @@
+//! Metadata-driven decoding and encoding of SCALE encoded runtime values.
+//!
+//! The node is compiled against a single version of the runtime, but the chain it follows may have
+//! been upgraded any number of times. Everything in here only relies on the `scale-info` type
+//! registry shipped in the runtime metadata, so it keeps working for blocks produced by runtimes
+//! that the node binary has never seen.
+
+use codec::{Compact, Decode, Encode};
+use frame_metadata::{
+	ExtrinsicMetadata, PalletMetadata, RuntimeMetadata, RuntimeMetadataPrefixed,
+	StorageEntryMetadata, StorageEntryType, StorageHasher,
+};
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef, TypeDefPrimitive, Variant};
+use serde_json::{json, Map, Value as Json};
+use sp_core::{
+	crypto::{AccountId32, Ss58Codec},
+	hashing::{blake2_128, blake2_256, twox_128, twox_256, twox_64},
+};
+use std::convert::{TryFrom, TryInto};
+
+/// A decoded SCALE value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	/// A struct or tuple struct. Unnamed fields have no name.
+	Composite(Vec<(Option<String>, Value)>),
+	/// One variant of an enum.
+	Variant {
+		name: String,
+		fields: Vec<(Option<String>, Value)>,
+	},
+	/// A vector, array or tuple.
+	Sequence(Vec<Value>),
+	/// A vector or array of bytes.
+	Bytes(Vec<u8>),
+	/// An account id, recognised by its type path.
+	Account(AccountId32),
+	Bool(bool),
+	Char(char),
+	Str(String),
+	Unsigned(u128),
+	Signed(i128),
+	/// 256 bit integers are kept as their raw little endian encoding.
+	Wide(Vec<u8>),
+}
+
+impl Value {
+	/// Look up a named field of a composite or variant.
+	pub fn field(&self, name: &str) -> Option<&Value> {
+		match self {
+			Value::Composite(fields) | Value::Variant { fields, .. } =>
+				fields.iter().find(|(n, _)| n.as_deref() == Some(name)).map(|(_, value)| value),
+			_ => None,
+		}
+	}
+
+	/// Collect every account id that appears anywhere inside of this value.
+	pub fn accounts(&self, out: &mut Vec<AccountId32>) {
+		match self {
+			Value::Account(who) =>
+				if !out.contains(who) {
+					out.push(who.clone())
+				},
+			Value::Composite(fields) | Value::Variant { fields, .. } =>
+				fields.iter().for_each(|(_, value)| value.accounts(out)),
+			Value::Sequence(values) => values.iter().for_each(|value| value.accounts(out)),
+			_ => {},
+		}
+	}
+
+	/// Render the value as JSON for human and machine consumption.
+	pub fn to_json(&self) -> Json {
+		fn fields_to_json(fields: &[(Option<String>, Value)]) -> Json {
+			if fields.iter().all(|(name, _)| name.is_some()) && !fields.is_empty() {
+				let mut map = Map::new();
+				for (name, value) in fields {
+					map.insert(name.clone().unwrap_or_default(), value.to_json());
+				}
+				Json::Object(map)
+			} else {
+				Json::Array(fields.iter().map(|(_, value)| value.to_json()).collect())
+			}
+		}
+
+		match self {
+			Value::Composite(fields) => fields_to_json(fields),
+			Value::Variant { name, fields } if fields.is_empty() => json!(name),
+			Value::Variant { name, fields } => json!({ name: fields_to_json(fields) }),
+			Value::Sequence(values) => Json::Array(values.iter().map(Value::to_json).collect()),
+			Value::Bytes(bytes) | Value::Wide(bytes) => json!(format!("0x{}", hex::encode(bytes))),
+			Value::Account(who) => json!(who.to_string()),
+			Value::Bool(b) => json!(b),
+			Value::Char(c) => json!(c),
+			Value::Str(s) => json!(s),
+			// Large numbers are printed as strings, JavaScript can not represent them.
+			Value::Unsigned(n) if *n > u64::MAX as u128 => json!(n.to_string()),
+			Value::Unsigned(n) => json!(*n as u64),
+			Value::Signed(n) if *n > i64::MAX as i128 || *n < i64::MIN as i128 =>
+				json!(n.to_string()),
+			Value::Signed(n) => json!(*n as i64),
+		}
+	}
+}
+
+/// The runtime metadata of one runtime version, ready for decoding.
+pub struct Metadata {
+	registry: PortableRegistry,
+	pallets: Vec<PalletMetadata<PortableForm>>,
+	extrinsic: ExtrinsicMetadata<PortableForm>,
+}
+
+impl Metadata {
+	/// Parse the output of the `Metadata_metadata` runtime api.
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+		let prefixed = RuntimeMetadataPrefixed::decode(&mut &bytes[..])
+			.map_err(|e| format!("Invalid runtime metadata: {}", e))?;
+		match prefixed.1 {
+			RuntimeMetadata::V14(metadata) => Ok(Metadata {
+				registry: metadata.types,
+				pallets: metadata.pallets,
+				extrinsic: metadata.extrinsic,
+			}),
+			_ => Err("Only V14 runtime metadata is supported".into()),
+		}
+	}
+
+	/// The pallets of the runtime.
+	pub fn pallets(&self) -> &[PalletMetadata<PortableForm>] {
+		&self.pallets
+	}
+
+	/// The format of the extrinsics of the runtime.
+	pub fn extrinsic(&self) -> &ExtrinsicMetadata<PortableForm> {
+		&self.extrinsic
+	}
+
+	/// The variants of the enum `ty`, none if `ty` is no enum.
+	pub fn variants(&self, ty: u32) -> &[Variant<PortableForm>] {
+		match self.registry.resolve(ty).map(|ty| ty.type_def()) {
+			Some(TypeDef::Variant(variant)) => variant.variants(),
+			_ => &[],
+		}
+	}
+
+	/// Describe how values of type `ty` are encoded.
+	///
+	/// Two types with the same signature have the same encoding, even if they are from different
+	/// registries. Names of types and fields don't show up in signatures as they are not encoded,
+	/// variant names do to tell variants apart.
+	pub fn type_signature(&self, ty: u32) -> String {
+		self.signature(ty, &mut Vec::new())
+	}
+
+	fn signature(&self, id: u32, stack: &mut Vec<u32>) -> String {
+		let ty = match self.registry.resolve(id) {
+			Some(ty) => ty,
+			None => return format!("<unknown {}>", id),
+		};
+		// Recursive types, e.g. the runtime `Call` in `sudo`, are only described once.
+		if stack.contains(&id) {
+			return format!("<{}>", ty.path().segments().join("::"))
+		}
+		stack.push(id);
+
+		let fields = |this: &Self, fields: &[Field<PortableForm>], stack: &mut Vec<u32>| {
+			fields
+				.iter()
+				.map(|field| this.signature(field.ty().id(), stack))
+				.collect::<Vec<_>>()
+		};
+		let signature = match ty.type_def() {
+			TypeDef::Composite(composite) =>
+				format!("({})", fields(self, composite.fields(), stack).join(", ")),
+			TypeDef::Variant(variant) => {
+				let variants = variant
+					.variants()
+					.iter()
+					.map(|v| {
+						format!(
+							"{} {}({})",
+							v.index(),
+							v.name(),
+							fields(self, v.fields(), stack).join(", ")
+						)
+					})
+					.collect::<Vec<_>>();
+				format!("enum {{ {} }}", variants.join(", "))
+			},
+			TypeDef::Sequence(sequence) =>
+				format!("[{}]", self.signature(sequence.type_param().id(), stack)),
+			TypeDef::Array(array) =>
+				format!("[{}; {}]", self.signature(array.type_param().id(), stack), array.len()),
+			TypeDef::Tuple(tuple) => format!(
+				"({})",
+				tuple
+					.fields()
+					.iter()
+					.map(|ty| self.signature(ty.id(), stack))
+					.collect::<Vec<_>>()
+					.join(", ")
+			),
+			TypeDef::Primitive(primitive) => format!("{:?}", primitive).to_lowercase(),
+			TypeDef::Compact(compact) =>
+				format!("Compact<{}>", self.signature(compact.type_param().id(), stack)),
+			TypeDef::BitSequence(bits) => format!(
+				"BitVec<{}, {}>",
+				self.signature(bits.bit_store_type().id(), stack),
+				self.signature(bits.bit_order_type().id(), stack)
+			),
+		};
+
+		stack.pop();
+		signature
+	}
+
+	/// Find a storage item by pallet and item name.
+	pub fn storage_entry(
+		&self,
+		pallet: &str,
+		item: &str,
+	) -> Option<&StorageEntryMetadata<PortableForm>> {
+		self.pallets
+			.iter()
+			.find(|p| p.name == pallet)
+			.and_then(|p| p.storage.as_ref())
+			.and_then(|storage| storage.entries.iter().find(|entry| entry.name == item))
+	}
+
+	/// Decode the value of a plain storage item, e.g. `System::Events`.
+	pub fn decode_storage_value(
+		&self,
+		pallet: &str,
+		item: &str,
+		bytes: &[u8],
+	) -> Result<Value, String> {
+		let entry = self
+			.storage_entry(pallet, item)
+			.ok_or_else(|| format!("No storage item {}::{} in metadata", pallet, item))?;
+		let ty = match &entry.ty {
+			StorageEntryType::Plain(ty) => ty.id(),
+			StorageEntryType::Map { value, .. } => value.id(),
+		};
+		self.decode(ty, &mut &bytes[..])
+	}
+
+	/// Compute the storage key of an entry from the SCALE encoded `keys`, one for each hasher of
+	/// the item. Plain items take no keys, leaving some keys out results in a key prefix.
+	pub fn storage_key(
+		&self,
+		pallet: &str,
+		item: &str,
+		keys: &[Vec<u8>],
+	) -> Result<Vec<u8>, String> {
+		let entry = self
+			.storage_entry(pallet, item)
+			.ok_or_else(|| format!("No storage item {}::{} in metadata", pallet, item))?;
+		let hashers = match &entry.ty {
+			StorageEntryType::Plain(_) => &[][..],
+			StorageEntryType::Map { hashers, .. } => &hashers[..],
+		};
+		if keys.len() > hashers.len() {
+			return Err(format!("{}::{} takes {} keys", pallet, item, hashers.len()))
+		}
+
+		let mut storage_key = storage_prefix(pallet, item);
+		for (hasher, key) in hashers.iter().zip(keys) {
+			storage_key.extend(hash(hasher, key));
+		}
+		Ok(storage_key)
+	}
+
+	/// Decode the keys of a map entry from its full storage `key`.
+	///
+	/// Keys hashed without their plain value appended can not be recovered, their hash is returned
+	/// as bytes instead.
+	pub fn decode_storage_key(
+		&self,
+		pallet: &str,
+		item: &str,
+		key: &[u8],
+	) -> Result<Vec<Value>, String> {
+		let entry = self
+			.storage_entry(pallet, item)
+			.ok_or_else(|| format!("No storage item {}::{} in metadata", pallet, item))?;
+		let (hashers, key_ty) = match &entry.ty {
+			StorageEntryType::Plain(_) => return Ok(Vec::new()),
+			StorageEntryType::Map { hashers, key, .. } => (hashers, key.id()),
+		};
+		// Maps with several hashers have a tuple of keys.
+		let key_tys = match self.registry.resolve(key_ty).map(|ty| ty.type_def()) {
+			Some(TypeDef::Tuple(tuple)) if hashers.len() > 1 =>
+				tuple.fields().iter().map(|ty| ty.id()).collect(),
+			_ => vec![key_ty],
+		};
+
+		let input =
+			&mut key.get(32..).ok_or_else(|| format!("Key is not in {}::{}", pallet, item))?;
+		hashers
+			.iter()
+			.zip(key_tys)
+			.map(|(hasher, ty)| {
+				let (hash_len, concat) = match hasher {
+					StorageHasher::Blake2_128 | StorageHasher::Twox128 => (16, false),
+					StorageHasher::Blake2_256 | StorageHasher::Twox256 => (32, false),
+					StorageHasher::Blake2_128Concat => (16, true),
+					StorageHasher::Twox64Concat => (8, true),
+					StorageHasher::Identity => (0, true),
+				};
+				if input.len() < hash_len {
+					return Err("Key is too short".to_string())
+				}
+				let (hash, rest) = input.split_at(hash_len);
+				*input = rest;
+				if concat {
+					self.decode(ty, input)
+				} else {
+					Ok(Value::Bytes(hash.to_vec()))
+				}
+			})
+			.collect()
+	}
+
+	/// Decode a call of the runtime, returns the name of the pallet and the call.
+	pub fn decode_call(&self, input: &mut &[u8]) -> Result<(String, Value), String> {
+		let index = u8::decode(input).map_err(|e| e.to_string())?;
+		let pallet = self
+			.pallets
+			.iter()
+			.find(|pallet| pallet.index == index)
+			.ok_or_else(|| format!("Unknown pallet index {}", index))?;
+		let calls = pallet.calls.as_ref().ok_or_else(|| format!("{} has no calls", pallet.name))?;
+		Ok((pallet.name.clone(), self.decode(calls.ty.id(), input)?))
+	}
+
+	/// Decode the signed extensions of a signed extrinsic, by their identifier.
+	pub fn decode_signed_extensions(
+		&self,
+		input: &mut &[u8],
+	) -> Result<Vec<(String, Value)>, String> {
+		self.extrinsic
+			.signed_extensions
+			.iter()
+			.map(|extension| {
+				Ok((extension.identifier.clone(), self.decode(extension.ty.id(), input)?))
+			})
+			.collect()
+	}
+
+	/// Decode a value of type `ty` from `input`.
+	pub fn decode(&self, ty: u32, input: &mut &[u8]) -> Result<Value, String> {
+		let ty = self
+			.registry
+			.resolve(ty)
+			.ok_or_else(|| format!("Type {} not found in the registry", ty))?;
+
+		if ty.path().segments().last().map(String::as_str) == Some("AccountId32") {
+			return AccountId32::decode(input).map(Value::Account).map_err(|e| e.to_string())
+		}
+
+		match ty.type_def() {
+			TypeDef::Composite(composite) =>
+				self.decode_fields(composite.fields(), input).map(Value::Composite),
+			TypeDef::Variant(variant) => {
+				let index = u8::decode(input).map_err(|e| e.to_string())?;
+				let variant = variant
+					.variants()
+					.iter()
+					.find(|v| v.index() == index)
+					.ok_or_else(|| format!("Unknown variant index {}", index))?;
+				self.decode_variant(variant, input)
+			},
+			TypeDef::Sequence(sequence) => {
+				let len = Compact::<u32>::decode(input).map_err(|e| e.to_string())?.0;
+				self.decode_sequence(sequence.type_param().id(), len as usize, input)
+			},
+			TypeDef::Array(array) =>
+				self.decode_sequence(array.type_param().id(), array.len() as usize, input),
+			TypeDef::Tuple(tuple) => tuple
+				.fields()
+				.iter()
+				.map(|ty| self.decode(ty.id(), input))
+				.collect::<Result<_, _>>()
+				.map(Value::Sequence),
+			TypeDef::Primitive(primitive) => decode_primitive(primitive, input),
+			TypeDef::Compact(compact) => self.decode_compact(compact.type_param().id(), input),
+			TypeDef::BitSequence(_) => Err("Bit sequences are not supported".into()),
+		}
+	}
+
+	fn decode_fields(
+		&self,
+		fields: &[Field<PortableForm>],
+		input: &mut &[u8],
+	) -> Result<Vec<(Option<String>, Value)>, String> {
+		fields
+			.iter()
+			.map(|field| Ok((field.name().cloned(), self.decode(field.ty().id(), input)?)))
+			.collect()
+	}
+
+	fn decode_variant(
+		&self,
+		variant: &Variant<PortableForm>,
+		input: &mut &[u8],
+	) -> Result<Value, String> {
+		Ok(Value::Variant {
+			name: variant.name().clone(),
+			fields: self.decode_fields(variant.fields(), input)?,
+		})
+	}
+
+	fn decode_sequence(&self, ty: u32, len: usize, input: &mut &[u8]) -> Result<Value, String> {
+		let is_bytes = matches!(
+			self.registry.resolve(ty).map(|t| t.type_def()),
+			Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+		);
+		if is_bytes {
+			if input.len() < len {
+				return Err("Not enough data to fill the byte sequence".into())
+			}
+			let (bytes, rest) = input.split_at(len);
+			*input = rest;
+			return Ok(Value::Bytes(bytes.to_vec()))
+		}
+		(0..len)
+			.map(|_| self.decode(ty, input))
+			.collect::<Result<_, _>>()
+			.map(Value::Sequence)
+	}
+
+	fn decode_compact(&self, ty: u32, input: &mut &[u8]) -> Result<Value, String> {
+		let inner = self
+			.registry
+			.resolve(ty)
+			.ok_or_else(|| format!("Type {} not found in the registry", ty))?;
+		match inner.type_def() {
+			// Compact wrappers such as `Compact<Perbill>` encode their single field compactly.
+			TypeDef::Composite(composite) if composite.fields().len() == 1 => {
+				let field = &composite.fields()[0];
+				Ok(Value::Composite(vec![(
+					field.name().cloned(),
+					self.decode_compact(field.ty().id(), input)?,
+				)]))
+			},
+			TypeDef::Primitive(_) => Compact::<u128>::decode(input)
+				.map(|c| Value::Unsigned(c.0))
+				.map_err(|e| e.to_string()),
+			_ => Err(format!("Type {} can not be compact encoded", ty)),
+		}
+	}
+
+	/// Encode a call of the runtime from JSON arguments, e.g. `{"dest": {"Id": "5Grw..."},
+	/// "value": 1000}` for `Balances::transfer`. See [`Metadata::encode`] for the JSON format.
+	pub fn encode_call(&self, pallet: &str, call: &str, args: &Json) -> Result<Vec<u8>, String> {
+		let pallet = self
+			.pallets
+			.iter()
+			.find(|p| p.name == pallet)
+			.ok_or_else(|| format!("No pallet {} in metadata", pallet))?;
+		let calls = pallet.calls.as_ref().ok_or_else(|| format!("{} has no calls", pallet.name))?;
+		let variant = self
+			.variants(calls.ty.id())
+			.iter()
+			.find(|v| v.name() == call)
+			.ok_or_else(|| format!("No call {}::{} in metadata", pallet.name, call))?;
+
+		let mut out = vec![pallet.index, variant.index()];
+		self.encode_fields(variant.fields(), args, &mut out)
+			.map_err(|e| format!("{}::{}: {}", pallet.name, call, e))?;
+		Ok(out)
+	}
+
+	/// Encode a JSON value as type `ty`, the inverse of [`Value::to_json`].
+	///
+	/// Composites take an object of their fields or an array, enums the name of a variant without
+	/// fields or an object with the name of the variant as its single key. Account ids take an
+	/// SS58 address, byte sequences `0x` hex or a plain string and large numbers a string.
+	/// Enums with an `Id` variant, like `MultiAddress`, also take the value of that variant, and
+	/// enums with an `Index` variant take a number as the value of that one.
+	pub fn encode(&self, ty: u32, json: &Json, out: &mut Vec<u8>) -> Result<(), String> {
+		let ty_id = ty;
+		let ty = self
+			.registry
+			.resolve(ty)
+			.ok_or_else(|| format!("Type {} not found in the registry", ty))?;
+
+		if ty.path().segments().last().map(String::as_str) == Some("AccountId32") {
+			let account = json.as_str().ok_or("Expected an account id")?;
+			let account = match account.strip_prefix("0x") {
+				Some(hex) => {
+					let bytes = hex::decode(hex).map_err(|e| e.to_string())?;
+					AccountId32::new(bytes.try_into().map_err(|_| "Account ids are 32 bytes")?)
+				},
+				None => AccountId32::from_ss58check(account)
+					.map_err(|e| format!("Invalid address {}: {:?}", account, e))?,
+			};
+			account.encode_to(out);
+			return Ok(())
+		}
+
+		match ty.type_def() {
+			TypeDef::Composite(composite) => self.encode_fields(composite.fields(), json, out),
+			TypeDef::Variant(variant) => {
+				let variants = variant.variants();
+				let (name, fields) = match json {
+					Json::String(name) => (name.as_str(), &Json::Null),
+					Json::Object(map) if map.len() == 1 =>
+						map.iter().next().map(|(name, fields)| (name.as_str(), fields)).unwrap(),
+					Json::Number(_) if variants.iter().any(|v| v.name() == "Index") =>
+						("Index", json),
+					_ => ("Id", json),
+				};
+				let variant = match variants.iter().find(|v| v.name() == name) {
+					Some(variant) => variant,
+					// Not a variant, but maybe the value of the `Id` variant.
+					None if name != "Id" && variants.iter().any(|v| v.name() == "Id") =>
+						return self.encode(ty_id, &json!({ "Id": json }), out),
+					None => return Err(format!("Unknown variant {}", name)),
+				};
+				out.push(variant.index());
+				self.encode_fields(variant.fields(), fields, out)
+			},
+			TypeDef::Sequence(sequence) => {
+				let ty = sequence.type_param().id();
+				if let Some(bytes) = self.bytes(ty, json)? {
+					bytes.encode_to(out);
+					return Ok(())
+				}
+				let items = json.as_array().ok_or("Expected an array")?;
+				Compact(items.len() as u32).encode_to(out);
+				items.iter().try_for_each(|item| self.encode(ty, item, out))
+			},
+			TypeDef::Array(array) => {
+				let ty = array.type_param().id();
+				let len = array.len() as usize;
+				if let Some(bytes) = self.bytes(ty, json)? {
+					if bytes.len() != len {
+						return Err(format!("Expected {} bytes", len))
+					}
+					out.extend(bytes);
+					return Ok(())
+				}
+				let items = json.as_array().filter(|items| items.len() == len);
+				let items = items.ok_or_else(|| format!("Expected an array of {} items", len))?;
+				items.iter().try_for_each(|item| self.encode(ty, item, out))
+			},
+			TypeDef::Tuple(tuple) => {
+				let items = match json {
+					Json::Array(items) => &items[..],
+					Json::Null => &[],
+					single => std::slice::from_ref(single),
+				};
+				if items.len() != tuple.fields().len() {
+					return Err(format!("Expected a tuple of {} items", tuple.fields().len()))
+				}
+				tuple
+					.fields()
+					.iter()
+					.zip(items)
+					.try_for_each(|(ty, item)| self.encode(ty.id(), item, out))
+			},
+			TypeDef::Primitive(primitive) => encode_primitive(primitive, json, out),
+			TypeDef::Compact(compact) => self.encode_compact(compact.type_param().id(), json, out),
+			TypeDef::BitSequence(_) => Err("Bit sequences are not supported".into()),
+		}
+	}
+
+	fn encode_fields(
+		&self,
+		fields: &[Field<PortableForm>],
+		json: &Json,
+		out: &mut Vec<u8>,
+	) -> Result<(), String> {
+		match json {
+			Json::Object(map)
+				if fields.iter().all(|field| field.name().is_some()) &&
+					(fields.len() != 1 || map.contains_key(fields[0].name().unwrap())) =>
+				fields.iter().try_for_each(|field| {
+					let name = field.name().expect("all fields are named; qed");
+					let value = map.get(name).ok_or_else(|| format!("Missing field {}", name))?;
+					self.encode(field.ty().id(), value, out).map_err(|e| format!("{}: {}", name, e))
+				}),
+			Json::Array(items) if items.len() == fields.len() => {
+				let mut positional = Vec::new();
+				let result = fields.iter().zip(items).try_for_each(|(field, item)| {
+					self.encode(field.ty().id(), item, &mut positional)
+				});
+				match result {
+					Ok(()) => {
+						out.extend(positional);
+						Ok(())
+					},
+					// A single field may be a sequence itself, e.g. of a `BoundedVec`.
+					Err(_) if fields.len() == 1 => self.encode(fields[0].ty().id(), json, out),
+					Err(e) => Err(e),
+				}
+			},
+			// Newtypes such as `H256` also take their single field directly.
+			single if fields.len() == 1 => self.encode(fields[0].ty().id(), single, out),
+			Json::Null if fields.is_empty() => Ok(()),
+			_ => Err(format!("Expected {} fields", fields.len())),
+		}
+	}
+
+	/// The bytes of a byte sequence given as `0x` hex or as a string, `None` for other sequences.
+	fn bytes(&self, ty: u32, json: &Json) -> Result<Option<Vec<u8>>, String> {
+		let is_bytes = matches!(
+			self.registry.resolve(ty).map(|t| t.type_def()),
+			Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+		);
+		match json {
+			Json::String(s) if is_bytes => match s.strip_prefix("0x") {
+				Some(hex) => hex::decode(hex).map(Some).map_err(|e| e.to_string()),
+				None => Ok(Some(s.as_bytes().to_vec())),
+			},
+			_ => Ok(None),
+		}
+	}
+
+	fn encode_compact(&self, ty: u32, json: &Json, out: &mut Vec<u8>) -> Result<(), String> {
+		let inner = self
+			.registry
+			.resolve(ty)
+			.ok_or_else(|| format!("Type {} not found in the registry", ty))?;
+		match inner.type_def() {
+			TypeDef::Composite(composite) if composite.fields().len() == 1 => {
+				let field = &composite.fields()[0];
+				let json = match (json, field.name()) {
+					(Json::Object(map), Some(name)) => map.get(name).unwrap_or(json),
+					_ => json,
+				};
+				self.encode_compact(field.ty().id(), json, out)
+			},
+			TypeDef::Primitive(_) => {
+				Compact(unsigned(json)?).encode_to(out);
+				Ok(())
+			},
+			_ => Err(format!("Type {} can not be compact encoded", ty)),
+		}
+	}
+}
+
+fn decode_primitive(primitive: &TypeDefPrimitive, input: &mut &[u8]) -> Result<Value, String> {
+	fn decode<T: Decode>(input: &mut &[u8]) -> Result<T, String> {
+		T::decode(input).map_err(|e| e.to_string())
+	}
+
+	Ok(match primitive {
+		TypeDefPrimitive::Bool => Value::Bool(decode(input)?),
+		TypeDefPrimitive::Char => {
+			let c: u32 = decode(input)?;
+			Value::Char(std::char::from_u32(c).ok_or("Invalid char")?)
+		},
+		TypeDefPrimitive::Str => Value::Str(decode(input)?),
+		TypeDefPrimitive::U8 => Value::Unsigned(decode::<u8>(input)?.into()),
+		TypeDefPrimitive::U16 => Value::Unsigned(decode::<u16>(input)?.into()),
+		TypeDefPrimitive::U32 => Value::Unsigned(decode::<u32>(input)?.into()),
+		TypeDefPrimitive::U64 => Value::Unsigned(decode::<u64>(input)?.into()),
+		TypeDefPrimitive::U128 => Value::Unsigned(decode(input)?),
+		TypeDefPrimitive::I8 => Value::Signed(decode::<i8>(input)?.into()),
+		TypeDefPrimitive::I16 => Value::Signed(decode::<i16>(input)?.into()),
+		TypeDefPrimitive::I32 => Value::Signed(decode::<i32>(input)?.into()),
+		TypeDefPrimitive::I64 => Value::Signed(decode::<i64>(input)?.into()),
+		TypeDefPrimitive::I128 => Value::Signed(decode(input)?),
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 =>
+			Value::Wide(decode::<[u8; 32]>(input)?.to_vec()),
+	})
+}
+
+fn encode_primitive(
+	primitive: &TypeDefPrimitive,
+	json: &Json,
+	out: &mut Vec<u8>,
+) -> Result<(), String> {
+	fn int<T: TryFrom<i128> + Encode>(json: &Json, out: &mut Vec<u8>) -> Result<(), String> {
+		let value = signed(json)?;
+		T::try_from(value)
+			.map_err(|_| format!("{} is out of range", value))?
+			.encode_to(out);
+		Ok(())
+	}
+	fn uint<T: TryFrom<u128> + Encode>(json: &Json, out: &mut Vec<u8>) -> Result<(), String> {
+		let value = unsigned(json)?;
+		T::try_from(value)
+			.map_err(|_| format!("{} is out of range", value))?
+			.encode_to(out);
+		Ok(())
+	}
+
+	match primitive {
+		TypeDefPrimitive::Bool => json.as_bool().ok_or("Expected a bool")?.encode_to(out),
+		TypeDefPrimitive::Char => {
+			let mut chars = json.as_str().ok_or("Expected a char")?.chars();
+			match (chars.next(), chars.next()) {
+				(Some(c), None) => (c as u32).encode_to(out),
+				_ => return Err("Expected a single char".into()),
+			}
+		},
+		TypeDefPrimitive::Str => json.as_str().ok_or("Expected a string")?.encode_to(out),
+		TypeDefPrimitive::U8 => uint::<u8>(json, out)?,
+		TypeDefPrimitive::U16 => uint::<u16>(json, out)?,
+		TypeDefPrimitive::U32 => uint::<u32>(json, out)?,
+		TypeDefPrimitive::U64 => uint::<u64>(json, out)?,
+		TypeDefPrimitive::U128 => uint::<u128>(json, out)?,
+		TypeDefPrimitive::I8 => int::<i8>(json, out)?,
+		TypeDefPrimitive::I16 => int::<i16>(json, out)?,
+		TypeDefPrimitive::I32 => int::<i32>(json, out)?,
+		TypeDefPrimitive::I64 => int::<i64>(json, out)?,
+		TypeDefPrimitive::I128 => int::<i128>(json, out)?,
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => {
+			let hex = json.as_str().and_then(|s| s.strip_prefix("0x"));
+			let bytes = hex::decode(hex.ok_or("Expected 0x hex")?).map_err(|e| e.to_string())?;
+			if bytes.len() != 32 {
+				return Err("Expected 32 little endian bytes".into())
+			}
+			out.extend(bytes);
+		},
+	}
+	Ok(())
+}
+
+/// A number, given as a JSON number or, for numbers beyond `u64`, as a string.
+fn unsigned(json: &Json) -> Result<u128, String> {
+	match json {
+		Json::Number(n) => n
+			.as_u64()
+			.map(Into::into)
+			.ok_or_else(|| format!("{} is no unsigned integer", n)),
+		Json::String(s) => s.parse().map_err(|e| format!("Invalid number {}: {}", s, e)),
+		_ => Err("Expected a number".into()),
+	}
+}
+
+fn signed(json: &Json) -> Result<i128, String> {
+	match json {
+		Json::Number(n) => n.as_i64().map(Into::into).ok_or_else(|| format!("{} is no integer", n)),
+		Json::String(s) => s.parse().map_err(|e| format!("Invalid number {}: {}", s, e)),
+		_ => Err("Expected a number".into()),
+	}
+}
+
+fn hash(hasher: &StorageHasher, key: &[u8]) -> Vec<u8> {
+	match hasher {
+		StorageHasher::Blake2_128 => blake2_128(key).to_vec(),
+		StorageHasher::Blake2_256 => blake2_256(key).to_vec(),
+		StorageHasher::Blake2_128Concat => [&blake2_128(key)[..], key].concat(),
+		StorageHasher::Twox128 => twox_128(key).to_vec(),
+		StorageHasher::Twox256 => twox_256(key).to_vec(),
+		StorageHasher::Twox64Concat => [&twox_64(key)[..], key].concat(),
+		StorageHasher::Identity => key.to_vec(),
+	}
+}
+
+/// The storage key prefix of a plain storage item or of all entries of a map.
+pub fn storage_prefix(pallet: &str, item: &str) -> Vec<u8> {
+	[twox_128(pallet.as_bytes()), twox_128(item.as_bytes())].concat()
+}
@@ -0,0 +1,313 @@
+//! An off-chain index of runtime events by account and topic.
+//!
+//! The indexer follows finality, so it never has to undo anything because of a reorg. Events are
+//! decoded through the runtime metadata of the block they were emitted in, which keeps the index
+//! working across runtime upgrades. Every account id that appears anywhere in an event, e.g. the
+//! `who` of `SomethingStored`, makes the event show up for that account.
+
+pub mod rpc;
+
+use crate::decode::{storage_prefix, Metadata, Value};
+use futures::StreamExt;
+use kvdb::KeyValueDB;
+use node_template_runtime::{opaque::Block, AccountId, BlockNumber, Hash};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_api::{Core, Metadata as MetadataApi, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::storage::StorageKey;
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+use std::{io, marker::PhantomData, path::Path, sync::Arc};
+
+const COL_META: u32 = 0;
+const COL_EVENTS: u32 = 1;
+const COL_ACCOUNTS: u32 = 2;
+const COL_TOPICS: u32 = 3;
+const NUM_COLUMNS: u32 = 4;
+
+const LAST_INDEXED: &[u8] = b"last_indexed";
+
+/// The position of an event in the chain, used to continue a paginated query.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub struct Cursor {
+	/// The block the event was emitted in.
+	pub block_number: BlockNumber,
+	/// The index of the event in `System::Events` of that block.
+	pub event_index: u32,
+}
+
+impl Cursor {
+	fn to_key(self) -> [u8; 8] {
+		let mut key = [0u8; 8];
+		key[..4].copy_from_slice(&self.block_number.to_be_bytes());
+		key[4..].copy_from_slice(&self.event_index.to_be_bytes());
+		key
+	}
+
+	fn from_key(key: &[u8]) -> Option<Self> {
+		let mut number = [0u8; 4];
+		let mut index = [0u8; 4];
+		number.copy_from_slice(key.get(..4)?);
+		index.copy_from_slice(key.get(4..8)?);
+		Some(Cursor {
+			block_number: BlockNumber::from_be_bytes(number),
+			event_index: u32::from_be_bytes(index),
+		})
+	}
+}
+
+/// An event as stored in the index.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedEvent {
+	/// Where the event was emitted.
+	pub cursor: Cursor,
+	/// The hash of the block the event was emitted in.
+	pub block_hash: Hash,
+	/// The name of the pallet that emitted the event.
+	pub pallet: String,
+	/// The name of the event variant.
+	pub name: String,
+	/// The event fields, decoded through the runtime metadata.
+	pub fields: serde_json::Value,
+	/// The topics the event was deposited with.
+	pub topics: Vec<Hash>,
+}
+
+/// One page of a query result.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Page {
+	/// The events of this page, oldest first.
+	pub events: Vec<IndexedEvent>,
+	/// Pass this as the cursor to fetch the next page, `None` if this was the last one.
+	pub next: Option<Cursor>,
+}
+
+/// The event index database.
+pub struct EventIndex {
+	db: Arc<dyn KeyValueDB>,
+}
+
+impl EventIndex {
+	/// Open or create the index database at `path`.
+	pub fn open(path: &Path) -> io::Result<Self> {
+		let config = kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS);
+		let path = path
+			.to_str()
+			.ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Invalid event index path"))?;
+		let db = kvdb_rocksdb::Database::open(&config, path)?;
+		Ok(EventIndex { db: Arc::new(db) })
+	}
+
+	/// The number of the last block that has been indexed.
+	pub fn last_indexed(&self) -> io::Result<Option<BlockNumber>> {
+		Ok(self.db.get(COL_META, LAST_INDEXED)?.and_then(|raw| {
+			let mut number = [0u8; 4];
+			number.copy_from_slice(raw.get(..4)?);
+			Some(BlockNumber::from_be_bytes(number))
+		}))
+	}
+
+	/// Store the events of block `number` and mark it as indexed.
+	fn insert_block(
+		&self,
+		number: BlockNumber,
+		events: Vec<(IndexedEvent, Vec<AccountId>)>,
+	) -> io::Result<()> {
+		let mut transaction = self.db.transaction();
+		for (event, accounts) in events {
+			let location = event.cursor.to_key();
+			for who in accounts {
+				let who: &[u8] = who.as_ref();
+				transaction.put(COL_ACCOUNTS, &[who, &location[..]].concat(), &[]);
+			}
+			for topic in &event.topics {
+				transaction.put(COL_TOPICS, &[topic.as_bytes(), &location[..]].concat(), &[]);
+			}
+			let encoded =
+				serde_json::to_vec(&event).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+			transaction.put(COL_EVENTS, &location, &encoded);
+		}
+		transaction.put(COL_META, LAST_INDEXED, &number.to_be_bytes());
+		self.db.write(transaction)
+	}
+
+	/// Events that mention `who`, starting at `from`.
+	pub fn events_by_account(
+		&self,
+		who: &AccountId,
+		from: Option<Cursor>,
+		limit: usize,
+	) -> io::Result<Page> {
+		self.query(COL_ACCOUNTS, who.as_ref(), from, limit)
+	}
+
+	/// Events deposited with `topic`, starting at `from`.
+	pub fn events_by_topic(
+		&self,
+		topic: &Hash,
+		from: Option<Cursor>,
+		limit: usize,
+	) -> io::Result<Page> {
+		self.query(COL_TOPICS, topic.as_bytes(), from, limit)
+	}
+
+	fn query(
+		&self,
+		column: u32,
+		prefix: &[u8],
+		from: Option<Cursor>,
+		limit: usize,
+	) -> io::Result<Page> {
+		// Keys are ordered by block and event index under the prefix, so a page is one range scan
+		// of the prefix. `KeyValueDB` can not seek within a prefix, the keys before the cursor are
+		// skipped while scanning. One more than requested tells where the next page starts.
+		let mut cursors = self
+			.db
+			.iter_with_prefix(column, prefix)
+			.filter_map(|(key, _)| Cursor::from_key(&key[prefix.len()..]))
+			.skip_while(|cursor| from.map_or(false, |from| *cursor < from))
+			.take(limit.saturating_add(1))
+			.collect::<Vec<_>>();
+
+		let next = if cursors.len() > limit { cursors.pop() } else { None };
+		let mut events = Vec::new();
+		for cursor in cursors {
+			if let Some(raw) = self.db.get(COL_EVENTS, &cursor.to_key())? {
+				let event = serde_json::from_slice(&raw)
+					.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+				events.push(event);
+			}
+		}
+
+		Ok(Page { events, next })
+	}
+}
+
+/// Follows finality and feeds the events of every finalized block into the index.
+pub struct Indexer<C, B> {
+	client: Arc<C>,
+	index: Arc<EventIndex>,
+	metadata: Option<(u32, Metadata)>,
+	_phantom: PhantomData<B>,
+}
+
+impl<C, B> Indexer<C, B>
+where
+	B: Backend<Block>,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + StorageProvider<Block, B>,
+	C: BlockchainEvents<Block>,
+	C::Api: Core<Block> + MetadataApi<Block>,
+{
+	/// Create a new indexer writing into `index`.
+	pub fn new(client: Arc<C>, index: Arc<EventIndex>) -> Self {
+		Indexer { client, index, metadata: None, _phantom: PhantomData }
+	}
+
+	/// Index all finalized blocks, then keep following finality until the node shuts down.
+	///
+	/// Indexing reads the state and writes the index synchronously, spawn this as a blocking task.
+	pub async fn run(mut self) {
+		let mut finality_notifications = self.client.finality_notification_stream();
+
+		let finalized = self.client.info().finalized_number;
+		if let Err(e) = self.index_up_to(finalized) {
+			log::error!(target: "event-index", "Failed to catch up with finality: {}", e);
+		}
+
+		while let Some(notification) = finality_notifications.next().await {
+			if let Err(e) = self.index_up_to(*notification.header.number()) {
+				log::error!(target: "event-index", "Failed to index events: {}", e);
+			}
+		}
+	}
+
+	/// Finality notifications may skip blocks, so always continue from the last indexed one.
+	fn index_up_to(&mut self, finalized: BlockNumber) -> Result<(), String> {
+		let last = self.index.last_indexed().map_err(|e| e.to_string())?.unwrap_or(0);
+		for number in last + 1..=finalized {
+			self.index_block(number)?;
+		}
+		Ok(())
+	}
+
+	fn index_block(&mut self, number: BlockNumber) -> Result<(), String> {
+		let hash = self
+			.client
+			.hash(number)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("Unknown block #{}", number))?;
+		let at = BlockId::Hash(hash);
+
+		let api = self.client.runtime_api();
+		let spec_version = api.version(&at).map_err(|e| e.to_string())?.spec_version;
+		if self.metadata.as_ref().map(|(version, _)| *version) != Some(spec_version) {
+			let raw = api.metadata(&at).map_err(|e| e.to_string())?;
+			self.metadata = Some((spec_version, Metadata::from_bytes(&raw)?));
+		}
+		let metadata = &self.metadata.as_ref().expect("metadata was set above; qed").1;
+
+		let key = StorageKey(storage_prefix("System", "Events"));
+		let events = match self.client.storage(&at, &key).map_err(|e| e.to_string())? {
+			Some(raw) => metadata.decode_storage_value("System", "Events", &raw.0)?,
+			None => Value::Sequence(Vec::new()),
+		};
+
+		let records = match events {
+			Value::Sequence(records) => records,
+			_ => return Err("`System::Events` is not a sequence".into()),
+		};
+		let events = records
+			.iter()
+			.enumerate()
+			.filter_map(|(index, record)| {
+				to_indexed_event(
+					record,
+					Cursor { block_number: number, event_index: index as u32 },
+					hash,
+				)
+			})
+			.collect();
+
+		self.index.insert_block(number, events).map_err(|e| e.to_string())
+	}
+}
+
+/// Turn a decoded `EventRecord` into an indexed event and the accounts it mentions.
+fn to_indexed_event(
+	record: &Value,
+	cursor: Cursor,
+	block_hash: Hash,
+) -> Option<(IndexedEvent, Vec<AccountId>)> {
+	// The outer enum is the runtime `Event`, the inner one the pallet's `Event`.
+	let (pallet, event) = match record.field("event")? {
+		Value::Variant { name, fields } => (name.clone(), &fields.first()?.1),
+		_ => return None,
+	};
+	let (name, fields) = match event {
+		Value::Variant { name, fields } => (name.clone(), Value::Composite(fields.clone())),
+		_ => return None,
+	};
+
+	let topics = match record.field("topics") {
+		Some(Value::Sequence(topics)) => topics.iter().filter_map(as_hash).collect(),
+		_ => Vec::new(),
+	};
+
+	let mut accounts = Vec::new();
+	fields.accounts(&mut accounts);
+
+	let event = IndexedEvent { cursor, block_hash, pallet, name, fields: fields.to_json(), topics };
+	Some((event, accounts))
+}
+
+/// `H256` is a tuple struct around a byte array.
+fn as_hash(value: &Value) -> Option<Hash> {
+	match value {
+		Value::Bytes(bytes) if bytes.len() == 32 => Some(Hash::from_slice(bytes)),
+		Value::Composite(fields) if fields.len() == 1 => as_hash(&fields[0].1),
+		_ => None,
+	}
+}
@@ -0,0 +1,91 @@
+//! RPC interface of the event index.
+
+use super::{Cursor, EventIndex, Page};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use node_template_runtime::{AccountId, Hash};
+use std::sync::Arc;
+
+/// The number of events returned when the caller does not ask for a specific page size.
+const DEFAULT_PAGE_SIZE: u32 = 20;
+/// The largest page a caller may ask for.
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// Query events that were indexed by the node.
+#[rpc]
+pub trait EventIndexApi {
+	/// All events that mention `account`, oldest first, starting at `cursor`.
+	#[rpc(name = "index_eventsByAccount")]
+	fn events_by_account(
+		&self,
+		account: AccountId,
+		cursor: Option<Cursor>,
+		limit: Option<u32>,
+	) -> Result<Page>;
+
+	/// All events deposited with `topic`, oldest first, starting at `cursor`.
+	#[rpc(name = "index_eventsByTopic")]
+	fn events_by_topic(
+		&self,
+		topic: Hash,
+		cursor: Option<Cursor>,
+		limit: Option<u32>,
+	) -> Result<Page>;
+}
+
+/// Implements [`EventIndexApi`] on top of an [`EventIndex`].
+pub struct EventIndexRpc {
+	index: Arc<EventIndex>,
+}
+
+impl EventIndexRpc {
+	/// Serve queries from `index`.
+	pub fn new(index: Arc<EventIndex>) -> Self {
+		EventIndexRpc { index }
+	}
+}
+
+fn page_size(limit: Option<u32>) -> Result<usize> {
+	match limit.unwrap_or(DEFAULT_PAGE_SIZE) {
+		0 => Err(invalid_params("`limit` must not be zero")),
+		limit if limit > MAX_PAGE_SIZE =>
+			Err(invalid_params(&format!("`limit` must not exceed {}", MAX_PAGE_SIZE))),
+		limit => Ok(limit as usize),
+	}
+}
+
+fn invalid_params(message: &str) -> RpcError {
+	RpcError { code: ErrorCode::InvalidParams, message: message.into(), data: None }
+}
+
+fn database_error(e: std::io::Error) -> RpcError {
+	RpcError {
+		code: ErrorCode::InternalError,
+		message: "Event index database error".into(),
+		data: Some(e.to_string().into()),
+	}
+}
+
+impl EventIndexApi for EventIndexRpc {
+	fn events_by_account(
+		&self,
+		account: AccountId,
+		cursor: Option<Cursor>,
+		limit: Option<u32>,
+	) -> Result<Page> {
+		self.index
+			.events_by_account(&account, cursor, page_size(limit)?)
+			.map_err(database_error)
+	}
+
+	fn events_by_topic(
+		&self,
+		topic: Hash,
+		cursor: Option<Cursor>,
+		limit: Option<u32>,
+	) -> Result<Page> {
+		self.index
+			.events_by_topic(&topic, cursor, page_size(limit)?)
+			.map_err(database_error)
+	}
+}
@@ -0,0 +1,91 @@
+//! Construction of signed extrinsics for the runtime.
+
+use codec::Encode;
+use node_template_runtime::{
+	AccountId, Balance, Call, Hash, Index, Runtime, Signature, SignedExtra, UncheckedExtrinsic,
+};
+use sp_core::{sr25519, Pair};
+use sp_runtime::generic::{Era, SignedPayload};
+
+/// Everything besides the call and the signer that goes into a signed extrinsic.
+#[derive(Debug, Clone)]
+pub struct ExtrinsicParams {
+	/// The nonce of the signer.
+	pub nonce: Index,
+	/// The tip for the block author.
+	pub tip: Balance,
+	/// The account paying the fee instead of the signer, see `pallet_sponsorship`.
+	pub sponsor: Option<AccountId>,
+	/// The period during which the extrinsic is valid.
+	pub era: Era,
+	/// The hash of the block `era` starts at, the genesis hash for immortal extrinsics.
+	pub era_block_hash: Hash,
+	/// The genesis hash of the chain.
+	pub genesis_hash: Hash,
+	/// The `spec_version` of the runtime the extrinsic is built for.
+	pub spec_version: u32,
+	/// The `transaction_version` of the runtime the extrinsic is built for.
+	pub transaction_version: u32,
+}
+
+impl ExtrinsicParams {
+	/// The signed extensions of the runtime, in the order of [`SignedExtra`].
+	pub fn signed_extra(&self) -> SignedExtra {
+		(
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(self.era),
+			frame_system::CheckNonce::<Runtime>::from(self.nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			node_template_runtime::lanes::CheckLane(
+				pallet_sponsorship::ChargeSponsored::<Runtime>::new(self.tip, self.sponsor.clone()),
+			),
+		)
+	}
+
+	/// The data the signed extensions add to the signed payload without putting it into the
+	/// extrinsic.
+	pub fn additional_signed(
+		&self,
+	) -> <SignedExtra as sp_runtime::traits::SignedExtension>::AdditionalSigned {
+		(
+			self.spec_version,
+			self.transaction_version,
+			self.genesis_hash,
+			self.era_block_hash,
+			(),
+			(),
+			(),
+		)
+	}
+}
+
+/// Build an extrinsic signed by `signer`.
+///
+/// Signing is done by the `sign` closure, which receives the payload to sign. This allows signing
+/// with keys held by a keystore as well as with a plain key pair, see [`create_signed`].
+pub fn create_signed_with<E>(
+	call: Call,
+	signer: AccountId,
+	params: &ExtrinsicParams,
+	sign: impl FnOnce(&[u8]) -> Result<Signature, E>,
+) -> Result<UncheckedExtrinsic, E> {
+	let payload = SignedPayload::from_raw(call, params.signed_extra(), params.additional_signed());
+	let signature = payload.using_encoded(sign)?;
+	let (call, extra, _) = payload.deconstruct();
+	Ok(UncheckedExtrinsic::new_signed(call, signer.into(), signature, extra))
+}
+
+/// Build an extrinsic signed by the sr25519 key `pair`.
+pub fn create_signed(
+	call: Call,
+	pair: &sr25519::Pair,
+	params: &ExtrinsicParams,
+) -> UncheckedExtrinsic {
+	let signer = AccountId::from(pair.public());
+	let result: Result<_, ()> = create_signed_with(call, signer, params, |payload| {
+		Ok(Signature::Sr25519(pair.sign(payload)))
+	});
+	result.expect("signing with a key pair does not fail; qed")
+}
@@ -0,0 +1,133 @@
+//! Forking a chain into a local development chain.
+//!
+//! `--chain fork:<path>` starts a development chain from the state of another chain. The state is
+//! either a chain spec written by `export-state` or a raw storage dump, a JSON object of hex
+//! encoded keys and values. Before the state becomes the genesis of the fork, it is rewritten so
+//! that the dev keys can operate the chain: Alice becomes the only Aura and GRANDPA authority, the
+//! sudo key and gets endowed, and the runtime code is replaced by the one of this node.
+
+use crate::{
+	chain_spec::{get_account_id_from_seed, get_from_seed, ChainSpec},
+	decode::storage_prefix,
+};
+use codec::{Decode, Encode};
+use node_template_runtime::{AccountId, Balance, Index, Runtime, WASM_BINARY};
+use sc_service::ChainSpec as ChainSpecT;
+use serde_json::{json, Value as Json};
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_core::{
+	hashing::{blake2_128, blake2_256},
+	sr25519,
+	storage::well_known_keys,
+};
+use sp_finality_grandpa::{
+	AuthorityId as GrandpaId, VersionedAuthorityList, GRANDPA_AUTHORITIES_KEY,
+};
+use sp_runtime::{BuildStorage, Storage};
+use std::{collections::BTreeMap, path::Path};
+
+/// The balance added to the free balance of Alice, so she can pay for transactions on the fork.
+const ENDOWMENT: Balance = 1 << 60;
+
+type AccountInfo = frame_system::AccountInfo<Index, <Runtime as frame_system::Config>::AccountData>;
+
+/// Build the chain spec of a development chain from the state in the file at `path`.
+pub fn fork_config(path: &Path) -> Result<ChainSpec, String> {
+	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
+	let (name, mut storage) = load_state(path)?;
+	rewrite_for_dev(&mut storage.top, wasm_binary);
+
+	// Each fork gets an id of its own, so it does not share a database with the dev chain or with
+	// forks of other states or runtimes, whose genesis differs.
+	let id = format!("fork-{}", hex::encode(&blake2_256(&storage.top.encode())[..8]));
+
+	let mut spec = crate::chain_spec::development_config()?;
+	spec.set_storage(storage);
+
+	let mut json: Json = serde_json::from_str(&spec.as_json(true)?).map_err(|e| e.to_string())?;
+	json["name"] = json!(format!("Fork of {}", name));
+	json["id"] = json!(id);
+	ChainSpec::from_json_bytes(serde_json::to_vec(&json).map_err(|e| e.to_string())?)
+}
+
+/// Load a chain spec or a raw storage dump, returns the name of the chain and its state.
+fn load_state(path: &Path) -> Result<(String, Storage), String> {
+	let bytes = std::fs::read(path)
+		.map_err(|e| format!("Failed to read the state to fork {}: {}", path.display(), e))?;
+	let json: Json = serde_json::from_slice(&bytes)
+		.map_err(|e| format!("The state to fork is not JSON: {}", e))?;
+
+	if json.get("genesis").is_some() {
+		let spec = ChainSpec::from_json_bytes(bytes)?;
+		let storage = spec.build_storage()?;
+		return Ok((spec.name().to_string(), storage))
+	}
+
+	// Raw storage dumps may also be wrapped like the raw genesis of a chain spec.
+	let dump = json
+		.get("top")
+		.unwrap_or(&json)
+		.as_object()
+		.ok_or("Expected a chain spec or an object of storage keys and values")?;
+	let top = dump
+		.iter()
+		.map(|(key, value)| {
+			let value =
+				value.as_str().ok_or_else(|| format!("The value of {} is no string", key))?;
+			Ok((decode_hex(key)?, decode_hex(value)?))
+		})
+		.collect::<Result<_, String>>()?;
+	Ok(("a storage dump".into(), Storage { top, children_default: Default::default() }))
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+	hex::decode(s.trim_start_matches("0x")).map_err(|e| format!("Invalid hex {}: {}", s, e))
+}
+
+/// Hand the chain over to the dev keys and run it with the runtime of this node.
+fn rewrite_for_dev(top: &mut BTreeMap<Vec<u8>, Vec<u8>>, wasm_binary: &[u8]) {
+	let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+
+	top.insert(well_known_keys::CODE.to_vec(), wasm_binary.to_vec());
+
+	top.insert(
+		storage_prefix("Aura", "Authorities"),
+		vec![get_from_seed::<AuraId>("Alice")].encode(),
+	);
+
+	let grandpa_authorities = vec![(get_from_seed::<GrandpaId>("Alice"), 1)];
+	top.insert(
+		GRANDPA_AUTHORITIES_KEY.to_vec(),
+		VersionedAuthorityList::from(grandpa_authorities).encode(),
+	);
+	// The GRANDPA voter of the node starts with set 0 and without pending changes.
+	top.insert(storage_prefix("Grandpa", "CurrentSetId"), 0u64.encode());
+	for item in ["PendingChange", "NextForced", "Stalled"] {
+		top.remove(&storage_prefix("Grandpa", item));
+	}
+
+	top.insert(storage_prefix("Sudo", "Key"), alice.encode());
+
+	endow(top, &alice);
+}
+
+/// Add [`ENDOWMENT`] to the free balance of `who`.
+fn endow(top: &mut BTreeMap<Vec<u8>, Vec<u8>>, who: &AccountId) {
+	let key =
+		[storage_prefix("System", "Account"), blake2_128(&who.encode()).to_vec(), who.encode()]
+			.concat();
+	let mut account = top
+		.get(&key)
+		.and_then(|raw| AccountInfo::decode(&mut &raw[..]).ok())
+		.unwrap_or_default();
+	account.providers = account.providers.max(1);
+	account.data.free = account.data.free.saturating_add(ENDOWMENT);
+	top.insert(key, account.encode());
+
+	let issuance_key = storage_prefix("Balances", "TotalIssuance");
+	let issuance = top
+		.get(&issuance_key)
+		.and_then(|raw| Balance::decode(&mut &raw[..]).ok())
+		.unwrap_or_default();
+	top.insert(issuance_key, issuance.saturating_add(ENDOWMENT).encode());
+}
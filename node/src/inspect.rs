@@ -0,0 +1,367 @@
+//! Offline inspection of the local database.
+//!
+//! The `inspect` subcommand prints blocks, extrinsics, events and storage entries of the chain in
+//! the local database. Calls, signed extensions, events and storage are decoded through the
+//! runtime metadata of the inspected block, so blocks from before a runtime upgrade decode as well.
+
+use crate::{
+	decode::{storage_prefix, Metadata, Value},
+	service::FullClient,
+};
+use codec::{Decode, Encode};
+use node_template_runtime::{opaque::Block, Address, BlockNumber, Hash, Signature};
+use sc_cli::{CliConfiguration, ImportParams, SharedParams};
+use sc_client_api::{BlockBackend, StorageProvider};
+use serde_json::{json, Value as Json};
+use sp_api::{Metadata as MetadataApi, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::storage::StorageKey;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+	MultiAddress,
+};
+use std::{str::FromStr, sync::Arc};
+use structopt::StructOpt;
+
+/// The `inspect` command.
+#[derive(Debug, StructOpt)]
+pub struct InspectCmd {
+	#[structopt(subcommand)]
+	pub command: InspectSubcommand,
+
+	/// Print JSON instead of human readable output.
+	#[structopt(long)]
+	pub json: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+/// What to inspect.
+#[derive(Debug, StructOpt)]
+pub enum InspectSubcommand {
+	/// Print the header and the extrinsics of a block.
+	Block {
+		/// The block number or hash, the best block if not given.
+		block: Option<BlockAddress>,
+	},
+
+	/// Print the extrinsics of a block.
+	Extrinsic {
+		/// The block number or hash.
+		block: BlockAddress,
+
+		/// Only print the extrinsic with this index.
+		index: Option<usize>,
+	},
+
+	/// Print the events deposited in a block.
+	Events {
+		/// The block number or hash, the best block if not given.
+		block: Option<BlockAddress>,
+	},
+
+	/// Print a storage item, e.g. `storage TemplateModule Something`.
+	Storage {
+		/// The name of the pallet.
+		pallet: String,
+
+		/// The name of the storage item.
+		item: String,
+
+		/// The hex encoded SCALE key of a map entry, once for each key of the map. Without keys
+		/// all entries of a map are printed.
+		#[structopt(long = "key")]
+		keys: Vec<String>,
+
+		/// The block number or hash, the best block if not given.
+		#[structopt(long)]
+		at: Option<BlockAddress>,
+	},
+}
+
+/// A block given by number or by hash.
+#[derive(Debug, Clone, Copy)]
+pub enum BlockAddress {
+	/// A block number, e.g. `42`.
+	Number(BlockNumber),
+	/// A `0x` prefixed block hash.
+	Hash(Hash),
+}
+
+impl FromStr for BlockAddress {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.strip_prefix("0x") {
+			Some(hash) => {
+				let bytes = hex::decode(hash).map_err(|e| format!("Invalid block hash: {}", e))?;
+				if bytes.len() != 32 {
+					return Err("Block hashes are 32 bytes long".into())
+				}
+				Ok(BlockAddress::Hash(Hash::from_slice(&bytes)))
+			},
+			None => s
+				.parse()
+				.map(BlockAddress::Number)
+				.map_err(|e| format!("Invalid block number: {}", e)),
+		}
+	}
+}
+
+impl InspectCmd {
+	/// Run the command against the database of `client`.
+	pub fn run(&self, client: Arc<FullClient>) -> sc_cli::Result<()> {
+		let inspector = Inspector { client };
+		let output = match &self.command {
+			InspectSubcommand::Block { block } => inspector.block(*block)?,
+			InspectSubcommand::Extrinsic { block, index } =>
+				inspector.extrinsics(*block, *index)?,
+			InspectSubcommand::Events { block } => inspector.events(*block)?,
+			InspectSubcommand::Storage { pallet, item, keys, at } =>
+				inspector.storage(*at, pallet, item, keys)?,
+		};
+
+		if self.json {
+			println!("{}", serde_json::to_string_pretty(&output).map_err(|e| e.to_string())?);
+		} else {
+			print_human(&output, 0);
+		}
+		Ok(())
+	}
+}
+
+impl CliConfiguration for InspectCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
+
+struct Inspector {
+	client: Arc<FullClient>,
+}
+
+impl Inspector {
+	fn hash(&self, block: Option<BlockAddress>) -> Result<Hash, String> {
+		match block {
+			None => Ok(self.client.info().best_hash),
+			Some(BlockAddress::Hash(hash)) => Ok(hash),
+			Some(BlockAddress::Number(number)) => self
+				.client
+				.hash(number)
+				.map_err(|e| e.to_string())?
+				.ok_or_else(|| format!("Block #{} is not in the database", number)),
+		}
+	}
+
+	fn metadata(&self, hash: Hash) -> Result<Metadata, String> {
+		let raw = self
+			.client
+			.runtime_api()
+			.metadata(&BlockId::Hash(hash))
+			.map_err(|e| e.to_string())?;
+		Metadata::from_bytes(&raw)
+	}
+
+	fn block(&self, block: Option<BlockAddress>) -> Result<Json, String> {
+		let hash = self.hash(block)?;
+		let block = self.load_block(hash)?;
+		let metadata = self.metadata(hash)?;
+		let header = block.header();
+		Ok(json!({
+			"number": header.number(),
+			"hash": hash,
+			"parentHash": header.parent_hash(),
+			"stateRoot": header.state_root(),
+			"extrinsicsRoot": header.extrinsics_root(),
+			"extrinsics": block
+				.extrinsics()
+				.iter()
+				.map(|xt| decode_extrinsic(&metadata, &xt.encode()))
+				.collect::<Vec<_>>(),
+		}))
+	}
+
+	fn extrinsics(&self, block: BlockAddress, index: Option<usize>) -> Result<Json, String> {
+		let hash = self.hash(Some(block))?;
+		let block = self.load_block(hash)?;
+		let metadata = self.metadata(hash)?;
+		let extrinsics = block.extrinsics();
+		match index {
+			Some(index) => extrinsics
+				.get(index)
+				.map(|xt| decode_extrinsic(&metadata, &xt.encode()))
+				.ok_or_else(|| format!("The block has {} extrinsics", extrinsics.len())),
+			None => Ok(Json::Array(
+				extrinsics.iter().map(|xt| decode_extrinsic(&metadata, &xt.encode())).collect(),
+			)),
+		}
+	}
+
+	fn events(&self, block: Option<BlockAddress>) -> Result<Json, String> {
+		let hash = self.hash(block)?;
+		let metadata = self.metadata(hash)?;
+		let key = StorageKey(storage_prefix("System", "Events"));
+		match self.client.storage(&BlockId::Hash(hash), &key).map_err(|e| e.to_string())? {
+			Some(raw) => Ok(metadata.decode_storage_value("System", "Events", &raw.0)?.to_json()),
+			None => Ok(Json::Array(Vec::new())),
+		}
+	}
+
+	fn storage(
+		&self,
+		at: Option<BlockAddress>,
+		pallet: &str,
+		item: &str,
+		keys: &[String],
+	) -> Result<Json, String> {
+		let hash = self.hash(at)?;
+		let at = BlockId::Hash(hash);
+		let metadata = self.metadata(hash)?;
+		let keys = keys
+			.iter()
+			.map(|key| hex::decode(key.trim_start_matches("0x")).map_err(|e| e.to_string()))
+			.collect::<Result<Vec<_>, _>>()?;
+		let storage_key = metadata.storage_key(pallet, item, &keys)?;
+
+		// A full key has a value, otherwise list all entries under the prefix.
+		if let Some(raw) = self
+			.client
+			.storage(&at, &StorageKey(storage_key.clone()))
+			.map_err(|e| e.to_string())?
+		{
+			return Ok(metadata.decode_storage_value(pallet, item, &raw.0)?.to_json())
+		}
+		let entries = self
+			.client
+			.storage_pairs(&at, &StorageKey(storage_key))
+			.map_err(|e| e.to_string())?
+			.into_iter()
+			.map(|(key, value)| {
+				let keys = metadata.decode_storage_key(pallet, item, &key.0)?;
+				let value = metadata.decode_storage_value(pallet, item, &value.0)?;
+				Ok(json!({
+					"key": keys.iter().map(Value::to_json).collect::<Vec<_>>(),
+					"value": value.to_json(),
+				}))
+			})
+			.collect::<Result<Vec<_>, String>>()?;
+		Ok(Json::Array(entries))
+	}
+
+	fn load_block(&self, hash: Hash) -> Result<Block, String> {
+		self.client
+			.block(&BlockId::Hash(hash))
+			.map_err(|e| e.to_string())?
+			.map(|signed| signed.block)
+			.ok_or_else(|| format!("Block {} is not in the database", hash))
+	}
+}
+
+/// Decode an `UncheckedExtrinsic`, falling back to its raw bytes if it does not decode.
+pub fn decode_extrinsic(metadata: &Metadata, encoded: &[u8]) -> Json {
+	let decoded = Vec::<u8>::decode(&mut &encoded[..])
+		.map_err(|e| e.to_string())
+		.and_then(|raw| decode_extrinsic_bytes(metadata, &mut &raw[..]));
+	match decoded {
+		Ok(json) => json,
+		Err(e) => json!({ "error": e, "raw": format!("0x{}", hex::encode(encoded)) }),
+	}
+}
+
+fn decode_extrinsic_bytes(metadata: &Metadata, input: &mut &[u8]) -> Result<Json, String> {
+	const SIGNED: u8 = 0b1000_0000;
+	const VERSION: u8 = 4;
+
+	let version = u8::decode(input).map_err(|e| e.to_string())?;
+	if version & !SIGNED != VERSION {
+		return Err(format!("Unsupported extrinsic version {}", version & !SIGNED))
+	}
+
+	let mut extrinsic = serde_json::Map::new();
+	if version & SIGNED != 0 {
+		let address = Address::decode(input).map_err(|e| e.to_string())?;
+		let signature = Signature::decode(input).map_err(|e| e.to_string())?;
+		let extensions = metadata.decode_signed_extensions(input)?;
+
+		let signer = match address {
+			MultiAddress::Id(who) => json!(who.to_string()),
+			other => json!(format!("{:?}", other)),
+		};
+		let signature = match signature {
+			Signature::Sr25519(sig) => format!("sr25519 0x{}", hex::encode(sig)),
+			Signature::Ed25519(sig) => format!("ed25519 0x{}", hex::encode(sig)),
+			Signature::Ecdsa(sig) => format!("ecdsa 0x{}", hex::encode(sig)),
+		};
+		extrinsic.insert("signer".into(), signer);
+		extrinsic.insert("signature".into(), json!(signature));
+		extrinsic.insert(
+			"extra".into(),
+			Json::Object(
+				extensions
+					.into_iter()
+					// Most extensions carry no data.
+					.filter(|(_, value)| value != &Value::Composite(Vec::new()))
+					.map(|(name, value)| (name, value.to_json()))
+					.collect(),
+			),
+		);
+	}
+
+	let (pallet, call) = metadata.decode_call(input)?;
+	extrinsic.insert("pallet".into(), json!(pallet));
+	extrinsic.insert("call".into(), call.to_json());
+	if !input.is_empty() {
+		return Err(format!("{} trailing bytes", input.len()))
+	}
+	Ok(Json::Object(extrinsic))
+}
+
+/// Print `value` as indented `key: value` lines.
+pub fn print_human(value: &Json, indent: usize) {
+	let pad = "  ".repeat(indent);
+	match value {
+		Json::Object(map) =>
+			for (key, value) in map {
+				if is_scalar(value) {
+					println!("{}{}: {}", pad, key, scalar(value));
+				} else {
+					println!("{}{}:", pad, key);
+					print_human(value, indent + 1);
+				}
+			},
+		Json::Array(values) if values.is_empty() => println!("{}(none)", pad),
+		Json::Array(values) =>
+			for (index, value) in values.iter().enumerate() {
+				if is_scalar(value) {
+					println!("{}#{} {}", pad, index, scalar(value));
+				} else {
+					println!("{}#{}", pad, index);
+					print_human(value, indent + 1);
+				}
+			},
+		scalar_value => println!("{}{}", pad, scalar(scalar_value)),
+	}
+}
+
+fn is_scalar(value: &Json) -> bool {
+	!matches!(value, Json::Object(_) | Json::Array(_)) ||
+		matches!(value, Json::Array(values) if values.iter().all(|v| !v.is_object() && !v.is_array()))
+}
+
+fn scalar(value: &Json) -> String {
+	match value {
+		Json::String(s) => s.clone(),
+		other => other.to_string(),
+	}
+}
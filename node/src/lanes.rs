@@ -0,0 +1,132 @@
+//! Per lane limits of the transaction pool.
+//!
+//! The runtime sorts transactions into [`Lane`]s, see [`node_template_runtime::lanes`]. The pool
+//! itself only knows limits for all of its ready and future transactions. The [`LaneEnforcer`]
+//! keeps each lane within its own limits on top: whenever transactions enter the pool or a block
+//! is imported, it removes the transactions of a lane beyond its limits, the ones with the lowest
+//! priority first. Removed transactions are dropped without banning them, so they can be
+//! submitted again once their lane has room.
+
+use crate::service::{FullClient, FullPool};
+use codec::{Decode, Encode};
+use futures::{stream, StreamExt};
+use node_template_runtime::{lanes::Lane, Hash, UncheckedExtrinsic};
+use sc_client_api::BlockchainEvents;
+use sc_transaction_pool::ValidatedTransaction;
+use sc_transaction_pool_api::{error::Error as TxPoolError, InPoolTransaction, TransactionPool};
+use sp_runtime::OpaqueExtrinsic;
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+
+/// The limits of a lane, as given on the command line as `<LANE>=<READY>[:<FUTURE>]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaneLimit {
+	/// The lane the limits apply to.
+	pub lane: Lane,
+	/// The maximum number of ready transactions of the lane.
+	pub ready: usize,
+	/// The maximum number of future transactions of the lane, a tenth of `ready` if not given
+	/// like for `--pool-limit`.
+	pub future: usize,
+}
+
+impl FromStr for LaneLimit {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (name, limits) = s.split_once('=').ok_or("Expected `<LANE>=<READY>[:<FUTURE>]`")?;
+		let lane = Lane::ALL.iter().find(|lane| lane.name() == name).copied().ok_or_else(|| {
+			let names = Lane::ALL.iter().map(Lane::name).collect::<Vec<_>>();
+			format!("Unknown lane `{}`, expected one of {}", name, names.join(", "))
+		})?;
+		let parse = |count: &str| {
+			count.parse::<usize>().map_err(|e| format!("Invalid limit `{}`: {}", count, e))
+		};
+		let (ready, future) = match limits.split_once(':') {
+			Some((ready, future)) => (parse(ready)?, parse(future)?),
+			None => {
+				let ready = parse(limits)?;
+				(ready, ready / 10)
+			},
+		};
+		Ok(LaneLimit { lane, ready, future })
+	}
+}
+
+/// The lane of a transaction in the pool, transactions this runtime can not decode are in the
+/// default lane.
+pub fn lane_of(extrinsic: &OpaqueExtrinsic) -> Lane {
+	match UncheckedExtrinsic::decode(&mut &extrinsic.encode()[..]) {
+		Ok(extrinsic) if extrinsic.signature.is_some() => Lane::of(&extrinsic.function),
+		Ok(extrinsic) => Lane::of_unsigned(&extrinsic.function),
+		Err(_) => Lane::Default,
+	}
+}
+
+/// The transactions beyond the limit of their lane, given in the order in which the ones to keep
+/// come first.
+pub fn excess<'a>(
+	transactions: impl IntoIterator<Item = (Hash, &'a OpaqueExtrinsic)>,
+	limit: impl Fn(Lane) -> Option<usize>,
+) -> Vec<Hash> {
+	let mut counts = BTreeMap::<Lane, usize>::new();
+	transactions
+		.into_iter()
+		.filter_map(|(hash, extrinsic)| {
+			let lane = lane_of(extrinsic);
+			let count = counts.entry(lane).or_default();
+			*count += 1;
+			limit(lane).filter(|limit| *count > *limit).map(|_| hash)
+		})
+		.collect()
+}
+
+/// Keeps the lanes of the pool within their [`LaneLimit`]s.
+pub struct LaneEnforcer {
+	client: Arc<FullClient>,
+	pool: Arc<FullPool>,
+	limits: BTreeMap<Lane, LaneLimit>,
+}
+
+impl LaneEnforcer {
+	/// Enforce `limits` on `pool`, the last limit given for a lane applies.
+	pub fn new(client: Arc<FullClient>, pool: Arc<FullPool>, limits: &[LaneLimit]) -> Self {
+		let limits = limits.iter().map(|limit| (limit.lane, *limit)).collect();
+		LaneEnforcer { client, pool, limits }
+	}
+
+	/// Enforce the limits until the pool or the client shut down.
+	pub async fn run(self) {
+		let imported_transactions = self.pool.import_notification_stream().map(|_| ());
+		let imported_blocks = self.client.import_notification_stream().map(|_| ());
+		let mut events = stream::select(imported_transactions, imported_blocks);
+		while events.next().await.is_some() {
+			self.enforce();
+		}
+	}
+
+	fn enforce(&self) {
+		// The ready transactions come with the highest priority first.
+		let ready = self.pool.ready().map(|tx| (*tx.hash(), tx.data().clone())).collect::<Vec<_>>();
+		let mut removed = excess(ready.iter().map(|(hash, xt)| (*hash, xt)), |lane| {
+			self.limits.get(&lane).map(|limit| limit.ready)
+		});
+		let future = self.pool.pool().validated_pool().futures();
+		removed.extend(excess(future.iter().map(|(hash, xt)| (*hash, xt)), |lane| {
+			self.limits.get(&lane).map(|limit| limit.future)
+		}));
+
+		if !removed.is_empty() {
+			log::debug!(target: "txpool", "Dropping {} transactions beyond their lane limits", removed.len());
+			// `remove_invalid` would ban them. Resubmitting them as unknown drops them, the
+			// transactions depending on them are resubmitted as they are.
+			let dropped = removed
+				.into_iter()
+				.map(|hash| {
+					let error = TxPoolError::ImmediatelyDropped.into();
+					(hash, ValidatedTransaction::Unknown(hash, error))
+				})
+				.collect();
+			self.pool.pool().validated_pool().resubmit(dropped);
+		}
+	}
+}
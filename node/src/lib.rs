@@ -1,3 +1,17 @@
+pub mod asset_fees;
 pub mod chain_spec;
+pub mod cli;
+pub mod command;
+pub mod decode;
+pub mod event_index;
+pub mod extrinsic;
+pub mod fork;
+pub mod inspect;
+pub mod lanes;
+pub mod managed_tx;
+pub mod metadata_diff;
 pub mod rpc;
+pub mod runtime_upgrade;
 pub mod service;
+pub mod snapshot;
+pub mod tx;
@@ -1,12 +1,24 @@
 //! Substrate Node Template CLI library.
 #![warn(missing_docs)]
 
+mod asset_fees;
 mod chain_spec;
+mod decode;
+mod event_index;
+mod extrinsic;
+mod fork;
+mod inspect;
+mod lanes;
+mod managed_tx;
+mod metadata_diff;
 #[macro_use]
 mod service;
 mod cli;
 mod command;
 mod rpc;
+mod runtime_upgrade;
+mod snapshot;
+mod tx;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
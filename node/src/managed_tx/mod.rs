@@ -0,0 +1,341 @@
+//! Managed submission of transactions signed with keys of the node keystore.
+//!
+//! Backends submitting many transactions from one account race each other for nonces. The
+//! [`TxManager`] hands out nonces itself: the next nonce of an account is the highest of its
+//! on-chain nonce, the nonces of its transactions in the ready queue of the pool and the nonces
+//! it has handed out before. Transactions are signed with a mortal era and watched in the pool.
+//! When one is dropped, usurped or falls out of its era, it is signed again with a fresh era and
+//! resubmitted with the same nonce. If that nonce was used on chain in the meantime, the manager
+//! gives up instead of signing the call with a new nonce: the pool may have missed the inclusion of
+//! the transaction itself, e.g. in a reorg, and the call would run twice. The blocks in the eras of
+//! its attempts are searched for the transaction to report which was the case.
+
+pub mod rpc;
+
+use crate::extrinsic::{create_signed_with, ExtrinsicParams};
+use codec::Encode;
+use futures::StreamExt;
+use node_template_runtime::{
+	opaque::Block, AccountId, BlockNumber, Call, Hash, Index, Signature, UncheckedExtrinsic,
+};
+use sc_client_api::BlockBackend;
+use sc_transaction_pool_api::{
+	InPoolTransaction, TransactionPool, TransactionSource, TransactionStatus,
+	TransactionStatusStreamFor,
+};
+use serde::Serialize;
+use sp_api::{Core, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	crypto::{key_types, CryptoTypePublicPair},
+	sr25519,
+};
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+use sp_runtime::{
+	generic::{BlockId, Era},
+	OpaqueExtrinsic,
+};
+use std::{
+	collections::HashMap,
+	convert::TryFrom,
+	pin::Pin,
+	sync::{Arc, Mutex},
+};
+use substrate_frame_rpc_system::AccountNonceApi;
+
+/// The number of blocks a managed transaction is valid for.
+pub const MORTAL_PERIOD: u64 = 64;
+/// How often a transaction is submitted before giving up on it.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// The status of a managed transaction, as reported to subscribers.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum ManagedStatus {
+	/// The transaction was signed and entered the pool.
+	#[serde(rename_all = "camelCase")]
+	Submitted { hash: Hash, nonce: Index, era_birth: BlockNumber, attempt: u32 },
+	/// The transaction was included in a block.
+	InBlock { block: Hash },
+	/// The block the transaction was included in was retracted.
+	Retracted { block: Hash },
+	/// The block the transaction was included in was finalized.
+	Finalized { block: Hash },
+	/// The block the transaction was included in was not finalized in time, the pool stopped
+	/// watching it.
+	FinalityTimeout { block: Hash },
+	/// The transaction left the pool without being included and is about to be resubmitted.
+	Resubmitting { reason: String },
+	/// The transaction could not be submitted, the manager stopped tracking it.
+	Failed { reason: String },
+}
+
+/// A transaction that entered the pool.
+struct Submission<P: TransactionPool> {
+	hash: Hash,
+	nonce: Index,
+	era_birth: BlockNumber,
+	statuses: Pin<Box<TransactionStatusStreamFor<P>>>,
+}
+
+/// Signs, submits and watches transactions of accounts in the node keystore.
+pub struct TxManager<C, P> {
+	client: Arc<C>,
+	pool: Arc<P>,
+	keystore: SyncCryptoStorePtr,
+	/// The next nonce to hand out for each account.
+	nonces: Mutex<HashMap<AccountId, Index>>,
+}
+
+impl<C, P> TxManager<C, P>
+where
+	C: ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ BlockBackend<Block>
+		+ Send
+		+ Sync
+		+ 'static,
+	C::Api: AccountNonceApi<Block, AccountId, Index> + Core<Block>,
+	P: TransactionPool<Block = Block, Hash = Hash> + 'static,
+{
+	/// Create a manager signing with keys of `keystore`.
+	pub fn new(client: Arc<C>, pool: Arc<P>, keystore: SyncCryptoStorePtr) -> Self {
+		TxManager { client, pool, keystore, nonces: Default::default() }
+	}
+
+	/// Whether the keystore has the sr25519 account key of `who`.
+	pub fn has_key(&self, who: &AccountId) -> bool {
+		SyncCryptoStore::has_keys(&*self.keystore, &[(who.encode(), key_types::ACCOUNT)])
+	}
+
+	/// Submit `call` signed by `who` and track it until it is finalized or given up on.
+	///
+	/// Every status change is passed to `notify`, tracking stops early when it returns `false`.
+	pub async fn submit_and_track(
+		&self,
+		call: Call,
+		who: AccountId,
+		notify: impl Fn(ManagedStatus) -> bool,
+	) {
+		let mut nonce = None;
+		let mut submitted = Vec::new();
+		for attempt in 1..=MAX_ATTEMPTS {
+			if let Some(used_nonce) = nonce {
+				match self.nonce_used(&who, used_nonce, &submitted) {
+					Ok(None) => {},
+					Ok(Some(reason)) | Err(reason) => {
+						notify(ManagedStatus::Failed { reason });
+						return
+					},
+				}
+			}
+
+			let Submission { hash, nonce: used_nonce, era_birth, mut statuses } =
+				match self.submit(call.clone(), &who, nonce).await {
+					Ok(submission) => submission,
+					Err(reason) => {
+						nonce.into_iter().for_each(|nonce| self.release_nonce(&who, nonce));
+						notify(ManagedStatus::Failed { reason });
+						return
+					},
+				};
+			nonce = Some(used_nonce);
+			submitted.push((hash, era_birth));
+			if !notify(ManagedStatus::Submitted { hash, nonce: used_nonce, era_birth, attempt }) {
+				return
+			}
+
+			let reason = loop {
+				let status = match statuses.next().await {
+					Some(TransactionStatus::InBlock(block)) => ManagedStatus::InBlock { block },
+					Some(TransactionStatus::Retracted(block)) => ManagedStatus::Retracted { block },
+					Some(TransactionStatus::Finalized(block)) => {
+						notify(ManagedStatus::Finalized { block });
+						return
+					},
+					Some(TransactionStatus::FinalityTimeout(block)) => {
+						notify(ManagedStatus::FinalityTimeout { block });
+						return
+					},
+					Some(TransactionStatus::Usurped(by)) => break format!("Usurped by {:?}", by),
+					Some(TransactionStatus::Dropped) => break "Dropped from the pool".into(),
+					Some(TransactionStatus::Invalid) =>
+						break "Invalid, e.g. because its era ended".into(),
+					Some(_) => continue,
+					None => break "The pool stopped watching it".into(),
+				};
+				if !notify(status) {
+					return
+				}
+			};
+			if !notify(ManagedStatus::Resubmitting { reason }) {
+				return
+			}
+		}
+
+		nonce.into_iter().for_each(|nonce| self.release_nonce(&who, nonce));
+		notify(ManagedStatus::Failed {
+			reason: format!("Not included after {} attempts", MAX_ATTEMPTS),
+		});
+	}
+
+	/// Why the transaction can not be resubmitted with `nonce`, if that nonce was used on chain.
+	///
+	/// `submitted` are the hashes and era births of the previous attempts.
+	fn nonce_used(
+		&self,
+		who: &AccountId,
+		nonce: Index,
+		submitted: &[(Hash, BlockNumber)],
+	) -> Result<Option<String>, String> {
+		let at = BlockId::Hash(self.client.info().best_hash);
+		if nonce >= self.chain_nonce(&at, who)? {
+			return Ok(None)
+		}
+		Ok(Some(match self.find_included(submitted)? {
+			Some(block) => format!("Included in {:?} without the pool reporting it", block),
+			None => format!("Nonce {} was used by another transaction", nonce),
+		}))
+	}
+
+	/// The block of the best chain that includes one of `submitted`, searched in their eras.
+	fn find_included(&self, submitted: &[(Hash, BlockNumber)]) -> Result<Option<Hash>, String> {
+		let best = self.client.info().best_number;
+		for (hash, era_birth) in submitted {
+			let era_end = era_birth.saturating_add(MORTAL_PERIOD as BlockNumber).min(best);
+			for number in *era_birth..=era_end {
+				let block = self
+					.client
+					.hash(number)
+					.map_err(|e| e.to_string())?
+					.ok_or_else(|| format!("Unknown block #{}", number))?;
+				let body = self
+					.client
+					.block_body(&BlockId::Hash(block))
+					.map_err(|e| e.to_string())?
+					.unwrap_or_default();
+				if body.iter().any(|extrinsic| self.pool.hash_of(extrinsic) == *hash) {
+					return Ok(Some(block))
+				}
+			}
+		}
+		Ok(None)
+	}
+
+	/// Sign and submit `call`, with the nonce of a previous attempt or a new one.
+	async fn submit(
+		&self,
+		call: Call,
+		who: &AccountId,
+		previous_nonce: Option<Index>,
+	) -> Result<Submission<P>, String> {
+		let at = BlockId::Hash(self.client.info().best_hash);
+		let nonce = match previous_nonce {
+			Some(nonce) => nonce,
+			None => self.allocate_nonce(&at, who)?,
+		};
+		let submission = self.sign_and_submit(&at, call, who, nonce).await;
+		if submission.is_err() {
+			self.release_nonce(who, nonce);
+		}
+		submission
+	}
+
+	async fn sign_and_submit(
+		&self,
+		at: &BlockId<Block>,
+		call: Call,
+		who: &AccountId,
+		nonce: Index,
+	) -> Result<Submission<P>, String> {
+		let (extrinsic, era_birth) = self.sign(call, who, nonce)?;
+		let extrinsic = OpaqueExtrinsic::from_bytes(&extrinsic.encode())
+			.map_err(|e| format!("Failed to convert the extrinsic: {}", e))?;
+		let hash = self.pool.hash_of(&extrinsic);
+		let statuses = self
+			.pool
+			.submit_and_watch(at, TransactionSource::External, extrinsic)
+			.await
+			.map_err(|e| format!("The pool rejected the transaction: {}", e))?;
+		Ok(Submission { hash, nonce, era_birth, statuses })
+	}
+
+	fn chain_nonce(&self, at: &BlockId<Block>, who: &AccountId) -> Result<Index, String> {
+		self.client
+			.runtime_api()
+			.account_nonce(at, who.clone())
+			.map_err(|e| format!("Failed to read the nonce of {}: {}", who, e))
+	}
+
+	/// The next nonce of `who`, see the module docs.
+	fn allocate_nonce(&self, at: &BlockId<Block>, who: &AccountId) -> Result<Index, String> {
+		let mut nonce = self.chain_nonce(at, who)?;
+		// The same as `system_accountNextIndex`: skip the nonces provided by ready transactions.
+		loop {
+			let tag = (who, nonce).encode();
+			if !self.pool.ready().any(|tx| tx.provides().contains(&tag)) {
+				break
+			}
+			nonce += 1;
+		}
+
+		let mut nonces = self.nonces.lock().expect("nonce lock is not poisoned; qed");
+		let next = nonces.entry(who.clone()).or_default();
+		let nonce = nonce.max(*next);
+		*next = nonce + 1;
+		Ok(nonce)
+	}
+
+	/// Hand out `nonce` again if it was the last one handed out, so it does not leave a gap.
+	fn release_nonce(&self, who: &AccountId, nonce: Index) {
+		let mut nonces = self.nonces.lock().expect("nonce lock is not poisoned; qed");
+		if let Some(next) = nonces.get_mut(who) {
+			if *next == nonce + 1 {
+				*next = nonce;
+			}
+		}
+	}
+
+	/// Sign `call` with a mortal era starting at the best block, returns the extrinsic and the
+	/// first block it is valid in.
+	fn sign(
+		&self,
+		call: Call,
+		who: &AccountId,
+		nonce: Index,
+	) -> Result<(UncheckedExtrinsic, BlockNumber), String> {
+		let info = self.client.info();
+		let at = BlockId::Hash(info.best_hash);
+		let version = self.client.runtime_api().version(&at).map_err(|e| e.to_string())?;
+
+		let era = Era::mortal(MORTAL_PERIOD, info.best_number.into());
+		let era_birth = era.birth(info.best_number.into()) as BlockNumber;
+		let era_block_hash = self
+			.client
+			.hash(era_birth)
+			.map_err(|e| e.to_string())?
+			.ok_or_else(|| format!("Unknown block #{}", era_birth))?;
+		let params = ExtrinsicParams {
+			nonce,
+			tip: 0,
+			sponsor: None,
+			era,
+			era_block_hash,
+			genesis_hash: info.genesis_hash,
+			spec_version: version.spec_version,
+			transaction_version: version.transaction_version,
+		};
+
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, who.encode());
+		let extrinsic = create_signed_with(call, who.clone(), &params, |payload| {
+			let signature =
+				SyncCryptoStore::sign_with(&*self.keystore, key_types::ACCOUNT, &key, payload)
+					.map_err(|e| format!("Failed to sign: {}", e))?
+					.ok_or_else(|| format!("The key of {} is not in the keystore", who))?;
+			sr25519::Signature::try_from(&signature[..])
+				.map(Signature::Sr25519)
+				.map_err(|_| "The keystore returned an invalid signature".to_string())
+		})?;
+		Ok((extrinsic, era_birth))
+	}
+}
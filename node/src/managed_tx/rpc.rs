@@ -0,0 +1,118 @@
+//! RPC interface of the transaction manager.
+
+use super::{ManagedStatus, TxManager};
+use codec::Decode;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use node_template_runtime::{opaque::Block, AccountId, Call, Hash, Index};
+use sc_client_api::BlockBackend;
+use sc_rpc_api::DenyUnsafe;
+use sc_transaction_pool_api::TransactionPool;
+use sp_api::{Core, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use std::sync::Arc;
+use substrate_frame_rpc_system::AccountNonceApi;
+
+/// Submit transactions signed by the node and have it manage nonces and resubmissions.
+#[rpc]
+pub trait ManagedTxApi {
+	/// RPC metadata
+	type Metadata;
+
+	/// Sign the SCALE encoded `call` with the key of `signer` in the node keystore, submit it and
+	/// subscribe to its [`ManagedStatus`].
+	#[pubsub(subscription = "author_managedStatus", subscribe, name = "author_submitManaged")]
+	fn submit_managed(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<ManagedStatus>,
+		call: Bytes,
+		signer: AccountId,
+	);
+
+	/// Stop reporting the status of a managed transaction, it stays in the pool.
+	#[pubsub(
+		subscription = "author_managedStatus",
+		unsubscribe,
+		name = "author_unsubscribeManaged"
+	)]
+	fn unsubscribe_managed(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool>;
+}
+
+/// Implements [`ManagedTxApi`] on top of a [`TxManager`].
+pub struct ManagedTxRpc<C, P> {
+	manager: Arc<TxManager<C, P>>,
+	subscriptions: SubscriptionManager,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<C, P> ManagedTxRpc<C, P> {
+	/// Serve submissions through `manager`, which signs with the node keys and is thus unsafe.
+	pub fn new(
+		manager: Arc<TxManager<C, P>>,
+		subscriptions: SubscriptionManager,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		ManagedTxRpc { manager, subscriptions, deny_unsafe }
+	}
+}
+
+fn invalid_params(message: String) -> RpcError {
+	RpcError { code: ErrorCode::InvalidParams, message, data: None }
+}
+
+impl<C, P> ManagedTxApi for ManagedTxRpc<C, P>
+where
+	C: ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ BlockBackend<Block>
+		+ Send
+		+ Sync
+		+ 'static,
+	C::Api: AccountNonceApi<Block, AccountId, Index> + Core<Block>,
+	P: TransactionPool<Block = Block, Hash = Hash> + 'static,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn submit_managed(
+		&self,
+		_: Self::Metadata,
+		subscriber: Subscriber<ManagedStatus>,
+		call: Bytes,
+		signer: AccountId,
+	) {
+		if let Err(e) = self.deny_unsafe.check_if_safe() {
+			let _ = subscriber.reject(e.into());
+			return
+		}
+		let call = match Call::decode(&mut &call[..]) {
+			Ok(call) => call,
+			Err(e) => {
+				let _ = subscriber.reject(invalid_params(format!("Invalid call: {}", e)));
+				return
+			},
+		};
+		if !self.manager.has_key(&signer) {
+			let message = format!("The key of {} is not in the keystore", signer);
+			let _ = subscriber.reject(invalid_params(message));
+			return
+		}
+
+		let manager = self.manager.clone();
+		self.subscriptions.add(subscriber, move |sink| async move {
+			manager
+				.submit_and_track(call, signer, |status| sink.notify(Ok(status)).is_ok())
+				.await
+		});
+	}
+
+	fn unsubscribe_managed(&self, _: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+		Ok(self.subscriptions.cancel(id))
+	}
+}
@@ -0,0 +1,499 @@
+//! Comparison of the runtime metadata of two runtime versions.
+//!
+//! Wallets sign extrinsics for a `transaction_version`. Whenever the encoding of an existing call
+//! or of the signed extensions changes, the `transaction_version` has to be bumped so that
+//! signers don't submit extrinsics that the new runtime decodes differently. The `metadata-diff`
+//! subcommand reports all changes between two runtimes and whether they require such a bump.
+
+use crate::decode::Metadata;
+use codec::{Decode, Encode};
+use frame_metadata::{PalletMetadata, StorageEntryMetadata, StorageEntryType};
+use jsonrpc_core_client::transports::http;
+use node_template_runtime::{Hash, Runtime};
+use sc_cli::{CliConfiguration, RuntimeVersion, SharedParams};
+use sc_executor::{WasmExecutionMethod, WasmExecutor};
+use sc_executor_common::runtime_blob::RuntimeBlob;
+use sc_rpc_api::state::StateClient;
+use scale_info::{form::PortableForm, Variant};
+use serde_json::{json, Value as Json};
+use sp_state_machine::BasicExternalities;
+use std::{fmt, path::PathBuf, str::FromStr};
+use structopt::StructOpt;
+
+/// What changed about an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+	Added,
+	Removed,
+	Changed,
+}
+
+/// The kind of item that changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+	Pallet,
+	Call,
+	Event,
+	Storage,
+	SignedExtension,
+}
+
+/// One change between two runtimes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+	pub kind: ChangeKind,
+	pub category: Category,
+	/// The changed item, e.g. `Balances::transfer`.
+	pub item: String,
+	/// What changed for [`ChangeKind::Changed`].
+	pub detail: Option<String>,
+	/// Whether extrinsics signed for the old runtime may decode differently in the new one.
+	pub breaks_signers: bool,
+}
+
+impl fmt::Display for Change {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:?} {:?} {}", self.kind, self.category, self.item)?;
+		if let Some(detail) = &self.detail {
+			write!(f, ": {}", detail)?;
+		}
+		Ok(())
+	}
+}
+
+/// All changes from one runtime to another.
+#[derive(Debug, Default)]
+pub struct MetadataDiff {
+	pub changes: Vec<Change>,
+}
+
+impl MetadataDiff {
+	/// Compare the metadata of the `old` runtime with the one of the `new` runtime.
+	pub fn new(old: &Metadata, new: &Metadata) -> Self {
+		let mut diff = MetadataDiff::default();
+
+		for old_pallet in old.pallets() {
+			match new.pallets().iter().find(|pallet| pallet.name == old_pallet.name) {
+				Some(new_pallet) => diff.pallet(old, old_pallet, new, new_pallet),
+				None => {
+					diff.push(ChangeKind::Removed, Category::Pallet, &old_pallet.name, None);
+					let (calls, events) = (calls(old, old_pallet), events(old, old_pallet));
+					diff.variants(Category::Call, &old_pallet.name, old, calls, new, &[]);
+					diff.variants(Category::Event, &old_pallet.name, old, events, new, &[]);
+					diff.storage(&old_pallet.name, old, entries(old_pallet), new, &[]);
+				},
+			}
+		}
+		for new_pallet in new.pallets() {
+			if !old.pallets().iter().any(|pallet| pallet.name == new_pallet.name) {
+				diff.push(ChangeKind::Added, Category::Pallet, &new_pallet.name, None);
+				let (calls, events) = (calls(new, new_pallet), events(new, new_pallet));
+				diff.variants(Category::Call, &new_pallet.name, old, &[], new, calls);
+				diff.variants(Category::Event, &new_pallet.name, old, &[], new, events);
+				diff.storage(&new_pallet.name, old, &[], new, entries(new_pallet));
+			}
+		}
+
+		diff.signed_extensions(old, new);
+		diff
+	}
+
+	/// Whether the changes require a new `transaction_version`.
+	pub fn requires_transaction_version_bump(&self) -> bool {
+		self.changes.iter().any(|change| change.breaks_signers)
+	}
+
+	/// The changes that break signers, see [`Change::breaks_signers`].
+	pub fn breaking_changes(&self) -> impl Iterator<Item = &Change> {
+		self.changes.iter().filter(|change| change.breaks_signers)
+	}
+
+	fn push(&mut self, kind: ChangeKind, category: Category, item: &str, detail: Option<String>) {
+		// Existing extrinsics only encode existing calls and the signed extensions.
+		let breaks_signers = match category {
+			Category::Call => kind != ChangeKind::Added,
+			Category::SignedExtension => true,
+			Category::Pallet | Category::Event | Category::Storage => false,
+		};
+		self.changes
+			.push(Change { kind, category, item: item.into(), detail, breaks_signers });
+	}
+
+	fn pallet(
+		&mut self,
+		old: &Metadata,
+		old_pallet: &PalletMetadata<PortableForm>,
+		new: &Metadata,
+		new_pallet: &PalletMetadata<PortableForm>,
+	) {
+		let name = &old_pallet.name;
+		if old_pallet.index != new_pallet.index {
+			self.push(
+				ChangeKind::Changed,
+				Category::Pallet,
+				name,
+				Some(format!("index {} -> {}", old_pallet.index, new_pallet.index)),
+			);
+			// The pallet index is the first byte of every call of the pallet.
+			if let Some(change) = self.changes.last_mut() {
+				change.breaks_signers = !calls(old, old_pallet).is_empty();
+			}
+		}
+		self.variants(
+			Category::Call,
+			name,
+			old,
+			calls(old, old_pallet),
+			new,
+			calls(new, new_pallet),
+		);
+		self.variants(
+			Category::Event,
+			name,
+			old,
+			events(old, old_pallet),
+			new,
+			events(new, new_pallet),
+		);
+		self.storage(name, old, entries(old_pallet), new, entries(new_pallet));
+	}
+
+	/// Compare the calls or events of a pallet by their name.
+	fn variants(
+		&mut self,
+		category: Category,
+		pallet: &str,
+		old: &Metadata,
+		old_variants: &[Variant<PortableForm>],
+		new: &Metadata,
+		new_variants: &[Variant<PortableForm>],
+	) {
+		for old_variant in old_variants {
+			let item = format!("{}::{}", pallet, old_variant.name());
+			let new_variant = match new_variants.iter().find(|v| v.name() == old_variant.name()) {
+				Some(variant) => variant,
+				None => {
+					self.push(ChangeKind::Removed, category, &item, None);
+					continue
+				},
+			};
+			let mut details = Vec::new();
+			if old_variant.index() != new_variant.index() {
+				details.push(format!("index {} -> {}", old_variant.index(), new_variant.index()));
+			}
+			let (old_fields, new_fields) = (fields(old, old_variant), fields(new, new_variant));
+			if old_fields != new_fields {
+				details.push(format!("fields ({}) -> ({})", old_fields, new_fields));
+			}
+			if !details.is_empty() {
+				self.push(ChangeKind::Changed, category, &item, Some(details.join(", ")));
+			}
+		}
+		for new_variant in new_variants {
+			if !old_variants.iter().any(|v| v.name() == new_variant.name()) {
+				let item = format!("{}::{}", pallet, new_variant.name());
+				self.push(ChangeKind::Added, category, &item, None);
+			}
+		}
+	}
+
+	/// Compare the storage items of a pallet by their name.
+	fn storage(
+		&mut self,
+		pallet: &str,
+		old: &Metadata,
+		old_entries: &[StorageEntryMetadata<PortableForm>],
+		new: &Metadata,
+		new_entries: &[StorageEntryMetadata<PortableForm>],
+	) {
+		for old_entry in old_entries {
+			let item = format!("{}::{}", pallet, old_entry.name);
+			match new_entries.iter().find(|entry| entry.name == old_entry.name) {
+				None => self.push(ChangeKind::Removed, Category::Storage, &item, None),
+				Some(new_entry) => {
+					let (old_layout, new_layout) =
+						(storage_layout(old, old_entry), storage_layout(new, new_entry));
+					if old_layout != new_layout {
+						let detail = format!("{} -> {}", old_layout, new_layout);
+						self.push(ChangeKind::Changed, Category::Storage, &item, Some(detail));
+					}
+				},
+			}
+		}
+		for new_entry in new_entries {
+			if !old_entries.iter().any(|entry| entry.name == new_entry.name) {
+				let item = format!("{}::{}", pallet, new_entry.name);
+				self.push(ChangeKind::Added, Category::Storage, &item, None);
+			}
+		}
+	}
+
+	/// Compare the signed extensions, their order is part of the encoding.
+	fn signed_extensions(&mut self, old: &Metadata, new: &Metadata) {
+		let (old_extensions, new_extensions) = (signed_extensions(old), signed_extensions(new));
+		for (name, old_layout) in &old_extensions {
+			match new_extensions.iter().find(|(new_name, _)| new_name == name) {
+				None => self.push(ChangeKind::Removed, Category::SignedExtension, name, None),
+				Some((_, new_layout)) if new_layout != old_layout => {
+					let detail = format!("{} -> {}", old_layout, new_layout);
+					self.push(ChangeKind::Changed, Category::SignedExtension, name, Some(detail));
+				},
+				Some(_) => {},
+			}
+		}
+		for (name, _) in &new_extensions {
+			if !old_extensions.iter().any(|(old_name, _)| old_name == name) {
+				self.push(ChangeKind::Added, Category::SignedExtension, name, None);
+			}
+		}
+
+		let names = |extensions: &[(String, String)], other: &[(String, String)]| {
+			extensions
+				.iter()
+				.map(|(name, _)| name.clone())
+				.filter(|name| other.iter().any(|(other, _)| other == name))
+				.collect::<Vec<_>>()
+		};
+		let (old_order, new_order) =
+			(names(&old_extensions, &new_extensions), names(&new_extensions, &old_extensions));
+		if old_order != new_order {
+			let detail = format!("order [{}] -> [{}]", old_order.join(", "), new_order.join(", "));
+			self.push(ChangeKind::Changed, Category::SignedExtension, "SignedExtra", Some(detail));
+		}
+	}
+
+	/// The report as JSON, with the transaction versions of both runtimes if they are known.
+	pub fn to_json(&self, versions: Option<(&RuntimeVersion, &RuntimeVersion)>) -> Json {
+		json!({
+			"changes": self
+				.changes
+				.iter()
+				.map(|change| json!({
+					"kind": format!("{:?}", change.kind),
+					"category": format!("{:?}", change.category),
+					"item": change.item,
+					"detail": change.detail,
+					"breaksSigners": change.breaks_signers,
+				}))
+				.collect::<Vec<_>>(),
+			"transactionVersionBumpRequired": self.requires_transaction_version_bump(),
+			"transactionVersion": versions.map(|(old, new)| json!({
+				"old": old.transaction_version,
+				"new": new.transaction_version,
+			})),
+		})
+	}
+}
+
+fn calls<'a>(
+	metadata: &'a Metadata,
+	pallet: &PalletMetadata<PortableForm>,
+) -> &'a [Variant<PortableForm>] {
+	pallet.calls.as_ref().map_or(&[], |calls| metadata.variants(calls.ty.id()))
+}
+
+fn events<'a>(
+	metadata: &'a Metadata,
+	pallet: &PalletMetadata<PortableForm>,
+) -> &'a [Variant<PortableForm>] {
+	pallet.event.as_ref().map_or(&[], |event| metadata.variants(event.ty.id()))
+}
+
+fn entries(pallet: &PalletMetadata<PortableForm>) -> &[StorageEntryMetadata<PortableForm>] {
+	pallet.storage.as_ref().map_or(&[], |storage| &storage.entries[..])
+}
+
+fn fields(metadata: &Metadata, variant: &Variant<PortableForm>) -> String {
+	variant
+		.fields()
+		.iter()
+		.map(|field| metadata.type_signature(field.ty().id()))
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+/// The hashers, keys and value of a storage item, everything that determines where and how its
+/// values are stored.
+fn storage_layout(metadata: &Metadata, entry: &StorageEntryMetadata<PortableForm>) -> String {
+	match &entry.ty {
+		StorageEntryType::Plain(value) => metadata.type_signature(value.id()),
+		StorageEntryType::Map { hashers, key, value } => format!(
+			"map {:?} {} => {}",
+			hashers,
+			metadata.type_signature(key.id()),
+			metadata.type_signature(value.id())
+		),
+	}
+}
+
+/// The signed extensions with the encoding of their extra and additional signed data.
+fn signed_extensions(metadata: &Metadata) -> Vec<(String, String)> {
+	metadata
+		.extrinsic()
+		.signed_extensions
+		.iter()
+		.map(|extension| {
+			let layout = format!(
+				"{} / {}",
+				metadata.type_signature(extension.ty.id()),
+				metadata.type_signature(extension.additional_signed.id())
+			);
+			(extension.identifier.clone(), layout)
+		})
+		.collect()
+}
+
+/// The runtime version of a runtime, from the `Version` constant of `frame_system`.
+pub fn runtime_version(metadata: &Metadata) -> Option<RuntimeVersion> {
+	let system = metadata.pallets().iter().find(|pallet| pallet.name == "System")?;
+	let version = system.constants.iter().find(|constant| constant.name == "Version")?;
+	RuntimeVersion::decode(&mut &version.value[..]).ok()
+}
+
+/// Call the `Metadata_metadata` runtime api of the runtime in `blob`.
+pub fn runtime_metadata(
+	blob: RuntimeBlob,
+	wasm_method: WasmExecutionMethod,
+) -> Result<Metadata, String> {
+	let executor = WasmExecutor::new(wasm_method, None, crate::service::host_functions(), 1, None);
+	let raw = executor
+		.uncached_call(blob, &mut BasicExternalities::default(), true, "Metadata_metadata", &[])
+		.map_err(|e| format!("Failed to read the metadata of the runtime: {}", e))?;
+	// The runtime api returns `OpaqueMetadata`, which encodes as bytes.
+	let metadata = Vec::<u8>::decode(&mut &raw[..]).map_err(|e| e.to_string())?;
+	Metadata::from_bytes(&metadata)
+}
+
+/// Where to get the metadata of a runtime from.
+#[derive(Debug, Clone)]
+pub enum MetadataSource {
+	/// `native`: the runtime this node is built with.
+	Native,
+	/// An `http://` or `https://` RPC endpoint of a node, the metadata of its best block.
+	Rpc(String),
+	/// A file with a runtime Wasm blob, SCALE encoded metadata or the `0x` hex encoded result of
+	/// `state_getMetadata`.
+	File(PathBuf),
+}
+
+impl FromStr for MetadataSource {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"native" => MetadataSource::Native,
+			url if url.starts_with("http://") || url.starts_with("https://") =>
+				MetadataSource::Rpc(url.into()),
+			path => MetadataSource::File(path.into()),
+		})
+	}
+}
+
+impl MetadataSource {
+	/// Load the metadata, executing Wasm runtimes with `wasm_method`.
+	pub async fn load(&self, wasm_method: WasmExecutionMethod) -> Result<Metadata, String> {
+		match self {
+			MetadataSource::Native => Metadata::from_bytes(&Runtime::metadata().encode()),
+			MetadataSource::Rpc(url) => {
+				let state = http::connect::<StateClient<Hash>>(url)
+					.await
+					.map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+				let metadata = state
+					.metadata(None)
+					.await
+					.map_err(|e| format!("Failed to fetch the metadata from {}: {}", url, e))?;
+				Metadata::from_bytes(&metadata)
+			},
+			MetadataSource::File(path) => {
+				let bytes = std::fs::read(path)
+					.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+				load_file(bytes, wasm_method)
+			},
+		}
+	}
+}
+
+fn load_file(bytes: Vec<u8>, wasm_method: WasmExecutionMethod) -> Result<Metadata, String> {
+	// Encoded metadata starts with the magic number `meta`.
+	const MAGIC: &[u8] = b"meta";
+
+	if let Some(hex) = std::str::from_utf8(&bytes).ok().map(str::trim) {
+		if let Some(hex) = hex.strip_prefix("0x") {
+			let bytes = hex::decode(hex).map_err(|e| format!("Invalid hex: {}", e))?;
+			return Metadata::from_bytes(&bytes)
+		}
+	}
+	if bytes.starts_with(MAGIC) {
+		return Metadata::from_bytes(&bytes)
+	}
+	let blob =
+		RuntimeBlob::uncompress_if_needed(&bytes).map_err(|e| format!("Invalid runtime: {}", e))?;
+	runtime_metadata(blob, wasm_method)
+}
+
+/// The `metadata-diff` command.
+#[derive(Debug, StructOpt)]
+pub struct MetadataDiffCmd {
+	/// The old runtime: `native`, an `http://` RPC endpoint, or a file with a runtime Wasm blob or
+	/// with metadata.
+	pub old: MetadataSource,
+
+	/// The new runtime, see `old`.
+	pub new: MetadataSource,
+
+	/// Print JSON instead of human readable output.
+	#[structopt(long)]
+	pub json: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl MetadataDiffCmd {
+	/// Print the changes from the old to the new runtime.
+	///
+	/// Fails if the changes require a new `transaction_version`, but the new runtime does not
+	/// have one.
+	pub async fn run(&self, wasm_method: WasmExecutionMethod) -> sc_cli::Result<()> {
+		let old = self.old.load(wasm_method).await?;
+		let new = self.new.load(wasm_method).await?;
+		let diff = MetadataDiff::new(&old, &new);
+		let (old_version, new_version) = (runtime_version(&old), runtime_version(&new));
+		let versions = old_version.as_ref().zip(new_version.as_ref());
+
+		if self.json {
+			let json = diff.to_json(versions);
+			println!("{}", serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?);
+		} else {
+			diff.changes.iter().for_each(|change| {
+				let breaking = if change.breaks_signers { " (breaks signers)" } else { "" };
+				println!("{}{}", change, breaking);
+			});
+			if diff.changes.is_empty() {
+				println!("No changes");
+			}
+			if diff.requires_transaction_version_bump() {
+				println!("The transaction version must be bumped");
+			}
+		}
+
+		match versions {
+			Some((old, new))
+				if diff.requires_transaction_version_bump() &&
+					new.transaction_version <= old.transaction_version =>
+				Err(format!(
+					"The transaction version must be bumped, but it is {} in the new runtime",
+					new.transaction_version
+				)
+				.into()),
+			_ => Ok(()),
+		}
+	}
+}
+
+impl CliConfiguration for MetadataDiffCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
@@ -7,7 +7,9 @@
 
 use std::sync::Arc;
 
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use crate::{event_index::EventIndex, managed_tx::TxManager};
+use node_template_runtime::{opaque::Block, AccountId, AssetId, Balance, BlockNumber, Hash, Index};
+use sc_rpc::SubscriptionTaskExecutor;
 pub use sc_rpc_api::DenyUnsafe;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
@@ -22,6 +24,12 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// The event index, if indexing is enabled.
+	pub event_index: Option<Arc<EventIndex>>,
+	/// Signs and tracks the transactions of `author_submitManaged`.
+	pub tx_manager: Arc<TxManager<C, P>>,
+	/// Executor for subscriptions.
+	pub subscription_executor: SubscriptionTaskExecutor,
 }
 
 /// Instantiate all full RPC extensions.
@@ -29,22 +37,54 @@ pub fn create_full<C, P>(deps: FullDeps<C, P>) -> jsonrpc_core::IoHandler<sc_rpc
 where
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+	C: sc_client_api::BlockBackend<Block>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_asset_fees_runtime_api::AssetFeesApi<Block, Balance, AssetId>,
+	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
 	C::Api: BlockBuilder<Block>,
-	P: TransactionPool + 'static,
+	C::Api: sp_api::Core<Block>,
+	P: TransactionPool<Block = Block, Hash = Hash> + 'static,
 {
+	use pallet_contracts_rpc::{Contracts, ContractsApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps { client, pool, deny_unsafe, event_index, tx_manager, subscription_executor } =
+		deps;
 
 	io.extend_with(SystemApi::to_delegate(FullSystem::new(client.clone(), pool, deny_unsafe)));
 
 	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
 
+	{
+		use crate::asset_fees::{AssetFeesRpc, AssetFeesRpcApi};
+
+		io.extend_with(AssetFeesRpcApi::to_delegate(AssetFeesRpc::new(client.clone())));
+	}
+
+	io.extend_with(ContractsApi::to_delegate(Contracts::new(client)));
+
+	{
+		use crate::managed_tx::rpc::{ManagedTxApi, ManagedTxRpc};
+		use jsonrpc_pubsub::manager::SubscriptionManager;
+
+		let subscriptions = SubscriptionManager::new(Arc::new(subscription_executor));
+		io.extend_with(ManagedTxApi::to_delegate(ManagedTxRpc::new(
+			tx_manager,
+			subscriptions,
+			deny_unsafe,
+		)));
+	}
+
+	if let Some(index) = event_index {
+		use crate::event_index::rpc::{EventIndexApi, EventIndexRpc};
+
+		io.extend_with(EventIndexApi::to_delegate(EventIndexRpc::new(index)));
+	}
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
@@ -0,0 +1,291 @@
+//! Preparing runtime upgrades.
+//!
+//! The `runtime-upgrade` subcommand checks a compiled runtime against the runtime of the chain in
+//! the local database and builds the extrinsic that makes it the new runtime. That is a
+//! `set_code` call dispatched by the sudo key through `sudo_unchecked_weight`, as `set_code`
+//! takes up a full block. The signed extrinsic is printed, or submitted to a node over RPC.
+
+use crate::{
+	decode::{storage_prefix, Metadata},
+	extrinsic::{create_signed, ExtrinsicParams},
+	metadata_diff::{runtime_metadata, MetadataDiff},
+	service::FullClient,
+};
+use codec::{Decode, Encode};
+use jsonrpc_core_client::transports::http;
+use node_template_runtime::{
+	opaque::Block, AccountId, Call, Hash, Index, UncheckedExtrinsic, VERSION,
+};
+use sc_cli::{CliConfiguration, ImportParams, RuntimeVersion, SharedParams};
+use sc_client_api::StorageProvider;
+use sc_executor::{read_embedded_version, WasmExecutionMethod};
+use sc_executor_common::runtime_blob::RuntimeBlob;
+use sc_rpc_api::author::AuthorClient;
+use sp_api::{Metadata as MetadataApi, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::{sr25519, storage::StorageKey, Bytes, Pair};
+use sp_runtime::generic::{BlockId, Era};
+use std::{path::PathBuf, sync::Arc};
+use structopt::StructOpt;
+use substrate_frame_rpc_system::AccountNonceApi;
+
+/// The `runtime-upgrade` command.
+#[derive(Debug, StructOpt)]
+pub struct RuntimeUpgradeCmd {
+	/// The compiled runtime, e.g.
+	/// `target/release/wbuild/node-template-runtime/node_template_runtime.compact.compressed.
+	/// wasm`.
+	#[structopt(parse(from_os_str))]
+	pub wasm: PathBuf,
+
+	/// The secret URI of the sudo key, e.g. `//Alice`.
+	#[structopt(long)]
+	pub suri: String,
+
+	/// The nonce of the sudo key, by default the one at the best block of the local database.
+	#[structopt(long)]
+	pub nonce: Option<Index>,
+
+	/// Submit the extrinsic to the node with this HTTP RPC endpoint, e.g.
+	/// `http://localhost:9933`, instead of printing it.
+	#[structopt(long, value_name = "URL")]
+	pub submit: Option<String>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl RuntimeUpgradeCmd {
+	/// Check the runtime against the best block of `client` and emit the upgrade extrinsic.
+	pub async fn run(
+		&self,
+		client: Arc<FullClient>,
+		wasm_method: WasmExecutionMethod,
+	) -> sc_cli::Result<()> {
+		let code = std::fs::read(&self.wasm)
+			.map_err(|e| format!("Failed to read {}: {}", self.wasm.display(), e))?;
+		let blob = RuntimeBlob::uncompress_if_needed(&code)
+			.map_err(|e| format!("Invalid runtime: {}", e))?;
+
+		let at = BlockId::Hash(client.info().best_hash);
+		let current = client.runtime_version_at(&at).map_err(|e| e.to_string())?;
+		let new = read_embedded_version(&blob)
+			.map_err(|e| format!("Invalid runtime: {}", e))?
+			.ok_or("The runtime has no embedded version, build it with `substrate-wasm-builder`")?;
+		check_versions(&current, &new)?;
+
+		let current_metadata =
+			Metadata::from_bytes(&client.runtime_api().metadata(&at).map_err(|e| e.to_string())?)?;
+		let new_metadata = runtime_metadata(blob, wasm_method)?;
+		check_transaction_version(&current, &current_metadata, &new, &new_metadata)?;
+		log::info!(
+			"Upgrading from {} to {}, transaction version {} to {}",
+			current.spec_version,
+			new.spec_version,
+			current.transaction_version,
+			new.transaction_version
+		);
+
+		let extrinsic = self.upgrade_extrinsic(&client, &at, &current, code)?;
+		match &self.submit {
+			None => println!("0x{}", hex::encode(extrinsic.encode())),
+			Some(url) => println!("{:?}", submit(url, extrinsic).await?),
+		}
+		Ok(())
+	}
+
+	/// Build the extrinsic that sets `code` as the runtime, signed by the sudo key.
+	fn upgrade_extrinsic(
+		&self,
+		client: &FullClient,
+		at: &BlockId<Block>,
+		current: &RuntimeVersion,
+		code: Vec<u8>,
+	) -> Result<UncheckedExtrinsic, String> {
+		// The call is encoded by the runtime of this node, which only matches the encoding of the
+		// chain for the same transaction version.
+		if current.transaction_version != VERSION.transaction_version {
+			return Err(format!(
+				"The node is built for transaction version {}, but the chain is at {}",
+				VERSION.transaction_version, current.transaction_version
+			))
+		}
+
+		let pair = sr25519::Pair::from_string(&self.suri, None)
+			.map_err(|e| format!("Invalid secret URI: {:?}", e))?;
+		let signer = AccountId::from(pair.public());
+		let sudo_key = client
+			.storage(at, &StorageKey(storage_prefix("Sudo", "Key")))
+			.map_err(|e| e.to_string())?
+			.and_then(|raw| AccountId::decode(&mut &raw.0[..]).ok());
+		if sudo_key.as_ref() != Some(&signer) {
+			return Err(format!("{} is not the sudo key", signer))
+		}
+
+		let nonce = match self.nonce {
+			Some(nonce) => nonce,
+			None => client
+				.runtime_api()
+				.account_nonce(at, signer)
+				.map_err(|e| format!("Failed to read the nonce of the sudo key: {}", e))?,
+		};
+		let genesis_hash = client.info().genesis_hash;
+		let params = ExtrinsicParams {
+			nonce,
+			tip: 0,
+			sponsor: None,
+			era: Era::Immortal,
+			era_block_hash: genesis_hash,
+			genesis_hash,
+			spec_version: current.spec_version,
+			transaction_version: current.transaction_version,
+		};
+		let call = Call::Sudo(pallet_sudo::Call::sudo_unchecked_weight {
+			call: Box::new(Call::System(frame_system::Call::set_code { code })),
+			weight: 0,
+		});
+		Ok(create_signed(call, &pair, &params))
+	}
+}
+
+impl CliConfiguration for RuntimeUpgradeCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
+
+/// Only newer versions of this runtime can replace the current one.
+fn check_versions(current: &RuntimeVersion, new: &RuntimeVersion) -> Result<(), String> {
+	if new.spec_name != VERSION.spec_name {
+		return Err(format!("The runtime is `{}`, expected `{}`", new.spec_name, VERSION.spec_name))
+	}
+	if new.spec_version <= current.spec_version {
+		return Err(format!(
+			"The runtime has spec version {}, it has to be greater than the current {}",
+			new.spec_version, current.spec_version
+		))
+	}
+	if new.transaction_version < current.transaction_version {
+		return Err(format!(
+			"The runtime has transaction version {}, it must not be lower than the current {}",
+			new.transaction_version, current.transaction_version
+		))
+	}
+	Ok(())
+}
+
+/// Changes to the encoding of calls require a new transaction version.
+fn check_transaction_version(
+	current: &RuntimeVersion,
+	current_metadata: &Metadata,
+	new: &RuntimeVersion,
+	new_metadata: &Metadata,
+) -> Result<(), String> {
+	let diff = MetadataDiff::new(current_metadata, new_metadata);
+	if diff.requires_transaction_version_bump() &&
+		new.transaction_version == current.transaction_version
+	{
+		let changes = diff.breaking_changes().map(ToString::to_string).collect::<Vec<_>>();
+		return Err(format!(
+			"The encoding of calls changed, `transaction_version` has to be bumped from {}:\n  {}",
+			current.transaction_version,
+			changes.join("\n  ")
+		))
+	}
+	Ok(())
+}
+
+/// Submit `extrinsic` to the node at `url`, returns the hash of the extrinsic.
+async fn submit(url: &str, extrinsic: UncheckedExtrinsic) -> Result<Hash, String> {
+	let author = http::connect::<AuthorClient<Hash, Hash>>(url)
+		.await
+		.map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+	author
+		.submit_extrinsic(Bytes(extrinsic.encode()))
+		.await
+		.map_err(|e| format!("Failed to submit the extrinsic: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+	use node_template_runtime::Runtime;
+
+	fn version(spec_version: u32, transaction_version: u32) -> RuntimeVersion {
+		RuntimeVersion { spec_version, transaction_version, ..VERSION }
+	}
+
+	fn metadata(f: impl FnOnce(&mut RuntimeMetadataPrefixed)) -> Metadata {
+		let mut prefixed = Runtime::metadata();
+		f(&mut prefixed);
+		Metadata::from_bytes(&prefixed.encode()).unwrap()
+	}
+
+	/// The native metadata with the `Balances` pallet moved to another index.
+	fn moved_balances(prefixed: &mut RuntimeMetadataPrefixed) {
+		match &mut prefixed.1 {
+			RuntimeMetadata::V14(metadata) => {
+				let balances = metadata.pallets.iter_mut().find(|p| p.name == "Balances").unwrap();
+				balances.index = 200;
+			},
+			_ => unreachable!("the runtime has V14 metadata"),
+		}
+	}
+
+	#[test]
+	fn newer_versions_pass() {
+		assert_eq!(check_versions(&version(1, 1), &version(2, 1)), Ok(()));
+		assert_eq!(check_versions(&version(1, 1), &version(2, 2)), Ok(()));
+	}
+
+	#[test]
+	fn other_runtimes_are_rejected() {
+		let other = RuntimeVersion { spec_name: "other".into(), ..version(2, 1) };
+		let err = check_versions(&version(1, 1), &other).unwrap_err();
+		assert!(err.contains("`other`"), "{}", err);
+	}
+
+	#[test]
+	fn spec_version_has_to_increase() {
+		assert!(check_versions(&version(2, 1), &version(2, 1)).is_err());
+		assert!(check_versions(&version(2, 1), &version(1, 1)).is_err());
+	}
+
+	#[test]
+	fn transaction_version_must_not_decrease() {
+		let err = check_versions(&version(1, 2), &version(2, 1)).unwrap_err();
+		assert!(err.contains("transaction version 1"), "{}", err);
+	}
+
+	#[test]
+	fn changed_calls_require_a_new_transaction_version() {
+		let (current, moved) = (metadata(|_| ()), metadata(moved_balances));
+
+		let err = check_transaction_version(&version(1, 1), &current, &version(2, 1), &moved)
+			.unwrap_err();
+		assert!(err.contains("Balances"), "{}", err);
+		assert_eq!(
+			check_transaction_version(&version(1, 1), &current, &version(2, 2), &moved),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn unchanged_calls_keep_the_transaction_version() {
+		let current = metadata(|_| ());
+		assert_eq!(
+			check_transaction_version(&version(1, 1), &current, &version(2, 1), &current),
+			Ok(())
+		);
+	}
+}
@@ -1,7 +1,7 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::{ExecutorProvider, RemoteBackend};
+use sc_client_api::RemoteBackend;
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
@@ -10,11 +10,18 @@ use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_consensus::SlotData;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use std::{path::Path, sync::Arc, time::Duration};
 
-// Our native executor instance.
+// Benchmarks and `try-runtime` execute the native runtime.
+#[cfg(all(feature = "wasm-only", any(feature = "runtime-benchmarks", feature = "try-runtime")))]
+compile_error!("`runtime-benchmarks` and `try-runtime` can't be combined with `wasm-only`");
+
+// Our native executor instance. Builds with the `wasm-only` feature don't have one, which keeps
+// the native implementation of the runtime out of the binary.
+#[cfg(not(feature = "wasm-only"))]
 pub struct ExecutorDispatch;
 
+#[cfg(not(feature = "wasm-only"))]
 impl sc_executor::NativeExecutionDispatch for ExecutorDispatch {
 	/// Only enable the benchmarking host functions when we actually want to benchmark.
 	#[cfg(feature = "runtime-benchmarks")]
@@ -32,9 +39,90 @@ impl sc_executor::NativeExecutionDispatch for ExecutorDispatch {
 	}
 }
 
-type FullClient =
-	sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
-type FullBackend = sc_service::TFullBackend<Block>;
+/// Executes the native runtime if it has the same version as the on-chain Wasm runtime.
+#[cfg(not(feature = "wasm-only"))]
+pub type Executor = NativeElseWasmExecutor<ExecutorDispatch>;
+/// Only executes the on-chain Wasm runtime, the native runtime is never called.
+#[cfg(feature = "wasm-only")]
+pub type Executor = sc_executor::WasmExecutor;
+
+/// Create the executor of the runtime as configured in `config`.
+#[cfg(not(feature = "wasm-only"))]
+pub fn new_executor(config: &Configuration) -> Executor {
+	NativeElseWasmExecutor::<ExecutorDispatch>::new(
+		config.wasm_method,
+		config.default_heap_pages,
+		config.max_runtime_instances,
+	)
+}
+
+/// Create the executor of the runtime as configured in `config`.
+#[cfg(feature = "wasm-only")]
+pub fn new_executor(config: &Configuration) -> Executor {
+	sc_executor::WasmExecutor::new(
+		config.wasm_method,
+		config.default_heap_pages,
+		host_functions(),
+		config.max_runtime_instances,
+		None,
+	)
+}
+
+/// The host functions available to the runtime, the same as `NativeElseWasmExecutor` provides.
+pub fn host_functions() -> Vec<&'static dyn sp_wasm_interface::Function> {
+	use sp_wasm_interface::HostFunctions;
+
+	let mut host_functions = Vec::new();
+	#[cfg(feature = "runtime-benchmarks")]
+	host_functions.extend(frame_benchmarking::benchmarking::HostFunctions::host_functions());
+	host_functions.extend(sp_io::SubstrateHostFunctions::host_functions());
+	host_functions
+}
+
+/// Whether the node may author blocks on top of a block.
+pub enum CanAuthorWith {
+	/// Only with a native runtime of the same version as the on-chain one.
+	#[cfg(not(feature = "wasm-only"))]
+	NativeVersion(sp_consensus::CanAuthorWithNativeVersion<Executor>),
+	/// Always, blocks are built with the on-chain runtime.
+	Always,
+}
+
+impl sp_consensus::CanAuthorWith<Block> for CanAuthorWith {
+	#[cfg_attr(feature = "wasm-only", allow(unused_variables))]
+	fn can_author_with(&self, at: &sp_runtime::generic::BlockId<Block>) -> Result<(), String> {
+		match self {
+			#[cfg(not(feature = "wasm-only"))]
+			CanAuthorWith::NativeVersion(native) => native.can_author_with(at),
+			CanAuthorWith::Always => Ok(()),
+		}
+	}
+}
+
+/// Blocks can only be authored with a native runtime of the same version as the on-chain one,
+/// unless the node executes only the on-chain runtime: without the `wasm-only` feature, with
+/// `wasm_only`.
+#[cfg(not(feature = "wasm-only"))]
+fn can_author_with(client: &FullClient, wasm_only: bool) -> CanAuthorWith {
+	use sc_client_api::ExecutorProvider;
+
+	if wasm_only {
+		return CanAuthorWith::Always
+	}
+	CanAuthorWith::NativeVersion(sp_consensus::CanAuthorWithNativeVersion::new(
+		client.executor().clone(),
+	))
+}
+
+/// Without a native runtime, blocks are always authored with the on-chain one.
+#[cfg(feature = "wasm-only")]
+fn can_author_with(_: &FullClient, _wasm_only: bool) -> CanAuthorWith {
+	CanAuthorWith::Always
+}
+
+pub type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
+pub type FullBackend = sc_service::TFullBackend<Block>;
+pub type FullPool = sc_transaction_pool::FullPool<Block, FullClient>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 
 pub fn new_partial(
@@ -45,7 +133,7 @@ pub fn new_partial(
 		FullBackend,
 		FullSelectChain,
 		sc_consensus::DefaultImportQueue<Block, FullClient>,
-		sc_transaction_pool::FullPool<Block, FullClient>,
+		FullPool,
 		(
 			sc_finality_grandpa::GrandpaBlockImport<
 				FullBackend,
@@ -74,11 +162,7 @@ pub fn new_partial(
 		})
 		.transpose()?;
 
-	let executor = NativeElseWasmExecutor::<ExecutorDispatch>::new(
-		config.wasm_method,
-		config.default_heap_pages,
-		config.max_runtime_instances,
-	);
+	let executor = new_executor(&config);
 
 	let (client, backend, keystore_container, task_manager) =
 		sc_service::new_full_parts::<Block, RuntimeApi, _>(
@@ -129,9 +213,8 @@ pub fn new_partial(
 				Ok((timestamp, slot))
 			},
 			spawner: &task_manager.spawn_essential_handle(),
-			can_author_with: sp_consensus::CanAuthorWithNativeVersion::new(
-				client.executor().clone(),
-			),
+			// Only skips checking the inherents of imported blocks.
+			can_author_with: can_author_with(&client, false),
 			registry: config.prometheus_registry(),
 			check_for_equivocation: Default::default(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
@@ -157,7 +240,18 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+///
+/// With `enable_event_index` the node also maintains an index of runtime events next to its
+/// database, see [`crate::event_index`]. The transactions of each priority lane of the pool are
+/// kept within `lane_limits`, see [`crate::lanes`]. With `wasm_only` the node authors blocks even
+/// if its native runtime differs from the on-chain one, the caller configures it to execute only
+/// the on-chain runtime.
+pub fn new_full(
+	mut config: Configuration,
+	enable_event_index: bool,
+	lane_limits: &[crate::lanes::LaneLimit],
+	wasm_only: bool,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -214,13 +308,54 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	let event_index = if enable_event_index {
+		let path = config
+			.database
+			.path()
+			.and_then(Path::parent)
+			.ok_or_else(|| {
+				ServiceError::Other("The event index requires an on-disk database.".into())
+			})?
+			.join("event_index");
+		let index = Arc::new(crate::event_index::EventIndex::open(&path).map_err(|e| {
+			ServiceError::Other(format!("Error opening the event index at {:?}: {}", path, e))
+		})?);
+		// Catching up with finality may take a while, keep it off the async executor.
+		task_manager.spawn_handle().spawn_blocking(
+			"event-index",
+			crate::event_index::Indexer::<_, FullBackend>::new(client.clone(), index.clone()).run(),
+		);
+		Some(index)
+	} else {
+		None
+	};
+
+	if !lane_limits.is_empty() {
+		task_manager.spawn_handle().spawn(
+			"txpool-lanes",
+			crate::lanes::LaneEnforcer::new(client.clone(), transaction_pool.clone(), lane_limits)
+				.run(),
+		);
+	}
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let tx_manager = Arc::new(crate::managed_tx::TxManager::new(
+			client.clone(),
+			pool.clone(),
+			keystore_container.sync_keystore(),
+		));
 
-		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				event_index: event_index.clone(),
+				tx_manager: tx_manager.clone(),
+				subscription_executor,
+			};
 
 			Ok(crate::rpc::create_full(deps))
 		})
@@ -250,8 +385,7 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 			telemetry.as_ref().map(|x| x.handle()),
 		);
 
-		let can_author_with =
-			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
+		let can_author_with = can_author_with(&client, wasm_only);
 
 		let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
 		let raw_slot_duration = slot_duration.slot_duration();
@@ -349,11 +483,7 @@ pub fn new_light(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		})
 		.transpose()?;
 
-	let executor = NativeElseWasmExecutor::<ExecutorDispatch>::new(
-		config.wasm_method,
-		config.default_heap_pages,
-		config.max_runtime_instances,
-	);
+	let executor = new_executor(&config);
 
 	let (client, backend, keystore_container, mut task_manager, on_demand) =
 		sc_service::new_light_parts::<Block, RuntimeApi, _>(
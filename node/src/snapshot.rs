@@ -0,0 +1,48 @@
+//! Conversion of exported state into snapshots for `try-runtime`.
+//!
+//! `export-state` writes the state of a block as the raw genesis of a chain spec, while
+//! `try-runtime ... snap` reads the SCALE encoded list of top level key value pairs written by
+//! `remote-externalities`. [`StateToSnapshotCmd`] converts the former into the latter, so runtime
+//! upgrades can be tried against exported state without a running node.
+
+use crate::chain_spec::ChainSpec;
+use codec::Encode;
+use sp_core::storage::{StorageData, StorageKey};
+use sp_runtime::BuildStorage;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// The `state-to-snapshot` command.
+#[derive(Debug, StructOpt)]
+pub struct StateToSnapshotCmd {
+	/// The chain spec written by `export-state`.
+	#[structopt(long, parse(from_os_str))]
+	pub input: PathBuf,
+
+	/// Where to write the snapshot.
+	#[structopt(long, parse(from_os_str))]
+	pub output: PathBuf,
+}
+
+impl StateToSnapshotCmd {
+	/// Run the command.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let spec = ChainSpec::from_json_file(self.input.clone())?;
+		let storage = spec.build_storage()?;
+		if !storage.children_default.is_empty() {
+			log::warn!(
+				"Skipping {} child tries, snapshots only hold the top trie",
+				storage.children_default.len()
+			);
+		}
+
+		let pairs = storage
+			.top
+			.into_iter()
+			.map(|(key, value)| (StorageKey(key), StorageData(value)))
+			.collect::<Vec<_>>();
+		std::fs::write(&self.output, pairs.encode())?;
+		log::info!("Wrote {} keys to {}", pairs.len(), self.output.display());
+		Ok(())
+	}
+}
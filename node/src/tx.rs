@@ -0,0 +1,293 @@
+//! Offline construction, signing and verification of extrinsics.
+//!
+//! The `tx` subcommand never talks to a node: everything that a signed extrinsic commits to, like
+//! the nonce, the genesis hash and the runtime versions, is given on the command line. That makes
+//! it usable on an air-gapped machine holding the keys. Calls are encoded through the metadata of
+//! the runtime this node is built with.
+
+use crate::{
+	decode::Metadata,
+	extrinsic::{create_signed_with, ExtrinsicParams},
+	inspect::{decode_extrinsic, print_human},
+};
+use codec::{Decode, Encode};
+use node_template_runtime::{
+	AccountId, Balance, BlockNumber, Call, Hash, Index, Runtime, Signature, UncheckedExtrinsic,
+};
+use sc_cli::{utils::pair_from_suri, KeystoreParams};
+use sc_keystore::LocalKeystore;
+use serde_json::{json, Value as Json};
+use sp_core::{
+	crypto::{CryptoTypePublicPair, KeyTypeId, Ss58Codec},
+	sr25519, Pair,
+};
+use sp_keystore::SyncCryptoStore;
+use sp_runtime::{
+	generic::{Era, SignedPayload},
+	traits::Verify,
+	MultiAddress,
+};
+use std::convert::TryFrom;
+use structopt::StructOpt;
+
+/// The `tx` command.
+#[derive(Debug, StructOpt)]
+pub enum TxCmd {
+	/// Build and sign an extrinsic, print it as SCALE encoded hex.
+	Build(BuildCmd),
+
+	/// Decode an extrinsic and verify its signature.
+	Decode(DecodeCmd),
+}
+
+impl TxCmd {
+	/// Run the command.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let metadata = Metadata::from_bytes(&Runtime::metadata().encode())?;
+		match self {
+			TxCmd::Build(cmd) => cmd.run(&metadata),
+			TxCmd::Decode(cmd) => cmd.run(&metadata),
+		}
+	}
+}
+
+/// What the additional signed data of an extrinsic commits to.
+#[derive(Debug, StructOpt)]
+pub struct ChainParams {
+	/// The genesis hash of the chain.
+	#[structopt(long)]
+	pub genesis_hash: Hash,
+
+	/// The `spec_version` of the runtime the extrinsic is for.
+	#[structopt(long)]
+	pub spec_version: u32,
+
+	/// The `transaction_version` of the runtime the extrinsic is for.
+	#[structopt(long)]
+	pub transaction_version: u32,
+
+	/// The hash of the block a mortal extrinsic is valid from.
+	#[structopt(long)]
+	pub era_block_hash: Option<Hash>,
+}
+
+/// The `tx build` command.
+#[derive(Debug, StructOpt)]
+pub struct BuildCmd {
+	/// The call as `Pallet.call`, e.g. `Balances.transfer`, or as `0x` hex encoded call.
+	pub call: String,
+
+	/// The arguments of the call as JSON, e.g. `{"dest": "5FHn...", "value": 1000}`.
+	pub args: Option<String>,
+
+	/// The nonce of the signer.
+	#[structopt(long)]
+	pub nonce: Index,
+
+	/// The tip for the block author.
+	#[structopt(long, default_value = "0")]
+	pub tip: Balance,
+
+	/// The SS58 address of the account paying the fee instead of the signer, which needs a
+	/// sponsorship policy covering the call.
+	#[structopt(long)]
+	pub sponsor: Option<AccountId>,
+
+	/// Make the extrinsic mortal, valid for this many blocks from `--era-block` on. Immortal if
+	/// not given.
+	#[structopt(long, requires_all = &["era-block", "era-block-hash"])]
+	pub era_period: Option<u64>,
+
+	/// The number of the block the mortal extrinsic is valid from.
+	#[structopt(long)]
+	pub era_block: Option<BlockNumber>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub chain: ChainParams,
+
+	/// The secret URI of the signer, e.g. `//Alice`. Alternatively sign with a key of the
+	/// keystore given by `--signer`.
+	#[structopt(long, conflicts_with = "signer", required_unless = "signer")]
+	pub suri: Option<String>,
+
+	/// The SS58 address of the sr25519 key in the keystore to sign with.
+	#[structopt(long, requires = "keystore-path")]
+	pub signer: Option<String>,
+
+	/// The key type of the key in the keystore.
+	#[structopt(long, default_value = "acco", parse(try_from_str = parse_key_type))]
+	pub key_type: KeyTypeId,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl BuildCmd {
+	fn run(&self, metadata: &Metadata) -> sc_cli::Result<()> {
+		let call = self.call(metadata)?;
+		let era = match (self.era_period, self.era_block) {
+			(Some(period), Some(block)) => Era::mortal(period, block.into()),
+			_ => Era::Immortal,
+		};
+		let params = ExtrinsicParams {
+			nonce: self.nonce,
+			tip: self.tip,
+			sponsor: self.sponsor.clone(),
+			era,
+			era_block_hash: self.chain.era_block_hash.unwrap_or(self.chain.genesis_hash),
+			genesis_hash: self.chain.genesis_hash,
+			spec_version: self.chain.spec_version,
+			transaction_version: self.chain.transaction_version,
+		};
+		let password = self.keystore_params.read_password()?;
+
+		let extrinsic = match (&self.suri, &self.signer) {
+			(Some(suri), _) => {
+				let pair = pair_from_suri::<sr25519::Pair>(suri, password)?;
+				create_signed_with(call, pair.public().into(), &params, |payload| {
+					Ok::<_, String>(Signature::Sr25519(pair.sign(payload)))
+				})?
+			},
+			(None, Some(signer)) => {
+				let public = sr25519::Public::from_ss58check(signer)
+					.map_err(|e| format!("Invalid signer {}: {:?}", signer, e))?;
+				let path = self.keystore_params.keystore_path.as_ref().ok_or("No keystore path")?;
+				let keystore = LocalKeystore::open(path, password)
+					.map_err(|e| format!("Failed to open the keystore: {}", e))?;
+				let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, public.to_raw_vec());
+				create_signed_with(call, public.into(), &params, |payload| {
+					let signature =
+						SyncCryptoStore::sign_with(&keystore, self.key_type, &key, payload)
+							.map_err(|e| format!("Failed to sign: {}", e))?
+							.ok_or_else(|| format!("{} is not in the keystore", signer))?;
+					sr25519::Signature::try_from(&signature[..])
+						.map(Signature::Sr25519)
+						.map_err(|_| "The keystore returned an invalid signature".to_string())
+				})?
+			},
+			(None, None) => return Err("Either `--suri` or `--signer` is required".into()),
+		};
+
+		println!("0x{}", hex::encode(extrinsic.encode()));
+		Ok(())
+	}
+
+	/// The call of the runtime this node is built with.
+	fn call(&self, metadata: &Metadata) -> Result<Call, String> {
+		let encoded = match self.call.strip_prefix("0x") {
+			Some(hex) => hex::decode(hex).map_err(|e| format!("Invalid call: {}", e))?,
+			None => {
+				let (pallet, call) =
+					self.call.split_once('.').ok_or("Expected the call as `Pallet.call`")?;
+				let args = match &self.args {
+					Some(args) => serde_json::from_str(args)
+						.map_err(|e| format!("The arguments are not JSON: {}", e))?,
+					None => Json::Null,
+				};
+				metadata.encode_call(pallet, call, &args)?
+			},
+		};
+
+		let input = &mut &encoded[..];
+		let call = Call::decode(input).map_err(|e| format!("Not a call of this runtime: {}", e))?;
+		if !input.is_empty() {
+			return Err(format!("{} trailing bytes after the call", input.len()))
+		}
+		Ok(call)
+	}
+}
+
+/// The `tx decode` command.
+#[derive(Debug, StructOpt)]
+pub struct DecodeCmd {
+	/// The `0x` hex encoded extrinsic.
+	pub extrinsic: String,
+
+	/// Verify the signature, which commits to the chain parameters besides the extrinsic.
+	#[structopt(long, requires_all = &["genesis-hash", "spec-version", "transaction-version"])]
+	pub verify: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub chain: OptionalChainParams,
+
+	/// Print JSON instead of human readable output.
+	#[structopt(long)]
+	pub json: bool,
+}
+
+/// [`ChainParams`] that are only needed to verify signatures.
+#[derive(Debug, StructOpt)]
+pub struct OptionalChainParams {
+	/// The genesis hash of the chain.
+	#[structopt(long)]
+	pub genesis_hash: Option<Hash>,
+
+	/// The `spec_version` of the runtime the extrinsic is for.
+	#[structopt(long)]
+	pub spec_version: Option<u32>,
+
+	/// The `transaction_version` of the runtime the extrinsic is for.
+	#[structopt(long)]
+	pub transaction_version: Option<u32>,
+
+	/// The hash of the block a mortal extrinsic is valid from.
+	#[structopt(long)]
+	pub era_block_hash: Option<Hash>,
+}
+
+impl DecodeCmd {
+	fn run(&self, metadata: &Metadata) -> sc_cli::Result<()> {
+		let encoded = hex::decode(self.extrinsic.trim_start_matches("0x"))
+			.map_err(|e| format!("Invalid hex: {}", e))?;
+		let mut output = decode_extrinsic(metadata, &encoded);
+
+		if let (true, Json::Object(map)) = (self.verify, &mut output) {
+			let valid = self.verify_signature(&encoded)?;
+			map.insert("signatureValid".into(), json!(valid));
+		}
+
+		if self.json {
+			println!("{}", serde_json::to_string_pretty(&output).map_err(|e| e.to_string())?);
+		} else {
+			print_human(&output, 0);
+		}
+		Ok(())
+	}
+
+	fn verify_signature(&self, encoded: &[u8]) -> Result<bool, String> {
+		let extrinsic = UncheckedExtrinsic::decode(&mut &encoded[..])
+			.map_err(|e| format!("Not an extrinsic of this runtime: {}", e))?;
+		let (address, signature, extra) =
+			extrinsic.signature.ok_or("The extrinsic is not signed")?;
+		let signer: AccountId = match address {
+			MultiAddress::Id(who) => who,
+			other => return Err(format!("Can not verify signatures of {:?}", other)),
+		};
+
+		let genesis_hash = self.chain.genesis_hash.ok_or("`--genesis-hash` is required")?;
+		// Only the fields of the additional signed data matter here.
+		let params = ExtrinsicParams {
+			nonce: 0,
+			tip: 0,
+			sponsor: None,
+			era: Era::Immortal,
+			era_block_hash: self.chain.era_block_hash.unwrap_or(genesis_hash),
+			genesis_hash,
+			spec_version: self.chain.spec_version.ok_or("`--spec-version` is required")?,
+			transaction_version: self
+				.chain
+				.transaction_version
+				.ok_or("`--transaction-version` is required")?,
+		};
+		let payload =
+			SignedPayload::from_raw(extrinsic.function, extra, params.additional_signed());
+		Ok(payload.using_encoded(|payload| signature.verify(payload, &signer)))
+	}
+}
+
+fn parse_key_type(s: &str) -> Result<KeyTypeId, String> {
+	KeyTypeId::try_from(s).map_err(|_| format!("Invalid key type {}, expected 4 characters", s))
+}
@@ -0,0 +1,26 @@
+//! Runtime API to quote transaction fees in assets.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
+use sp_runtime::traits::MaybeDisplay;
+
+sp_api::decl_runtime_apis! {
+	pub trait AssetFeesApi<Balance, AssetId> where
+		Balance: Codec + MaybeDisplay,
+		AssetId: Codec,
+	{
+		/// The dispatch info of `uxt` with its partial fee in `asset`, `None` if fees can not be
+		/// paid in `asset`.
+		fn query_info_in_asset(
+			uxt: Block::Extrinsic,
+			len: u32,
+			asset: AssetId,
+		) -> Option<RuntimeDispatchInfo<Balance>>;
+
+		/// The amount of `asset` that pays a fee of `fee` in the native currency, `None` if fees
+		/// can not be paid in `asset`.
+		fn quote(asset: AssetId, fee: Balance) -> Option<Balance>;
+	}
+}
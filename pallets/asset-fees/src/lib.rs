@@ -0,0 +1,225 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Asset Fees Pallet
+//!
+//! Lets accounts pay transaction fees in assets instead of the native currency. The `RateOrigin`
+//! registers an asset for fee payment by setting its rate, the amount of the asset that pays for
+//! one unit of the native currency. An account chooses the asset it pays its fees in with
+//! [`Call::set_fee_asset`].
+//!
+//! [`AssetsOrNative`] is the `OnChargeTransaction` of `pallet_transaction_payment`. Fees are
+//! computed in the native currency as before and converted at the rate of the fee asset of the
+//! payer. The converted fee is burned before dispatch, and the part that was not used is minted
+//! back after dispatch, like the native currency adapter does. Accounts without a fee asset pay
+//! through the wrapped native `OnChargeTransaction`.
+//!
+//! The fee in an asset is quoted by [`Pallet::quote`], which backs the `AssetFeesApi` runtime
+//! API.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use frame_support::traits::tokens::fungibles::{Inspect, Mutate};
+use pallet_transaction_payment::OnChargeTransaction;
+use sp_runtime::{
+	traits::{DispatchInfoOf, PostDispatchInfoOf, Saturating, UniqueSaturatedInto, Zero},
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+	FixedPointNumber, FixedPointOperand, FixedU128,
+};
+use sp_std::marker::PhantomData;
+
+/// The identifier of an asset.
+pub type AssetIdOf<T> =
+	<<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+
+/// The balance of an asset.
+pub type AssetBalanceOf<T> =
+	<<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_transaction_payment::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The assets fees can be paid in.
+		type Assets: Mutate<Self::AccountId>;
+
+		/// The origin that registers assets for fee payment and sets their rates.
+		type RateOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// The amount of an asset that pays for one unit of the native currency, by asset. Fees can
+	/// only be paid in assets with a rate.
+	#[pallet::storage]
+	#[pallet::getter(fn rate)]
+	pub type Rates<T: Config> = StorageMap<_, Blake2_128Concat, AssetIdOf<T>, FixedU128>;
+
+	/// The asset each account pays its fees in, the native currency if none.
+	#[pallet::storage]
+	#[pallet::getter(fn fee_asset)]
+	pub type FeeAsset<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, AssetIdOf<T>>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The rate of an asset was set, `None` if fees can no longer be paid in it.
+		/// [asset, rate]
+		RateSet(AssetIdOf<T>, Option<FixedU128>),
+		/// An account chose the asset it pays fees in, `None` for the native currency.
+		/// [who, asset]
+		FeeAssetSet(T::AccountId, Option<AssetIdOf<T>>),
+		/// A transaction fee was paid in an asset. [who, asset, amount]
+		AssetFeePaid(T::AccountId, AssetIdOf<T>, AssetBalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Fees can not be paid in the asset.
+		NotRegistered,
+		/// A rate of zero would make fees in the asset free.
+		ZeroRate,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set the rate of `asset`, or stop accepting fees in it with `None`.
+		///
+		/// Accounts paying in an asset that is no longer accepted can not pay fees until they
+		/// choose another asset. The rate must not be zero.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_rate(
+			origin: OriginFor<T>,
+			asset: AssetIdOf<T>,
+			rate: Option<FixedU128>,
+		) -> DispatchResult {
+			T::RateOrigin::ensure_origin(origin)?;
+			ensure!(!rate.map_or(false, |rate| rate.is_zero()), Error::<T>::ZeroRate);
+			Rates::<T>::mutate(asset, |current| *current = rate);
+			Self::deposit_event(Event::RateSet(asset, rate));
+			Ok(())
+		}
+
+		/// Pay the fees of the sender in `asset`, or in the native currency with `None`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_fee_asset(origin: OriginFor<T>, asset: Option<AssetIdOf<T>>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			if let Some(asset) = asset {
+				ensure!(Rates::<T>::contains_key(asset), Error::<T>::NotRegistered);
+			}
+			FeeAsset::<T>::mutate(&who, |current| *current = asset);
+			Self::deposit_event(Event::FeeAssetSet(who, asset));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T>
+	where
+		AssetBalanceOf<T>: FixedPointOperand,
+	{
+		/// The amount of `asset` that pays a fee of `fee` in the native currency, `None` if fees
+		/// can not be paid in `asset`.
+		pub fn quote(
+			asset: AssetIdOf<T>,
+			fee: impl UniqueSaturatedInto<u128>,
+		) -> Option<AssetBalanceOf<T>> {
+			let fee: u128 = fee.unique_saturated_into();
+			Rates::<T>::get(asset).map(|rate| rate.saturating_mul_int(fee.unique_saturated_into()))
+		}
+	}
+}
+
+/// What [`AssetsOrNative`] withdrew from the payer before dispatch.
+pub enum Withdrawn<AssetId, AssetBalance, NativeLiquidity> {
+	/// The fee was paid in the native currency.
+	Native(NativeLiquidity),
+	/// The given amount of an asset was burned.
+	Asset(AssetId, AssetBalance),
+}
+
+impl<A, B, N: Default> Default for Withdrawn<A, B, N> {
+	fn default() -> Self {
+		Withdrawn::Native(N::default())
+	}
+}
+
+/// Charges fees in the fee asset of the payer, or through `Native` in the native currency.
+pub struct AssetsOrNative<T, Native>(PhantomData<(T, Native)>);
+
+impl<T, Native> OnChargeTransaction<T> for AssetsOrNative<T, Native>
+where
+	T: Config,
+	Native: OnChargeTransaction<T>,
+	AssetBalanceOf<T>: FixedPointOperand,
+{
+	type Balance = Native::Balance;
+	type LiquidityInfo = Withdrawn<AssetIdOf<T>, AssetBalanceOf<T>, Native::LiquidityInfo>;
+
+	fn withdraw_fee(
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::Call,
+		info: &DispatchInfoOf<<T as frame_system::Config>::Call>,
+		fee: Self::Balance,
+		tip: Self::Balance,
+	) -> Result<Self::LiquidityInfo, TransactionValidityError> {
+		let asset = match FeeAsset::<T>::get(who) {
+			Some(asset) => asset,
+			None => return Native::withdraw_fee(who, call, info, fee, tip).map(Withdrawn::Native),
+		};
+		if fee.is_zero() {
+			return Ok(Withdrawn::Asset(asset, Zero::zero()))
+		}
+
+		let amount = Pallet::<T>::quote(asset, fee).ok_or(InvalidTransaction::Payment)?;
+		T::Assets::burn_from(asset, who, amount).map_err(|_| InvalidTransaction::Payment)?;
+		Ok(Withdrawn::Asset(asset, amount))
+	}
+
+	fn correct_and_deposit_fee(
+		who: &T::AccountId,
+		info: &DispatchInfoOf<<T as frame_system::Config>::Call>,
+		post_info: &PostDispatchInfoOf<<T as frame_system::Config>::Call>,
+		corrected_fee: Self::Balance,
+		tip: Self::Balance,
+		already_withdrawn: Self::LiquidityInfo,
+	) -> Result<(), TransactionValidityError> {
+		let (asset, paid) = match already_withdrawn {
+			Withdrawn::Asset(asset, paid) => (asset, paid),
+			Withdrawn::Native(withdrawn) =>
+				return Native::correct_and_deposit_fee(
+					who,
+					info,
+					post_info,
+					corrected_fee,
+					tip,
+					withdrawn,
+				),
+		};
+		if paid.is_zero() {
+			return Ok(())
+		}
+
+		// The rate may have been removed by a transaction of the same block, keep what was paid.
+		let due = Pallet::<T>::quote(asset, corrected_fee).map_or(paid, |due| due.min(paid));
+		let refund = paid.saturating_sub(due);
+		if !refund.is_zero() {
+			T::Assets::mint_into(asset, who, refund).map_err(|_| InvalidTransaction::Payment)?;
+		}
+		Pallet::<T>::deposit_event(Event::AssetFeePaid(who.clone(), asset, due));
+		Ok(())
+	}
+}
@@ -0,0 +1,119 @@
+use crate::{mock::*, Error, Event as AssetFeesEvent};
+use frame_support::{
+	assert_noop, assert_ok,
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_runtime::{
+	traits::{SignedExtension, Zero},
+	transaction_validity::InvalidTransaction,
+	DispatchError, FixedPointNumber, FixedU128,
+};
+
+const LEN: usize = 10;
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn info() -> DispatchInfo {
+	DispatchInfo { weight: 100, ..Default::default() }
+}
+
+fn fee(weight: u64) -> u64 {
+	let info = DispatchInfo { weight, ..Default::default() };
+	TransactionPayment::compute_fee(LEN as u32, &info, 0)
+}
+
+fn two() -> FixedU128 {
+	FixedU128::from_integer(2)
+}
+
+#[test]
+fn fees_are_paid_in_the_native_currency_by_default() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetFees::set_rate(Origin::root(), ASSET, Some(two())));
+
+		let extension = ChargeTransactionPayment::<Test>::from(0);
+		assert_eq!(
+			extension.validate(&USER, &remark(), &info(), LEN),
+			Err(InvalidTransaction::Payment.into())
+		);
+		assert_eq!(Assets::balance(ASSET, USER), USER_ASSETS);
+	});
+}
+
+#[test]
+fn fees_are_paid_in_the_fee_asset_at_its_rate() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AssetFees::set_rate(Origin::root(), ASSET, Some(two())));
+		assert_ok!(AssetFees::set_fee_asset(Origin::signed(USER), Some(ASSET)));
+
+		let extension = ChargeTransactionPayment::<Test>::from(0);
+		let pre = extension.pre_dispatch(&USER, &remark(), &info(), LEN).unwrap();
+		assert_eq!(Assets::balance(ASSET, USER), USER_ASSETS - 2 * fee(100));
+
+		// Half of the weight was used, the rest of the fee is refunded in the asset.
+		let post_info = PostDispatchInfo { actual_weight: Some(50), ..Default::default() };
+		assert_ok!(ChargeTransactionPayment::<Test>::post_dispatch(
+			pre,
+			&info(),
+			&post_info,
+			LEN,
+			&Ok(())
+		));
+		assert_eq!(Assets::balance(ASSET, USER), USER_ASSETS - 2 * fee(50));
+		System::assert_last_event(AssetFeesEvent::AssetFeePaid(USER, ASSET, 2 * fee(50)).into());
+	});
+}
+
+#[test]
+fn only_registered_assets_pay_fees() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AssetFees::set_fee_asset(Origin::signed(USER), Some(ASSET)),
+			Error::<Test>::NotRegistered
+		);
+		assert_noop!(
+			AssetFees::set_rate(Origin::signed(USER), ASSET, Some(two())),
+			DispatchError::BadOrigin
+		);
+
+		assert_ok!(AssetFees::set_rate(Origin::root(), ASSET, Some(two())));
+		assert_ok!(AssetFees::set_fee_asset(Origin::signed(USER), Some(ASSET)));
+		System::assert_last_event(AssetFeesEvent::FeeAssetSet(USER, Some(ASSET)).into());
+
+		// Fees can no longer be paid once the rate is removed.
+		assert_ok!(AssetFees::set_rate(Origin::root(), ASSET, None));
+		let extension = ChargeTransactionPayment::<Test>::from(0);
+		assert_eq!(
+			extension.validate(&USER, &remark(), &info(), LEN),
+			Err(InvalidTransaction::Payment.into())
+		);
+	});
+}
+
+#[test]
+fn quotes_follow_the_rate() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(AssetFees::quote(ASSET, 10u64), None);
+
+		assert_ok!(AssetFees::set_rate(Origin::root(), ASSET, Some(two())));
+		assert_eq!(AssetFees::quote(ASSET, 10u64), Some(20));
+
+		let half = FixedU128::saturating_from_rational(1, 2);
+		assert_ok!(AssetFees::set_rate(Origin::root(), ASSET, Some(half)));
+		assert_eq!(AssetFees::quote(ASSET, 10u64), Some(5));
+	});
+}
+
+#[test]
+fn zero_rates_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			AssetFees::set_rate(Origin::root(), ASSET, Some(FixedU128::zero())),
+			Error::<Test>::ZeroRate
+		);
+		assert_eq!(AssetFees::rate(ASSET), None);
+	});
+}
@@ -0,0 +1,314 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Contract Scheduler Pallet
+//!
+//! Lets contracts schedule calls to themselves through `pallet_scheduler`, see the scheduler
+//! chain extension of the runtime.
+//!
+//! A contract schedules a call, its selector and input, at a block or after a delay with
+//! [`Pallet::schedule`], once or periodically for a number of runs. The fee for the gas limit of
+//! every run is reserved from the balance of the contract up front. Each run takes the fee for
+//! the gas it consumed from the reserve and returns the rest, and a contract that cancels a
+//! schedule with [`Pallet::cancel`] gets the reserve of the remaining runs back. Calls that fail
+//! emit [`Event::CallFailed`], as nobody else would notice.
+//!
+//! The input of a call is limited to `MaxDataLen` bytes, for which the contract reserves
+//! `DataDepositPerByte` until the last run or the schedule is cancelled. Contracts may cancel a
+//! schedule from one of its own runs.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{
+		schedule::{DispatchTime, Named, Period, LOWEST_PRIORITY},
+		Currency, OnUnbalanced, ReservableCurrency,
+	},
+	weights::{Weight, WeightToFeePolynomial},
+	BoundedVec,
+};
+use scale_info::TypeInfo;
+use sp_runtime::{DispatchResult, RuntimeDebug};
+use sp_std::prelude::*;
+
+/// The identifier of a schedule.
+pub type ScheduleId = u64;
+
+/// The balance of the currency of the fees.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// The fees of scheduled calls.
+pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// The prefix of the names of the schedules in `pallet_scheduler`.
+const SCHEDULE_PREFIX: [u8; 8] = *b"contract";
+
+/// Calls contracts, implemented by the runtime with `pallet_contracts`.
+pub trait CallContract<AccountId> {
+	/// Call `contract` with `data` as input and the contract itself as caller, returning the gas
+	/// consumed and whether the call succeeded.
+	fn call(contract: &AccountId, gas_limit: Weight, data: Vec<u8>) -> (Weight, DispatchResult);
+}
+
+/// A schedule as stored, with its input bounded by `MaxDataLen`.
+pub type ScheduledCallOf<T> = ScheduledCall<
+	<T as frame_system::Config>::AccountId,
+	BalanceOf<T>,
+	BoundedVec<u8, <T as Config>::MaxDataLen>,
+>;
+
+/// A call a contract scheduled to itself.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct ScheduledCall<AccountId, Balance, Data> {
+	/// The contract that is called.
+	pub contract: AccountId,
+	/// The selector and input of the call.
+	pub data: Data,
+	/// The gas limit of every run.
+	pub gas_limit: Weight,
+	/// The number of runs that were not dispatched yet.
+	pub runs_left: u32,
+	/// The fee reserved for every run.
+	pub fee_per_run: Balance,
+	/// The deposit reserved for `data`.
+	pub deposit: Balance,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{pallet_prelude::*, transactional, weights::WithPostDispatchInfo};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{Saturating, Zero};
+	use sp_std::convert::TryInto;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency of the fees.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Converts the gas of scheduled calls to fees.
+		type WeightToFee: WeightToFeePolynomial<Balance = BalanceOf<Self>>;
+
+		/// What to do with the fees of scheduled calls.
+		type Fees: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// The contracts that are called.
+		type Contracts: CallContract<Self::AccountId>;
+
+		/// The call the scheduler dispatches, i.e. the outer call of the runtime.
+		type ScheduledCall: From<Call<Self>>;
+
+		/// The origin the scheduler dispatches with.
+		type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+
+		/// The scheduler dispatching the calls.
+		type Scheduler: Named<Self::BlockNumber, Self::ScheduledCall, Self::PalletsOrigin>;
+
+		/// The highest gas limit of a scheduled call.
+		#[pallet::constant]
+		type MaxGasLimit: Get<Weight>;
+
+		/// The highest number of runs of a periodic schedule.
+		#[pallet::constant]
+		type MaxRuns: Get<u32>;
+
+		/// The longest selector and input of a scheduled call.
+		#[pallet::constant]
+		type MaxDataLen: Get<u32>;
+
+		/// The deposit reserved per byte of the selector and input of a schedule.
+		#[pallet::constant]
+		type DataDepositPerByte: Get<BalanceOf<Self>>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// The schedules with runs left.
+	#[pallet::storage]
+	#[pallet::getter(fn schedule_of)]
+	pub type Schedules<T: Config> =
+		StorageMap<_, Twox64Concat, ScheduleId, ScheduledCallOf<T>, OptionQuery>;
+
+	/// The identifier of the next schedule.
+	#[pallet::storage]
+	pub type NextScheduleId<T> = StorageValue<_, ScheduleId, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A contract scheduled a call to itself. [id, contract]
+		Scheduled(ScheduleId, T::AccountId),
+		/// A contract cancelled a schedule. [id, contract]
+		Cancelled(ScheduleId, T::AccountId),
+		/// A scheduled call failed. [id, contract, error]
+		CallFailed(ScheduleId, T::AccountId, DispatchError),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The gas limit is higher than `MaxGasLimit`.
+		GasLimitTooHigh,
+		/// The block of the call is not in the future.
+		InPast,
+		/// The period of a periodic schedule is zero.
+		ZeroPeriod,
+		/// A periodic schedule has more than `MaxRuns` runs.
+		TooManyRuns,
+		/// The scheduler refused the call.
+		FailedToSchedule,
+		/// The schedule does not exist or has no runs left.
+		UnknownSchedule,
+		/// The schedule belongs to another contract.
+		NotOwner,
+		/// The selector and input are longer than `MaxDataLen`.
+		DataTooLong,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Run schedule `id`, dispatched by the scheduler.
+		///
+		/// `gas_limit` is the gas limit of the schedule, it is an argument so that the scheduler
+		/// knows the weight of the call.
+		///
+		/// The run is taken off the schedule before the contract is called, so that the contract
+		/// may cancel the schedule. The scheduler still dispatches the remaining runs of a
+		/// schedule cancelled that way, which fail without calling the contract.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3) + *gas_limit)]
+		pub fn dispatch(
+			origin: OriginFor<T>,
+			id: ScheduleId,
+			gas_limit: Weight,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+			let mut scheduled = Schedules::<T>::get(id).ok_or_else(|| {
+				Error::<T>::UnknownSchedule.with_weight(10_000 + T::DbWeight::get().reads(1))
+			})?;
+
+			// The fee of this run stays reserved until the gas it consumed is known.
+			scheduled.runs_left = scheduled.runs_left.saturating_sub(1);
+			if scheduled.runs_left.is_zero() {
+				Schedules::<T>::remove(id);
+				T::Currency::unreserve(&scheduled.contract, scheduled.deposit);
+			} else {
+				Schedules::<T>::insert(id, &scheduled);
+			}
+
+			let ScheduledCall { contract, data, fee_per_run, .. } = scheduled;
+			let (used, result) = T::Contracts::call(&contract, gas_limit, data.into_inner());
+
+			let fee = T::WeightToFee::calc(&used).min(fee_per_run);
+			let (imbalance, _) = T::Currency::slash_reserved(&contract, fee);
+			T::Fees::on_unbalanced(imbalance);
+			T::Currency::unreserve(&contract, fee_per_run.saturating_sub(fee));
+
+			if let Err(error) = result {
+				Self::deposit_event(Event::CallFailed(id, contract, error));
+			}
+			Ok(Some(10_000 + T::DbWeight::get().reads_writes(2, 3) + used).into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The name of schedule `id` in the scheduler.
+		fn schedule_name(id: ScheduleId) -> Vec<u8> {
+			(SCHEDULE_PREFIX, id).encode()
+		}
+
+		/// Schedule a call of `contract` to itself with `data` as input, at `when` and, if
+		/// `periodic`, every `period` blocks after that for `count` runs in total.
+		///
+		/// Reserves the fee for `gas_limit` of every run and the deposit for `data` from
+		/// `contract`.
+		#[transactional]
+		pub fn schedule(
+			contract: T::AccountId,
+			when: DispatchTime<T::BlockNumber>,
+			periodic: Option<Period<T::BlockNumber>>,
+			gas_limit: Weight,
+			data: Vec<u8>,
+		) -> Result<ScheduleId, DispatchError> {
+			ensure!(gas_limit <= T::MaxGasLimit::get(), Error::<T>::GasLimitTooHigh);
+			if let DispatchTime::At(block) = when {
+				let now = frame_system::Pallet::<T>::block_number();
+				ensure!(block > now, Error::<T>::InPast);
+			}
+			let runs = match periodic {
+				Some((period, count)) => {
+					ensure!(!period.is_zero(), Error::<T>::ZeroPeriod);
+					ensure!(count <= T::MaxRuns::get(), Error::<T>::TooManyRuns);
+					// The scheduler treats fewer than two runs as a single one.
+					count.max(1)
+				},
+				None => 1,
+			};
+
+			let data: BoundedVec<u8, T::MaxDataLen> =
+				data.try_into().map_err(|_| Error::<T>::DataTooLong)?;
+			let deposit = T::DataDepositPerByte::get().saturating_mul((data.len() as u32).into());
+			let fee_per_run = T::WeightToFee::calc(&gas_limit);
+			let reserved = fee_per_run.saturating_mul(runs.into()).saturating_add(deposit);
+			T::Currency::reserve(&contract, reserved)?;
+
+			let id = NextScheduleId::<T>::mutate(|next| {
+				let id = *next;
+				*next += 1;
+				id
+			});
+			let call = Call::<T>::dispatch { id, gas_limit };
+			T::Scheduler::schedule_named(
+				Self::schedule_name(id),
+				when,
+				periodic,
+				LOWEST_PRIORITY,
+				frame_system::RawOrigin::Root.into(),
+				call.into(),
+			)
+			.map_err(|_| Error::<T>::FailedToSchedule)?;
+
+			let scheduled = ScheduledCall {
+				contract: contract.clone(),
+				data,
+				gas_limit,
+				runs_left: runs,
+				fee_per_run,
+				deposit,
+			};
+			Schedules::<T>::insert(id, scheduled);
+			Self::deposit_event(Event::Scheduled(id, contract));
+			Ok(id)
+		}
+
+		/// Cancel schedule `id` of `contract`, returning the fees reserved for the runs left and
+		/// the deposit.
+		#[transactional]
+		pub fn cancel(contract: T::AccountId, id: ScheduleId) -> DispatchResult {
+			let scheduled = Schedules::<T>::get(id).ok_or(Error::<T>::UnknownSchedule)?;
+			ensure!(scheduled.contract == contract, Error::<T>::NotOwner);
+			// The scheduler does not know the name of the schedule it is running, a schedule
+			// cancelled from its own run is left to `dispatch`.
+			let _ = T::Scheduler::cancel_named(Self::schedule_name(id));
+
+			Schedules::<T>::remove(id);
+			let reserved = scheduled.fee_per_run.saturating_mul(scheduled.runs_left.into());
+			T::Currency::unreserve(&contract, reserved.saturating_add(scheduled.deposit));
+			Self::deposit_event(Event::Cancelled(id, contract));
+			Ok(())
+		}
+	}
+}
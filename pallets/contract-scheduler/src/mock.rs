@@ -0,0 +1,186 @@
+use crate::{self as pallet_contract_scheduler, CallContract, ScheduleId};
+use codec::Decode;
+use frame_support::{
+	parameter_types,
+	traits::{EqualPrivilegeOnly, OnFinalize, OnInitialize},
+	weights::{IdentityFee, Weight},
+};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	DispatchError, DispatchResult,
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
+		ContractScheduler: pallet_contract_scheduler::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub BlockWeights: frame_system::limits::BlockWeights =
+		frame_system::limits::BlockWeights::simple_max(1_000_000_000);
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = BlockWeights;
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MaximumSchedulerWeight: Weight = 500_000_000;
+	pub const MaxScheduledPerBlock: u32 = 10;
+}
+
+impl pallet_scheduler::Config for Test {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = EnsureRoot<u64>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type WeightInfo = ();
+}
+
+thread_local! {
+	/// The contracts called so far, with their input.
+	pub static CALLS: RefCell<Vec<(u64, Vec<u8>)>> = RefCell::new(Vec::new());
+}
+
+/// The gas every call of [`MockContracts`] consumes.
+pub const GAS_USED: Weight = 1_000;
+
+/// Records calls, which fail if their input is `FAIL` and cancel a schedule of the contract if
+/// their input is `CANCEL` followed by its id.
+pub struct MockContracts;
+
+impl CallContract<u64> for MockContracts {
+	fn call(contract: &u64, _gas_limit: Weight, data: Vec<u8>) -> (Weight, DispatchResult) {
+		let result = if data == FAIL {
+			Err(DispatchError::Other("contract trapped"))
+		} else if let Some(mut id) = data.strip_prefix(CANCEL) {
+			let id = ScheduleId::decode(&mut id).unwrap();
+			ContractScheduler::cancel(*contract, id)
+		} else {
+			Ok(())
+		};
+		CALLS.with(|calls| calls.borrow_mut().push((*contract, data)));
+		(GAS_USED, result)
+	}
+}
+
+parameter_types! {
+	pub const MaxGasLimit: Weight = 100_000;
+	pub const MaxRuns: u32 = 5;
+	pub const MaxDataLen: u32 = 16;
+	pub const DataDepositPerByte: u64 = 10;
+}
+
+impl pallet_contract_scheduler::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type WeightToFee = IdentityFee<u64>;
+	type Fees = ();
+	type Contracts = MockContracts;
+	type ScheduledCall = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MaxGasLimit = MaxGasLimit;
+	type MaxRuns = MaxRuns;
+	type MaxDataLen = MaxDataLen;
+	type DataDepositPerByte = DataDepositPerByte;
+}
+
+/// A contract scheduling calls in the tests.
+pub const CONTRACT: u64 = 1;
+/// Another contract.
+pub const OTHER: u64 = 2;
+/// The balance of the contracts at genesis.
+pub const BALANCE: u64 = 1_000_000;
+/// The input of calls that fail.
+pub const FAIL: &[u8] = b"fail";
+/// The prefix of the input of calls that cancel a schedule.
+pub const CANCEL: &[u8] = b"cancel";
+
+/// The calls of contracts so far.
+pub fn calls() -> Vec<(u64, Vec<u8>)> {
+	CALLS.with(|calls| calls.borrow().clone())
+}
+
+/// Finalize the current block and initialize blocks until block `n` is reached, running the
+/// scheduler.
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		Scheduler::on_finalize(System::block_number());
+		System::set_block_number(System::block_number() + 1);
+		Scheduler::on_initialize(System::block_number());
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(CONTRACT, BALANCE), (OTHER, BALANCE)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+	let mut ext: sp_io::TestExternalities = storage.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
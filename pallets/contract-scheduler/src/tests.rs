@@ -0,0 +1,157 @@
+use crate::{mock::*, Error, Event as SchedulerEvent};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, traits::schedule::DispatchTime};
+use sp_runtime::{traits::BadOrigin, DispatchError};
+
+const GAS_LIMIT: u64 = 10_000;
+
+#[test]
+fn calls_run_at_a_block_or_after_a_delay() {
+	new_test_ext().execute_with(|| {
+		let at = DispatchTime::At(5);
+		assert_eq!(
+			ContractScheduler::schedule(CONTRACT, at, None, GAS_LIMIT, b"at".to_vec()),
+			Ok(0)
+		);
+		System::assert_last_event(SchedulerEvent::Scheduled(0, CONTRACT).into());
+		let after = DispatchTime::After(3);
+		assert_eq!(
+			ContractScheduler::schedule(OTHER, after, None, GAS_LIMIT, b"after".to_vec()),
+			Ok(1)
+		);
+		// The fee of the gas limit and the deposit for the input.
+		assert_eq!(Balances::reserved_balance(CONTRACT), GAS_LIMIT + 2 * DataDepositPerByte::get());
+
+		run_to_block(3);
+		assert!(calls().is_empty());
+		run_to_block(5);
+		let mut expected = vec![(CONTRACT, b"at".to_vec()), (OTHER, b"after".to_vec())];
+		let mut actual = calls();
+		actual.sort();
+		expected.sort();
+		assert_eq!(actual, expected);
+
+		// Only the fee of the consumed gas is taken from the reserve.
+		assert_eq!(Balances::reserved_balance(CONTRACT), 0);
+		assert_eq!(Balances::free_balance(CONTRACT), BALANCE - GAS_USED);
+		assert!(ContractScheduler::schedule_of(0).is_none());
+	});
+}
+
+#[test]
+fn periodic_calls_run_for_all_runs() {
+	new_test_ext().execute_with(|| {
+		let when = DispatchTime::At(3);
+		let deposit = DataDepositPerByte::get();
+		assert_ok!(ContractScheduler::schedule(CONTRACT, when, Some((2, 3)), GAS_LIMIT, vec![1]));
+		assert_eq!(Balances::reserved_balance(CONTRACT), 3 * GAS_LIMIT + deposit);
+
+		run_to_block(3);
+		assert_eq!(calls().len(), 1);
+		assert_eq!(Balances::reserved_balance(CONTRACT), 2 * GAS_LIMIT + deposit);
+		assert_eq!(ContractScheduler::schedule_of(0).unwrap().runs_left, 2);
+
+		run_to_block(10);
+		assert_eq!(calls(), vec![(CONTRACT, vec![1]); 3]);
+		assert_eq!(Balances::reserved_balance(CONTRACT), 0);
+		assert_eq!(Balances::free_balance(CONTRACT), BALANCE - 3 * GAS_USED);
+		assert!(ContractScheduler::schedule_of(0).is_none());
+	});
+}
+
+#[test]
+fn contracts_cancel_their_schedules() {
+	new_test_ext().execute_with(|| {
+		let when = DispatchTime::At(3);
+		assert_ok!(ContractScheduler::schedule(CONTRACT, when, Some((2, 3)), GAS_LIMIT, vec![1]));
+		run_to_block(3);
+
+		assert_noop!(ContractScheduler::cancel(OTHER, 0), Error::<Test>::NotOwner);
+		assert_ok!(ContractScheduler::cancel(CONTRACT, 0));
+		System::assert_last_event(SchedulerEvent::Cancelled(0, CONTRACT).into());
+		assert_eq!(Balances::reserved_balance(CONTRACT), 0);
+		assert_eq!(Balances::free_balance(CONTRACT), BALANCE - GAS_USED);
+
+		run_to_block(10);
+		assert_eq!(calls().len(), 1);
+		assert_noop!(ContractScheduler::cancel(CONTRACT, 0), Error::<Test>::UnknownSchedule);
+	});
+}
+
+#[test]
+fn contracts_cancel_schedules_while_they_run() {
+	new_test_ext().execute_with(|| {
+		let when = DispatchTime::At(3);
+		let data = [CANCEL, &0u64.encode()].concat();
+		assert_ok!(ContractScheduler::schedule(CONTRACT, when, Some((2, 3)), GAS_LIMIT, data));
+
+		run_to_block(3);
+		System::assert_has_event(SchedulerEvent::Cancelled(0, CONTRACT).into());
+		// The running call paid for its gas, nothing is released twice.
+		assert_eq!(Balances::reserved_balance(CONTRACT), 0);
+		assert_eq!(Balances::free_balance(CONTRACT), BALANCE - GAS_USED);
+		assert!(ContractScheduler::schedule_of(0).is_none());
+
+		// Cancelled schedules do not come back.
+		run_to_block(10);
+		assert_eq!(calls().len(), 1);
+		assert_eq!(Balances::free_balance(CONTRACT), BALANCE - GAS_USED);
+		assert!(ContractScheduler::schedule_of(0).is_none());
+	});
+}
+
+#[test]
+fn failed_calls_emit_events() {
+	new_test_ext().execute_with(|| {
+		let when = DispatchTime::At(3);
+		assert_ok!(ContractScheduler::schedule(CONTRACT, when, None, GAS_LIMIT, FAIL.to_vec()));
+		run_to_block(3);
+
+		let error = DispatchError::Other("contract trapped");
+		System::assert_has_event(SchedulerEvent::CallFailed(0, CONTRACT, error).into());
+		// The gas of failed calls is paid as well.
+		assert_eq!(Balances::free_balance(CONTRACT), BALANCE - GAS_USED);
+	});
+}
+
+#[test]
+fn invalid_schedules_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let when = DispatchTime::At(3);
+		let schedule = |contract, when, periodic, gas_limit| {
+			ContractScheduler::schedule(contract, when, periodic, gas_limit, vec![])
+		};
+		assert_noop!(schedule(CONTRACT, when, None, 100_001), Error::<Test>::GasLimitTooHigh);
+		assert_noop!(
+			schedule(CONTRACT, DispatchTime::At(1), None, GAS_LIMIT),
+			Error::<Test>::InPast
+		);
+		assert_noop!(schedule(CONTRACT, when, Some((0, 2)), GAS_LIMIT), Error::<Test>::ZeroPeriod);
+		assert_noop!(schedule(CONTRACT, when, Some((1, 6)), GAS_LIMIT), Error::<Test>::TooManyRuns);
+		assert_noop!(
+			ContractScheduler::schedule(CONTRACT, when, None, GAS_LIMIT, vec![0; 17]),
+			Error::<Test>::DataTooLong
+		);
+		assert_noop!(
+			schedule(3, when, None, GAS_LIMIT),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+	});
+}
+
+#[test]
+fn only_the_scheduler_dispatches() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ContractScheduler::schedule(
+			CONTRACT,
+			DispatchTime::At(3),
+			None,
+			GAS_LIMIT,
+			vec![]
+		));
+		assert_noop!(
+			ContractScheduler::dispatch(Origin::signed(CONTRACT), 0, GAS_LIMIT),
+			BadOrigin
+		);
+	});
+}
@@ -0,0 +1,57 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Identity Registrars Pallet
+//!
+//! `pallet_identity` has no genesis configuration, registrars are added by its `RegistrarOrigin`
+//! after launch. This pallet adds the registrars of its genesis configuration, so that dev chains
+//! start with registrars that can judge identities right away.
+//!
+//! The registrars are added in order, the first one gets registrar index 0. Their fees and
+//! fields are left for them to set.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::pallet_prelude::*;
+	use sp_std::prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_identity::Config {}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// The accounts added as registrars.
+		pub registrars: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { registrars: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for registrar in &self.registrars {
+				pallet_identity::Pallet::<T>::add_registrar(
+					frame_system::RawOrigin::Root.into(),
+					registrar.clone(),
+				)
+				.expect("the registrar origin of pallet_identity accepts root at genesis");
+			}
+		}
+	}
+}
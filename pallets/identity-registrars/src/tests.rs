@@ -0,0 +1,15 @@
+use crate::mock::*;
+
+#[test]
+fn registrars_are_added_at_genesis() {
+	new_test_ext().execute_with(|| {
+		let registrars: Vec<_> = Identity::registrars()
+			.into_iter()
+			.map(|registrar| {
+				let registrar = registrar.unwrap();
+				(registrar.account, registrar.fee)
+			})
+			.collect();
+		assert_eq!(registrars, vec![(REGISTRARS[0], 0), (REGISTRARS[1], 0)]);
+	});
+}
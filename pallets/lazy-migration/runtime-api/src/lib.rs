@@ -0,0 +1,17 @@
+//! Runtime API to follow the progress of lazy migrations.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_lazy_migration::{Outcome, Progress};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait LazyMigrationApi<BlockNumber> where BlockNumber: Codec {
+		/// The progress of the migration in flight, if any.
+		fn progress() -> Option<Progress>;
+
+		/// How the migration `id` ended, if it did.
+		fn outcome(id: Vec<u8>) -> Option<Outcome<BlockNumber>>;
+	}
+}
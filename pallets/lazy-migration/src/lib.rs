@@ -0,0 +1,400 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Lazy Migration Pallet
+//!
+//! Runs a storage migration across many blocks. Migrations that touch a lot of storage don't fit
+//! into the weight of a single block, so instead of running from `on_runtime_upgrade` they
+//! implement [`SteppedMigration`] and migrate one batch of storage at a time from a cursor. This
+//! pallet drives the steps with the weight left over in `on_idle` until the migration is done.
+//!
+//! A migration is started by the runtime upgrade that introduces it, or through [`Call::start`].
+//! While it is in flight, the calls of the pallets it affects are rejected by
+//! [`MigrationCallFilter`], which has to be part of the `BaseCallFilter` of the runtime. The
+//! `ControlOrigin` can pause and resume a migration, and roll it back if it supports that. A step
+//! that fails leaves the migration in flight, so affected calls stay blocked until the migration
+//! is resumed or rolled back.
+//!
+//! Progress is reported through events and the `LazyMigrationApi` runtime API.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{Contains, GetCallMetadata},
+	weights::Weight,
+	RuntimeDebug,
+};
+use scale_info::TypeInfo;
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// The position of a migration in the storage it migrates. Only the migration itself knows how to
+/// interpret it.
+pub type Cursor = Vec<u8>;
+
+/// A storage migration that runs in steps.
+pub trait SteppedMigration {
+	/// The unique identifier of the migration, empty for no migration.
+	fn id() -> Vec<u8>;
+
+	/// The names of the pallets, as given to `construct_runtime!`, whose calls must not be
+	/// dispatched while the migration is in flight.
+	fn affected_pallets() -> Vec<&'static str>;
+
+	/// Migrate the next batch of storage, starting at `cursor` or at the beginning for `None`.
+	///
+	/// Returns the cursor to continue from, `None` once all storage is migrated, and the weight
+	/// used, which must not exceed `limit`. Changes of a step that fails are reverted.
+	fn step(
+		cursor: Option<Cursor>,
+		limit: Weight,
+	) -> Result<(Option<Cursor>, Weight), &'static str>;
+
+	/// Undo a batch of the migration, going backwards from `cursor`.
+	///
+	/// Returns the cursor to continue from, `None` once all storage is restored, and the weight
+	/// used, which must not exceed `limit`.
+	fn rollback_step(
+		cursor: Cursor,
+		limit: Weight,
+	) -> Result<(Option<Cursor>, Weight), &'static str> {
+		let _ = (cursor, limit);
+		Err("The migration can not be rolled back")
+	}
+}
+
+/// No migration.
+impl SteppedMigration for () {
+	fn id() -> Vec<u8> {
+		Vec::new()
+	}
+
+	fn affected_pallets() -> Vec<&'static str> {
+		Vec::new()
+	}
+
+	fn step(_: Option<Cursor>, _: Weight) -> Result<(Option<Cursor>, Weight), &'static str> {
+		Ok((None, 0))
+	}
+}
+
+/// What the migration in flight is doing.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum Phase {
+	/// Migrating storage in `on_idle`.
+	Running,
+	/// Stopped by the `ControlOrigin`.
+	Paused,
+	/// Undoing the migration in `on_idle`.
+	RollingBack,
+	/// Stopped because a step failed.
+	Failed,
+}
+
+/// The progress of the migration in flight.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct Progress {
+	/// The identifier of the migration.
+	pub id: Vec<u8>,
+	/// What the migration is doing.
+	pub phase: Phase,
+	/// Where the next step continues, `None` if no storage was migrated yet.
+	pub cursor: Option<Cursor>,
+	/// The number of steps taken so far.
+	pub steps: u32,
+}
+
+/// How a migration ended.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum Outcome<BlockNumber> {
+	/// All storage was migrated in the given block.
+	Completed(BlockNumber),
+	/// The migration was undone in the given block. It is not started again by runtime upgrades.
+	RolledBack(BlockNumber),
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::{
+		pallet_prelude::*,
+		storage::{with_transaction, TransactionOutcome},
+	};
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The migration to run.
+		type Migration: SteppedMigration;
+
+		/// The origin that may start, pause, resume and roll back the migration.
+		type ControlOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// The migration in flight, if any.
+	#[pallet::storage]
+	#[pallet::getter(fn progress)]
+	pub type Current<T> = StorageValue<_, Progress>;
+
+	/// How migrations ended, by identifier.
+	#[pallet::storage]
+	#[pallet::getter(fn outcome)]
+	pub type Outcomes<T: Config> = StorageMap<_, Twox64Concat, Vec<u8>, Outcome<T::BlockNumber>>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A migration was started. [id]
+		Started(Vec<u8>),
+		/// A migration took a step. [id, steps]
+		Progressed(Vec<u8>, u32),
+		/// A migration migrated all storage. [id, steps]
+		Completed(Vec<u8>, u32),
+		/// A step of a migration failed, the migration stopped. [id, steps]
+		Failed(Vec<u8>, u32),
+		/// A migration was paused. [id]
+		Paused(Vec<u8>),
+		/// A migration was resumed. [id]
+		Resumed(Vec<u8>),
+		/// A migration is being rolled back. [id]
+		RollbackStarted(Vec<u8>),
+		/// A migration was rolled back. [id]
+		RolledBack(Vec<u8>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The runtime has no migration.
+		NoMigration,
+		/// A migration is already in flight.
+		InFlight,
+		/// No migration is in flight.
+		NotInFlight,
+		/// The migration is not running.
+		NotRunning,
+		/// The migration is neither paused nor failed.
+		NotStopped,
+		/// The migration is already being rolled back.
+		RollingBack,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let id = T::Migration::id();
+			if id.is_empty() || Current::<T>::exists() || Outcomes::<T>::contains_key(&id) {
+				return T::DbWeight::get().reads(2)
+			}
+			Self::start_migration(id);
+			T::DbWeight::get().reads_writes(2, 1)
+		}
+
+		fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			// Reading and updating the progress, and recording the outcome.
+			let overhead = T::DbWeight::get().reads_writes(1, 2);
+			let limit = match remaining_weight.checked_sub(overhead) {
+				Some(limit) if limit > 0 => limit,
+				_ => return 0,
+			};
+
+			let progress = match Current::<T>::get() {
+				Some(progress) => progress,
+				None => return T::DbWeight::get().reads(1),
+			};
+			let used = match progress.phase {
+				Phase::Running => Self::migrate(progress, limit),
+				Phase::RollingBack => Self::roll_back(progress, limit),
+				Phase::Paused | Phase::Failed => 0,
+			};
+			used.min(limit).saturating_add(overhead)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Start the migration of the runtime, even if it ended before.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn start(origin: OriginFor<T>) -> DispatchResult {
+			T::ControlOrigin::ensure_origin(origin)?;
+			let id = T::Migration::id();
+			ensure!(!id.is_empty(), Error::<T>::NoMigration);
+			ensure!(!Current::<T>::exists(), Error::<T>::InFlight);
+
+			Outcomes::<T>::remove(&id);
+			Self::start_migration(id);
+			Ok(())
+		}
+
+		/// Stop the running migration until it is resumed.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn pause(origin: OriginFor<T>) -> DispatchResult {
+			T::ControlOrigin::ensure_origin(origin)?;
+			let mut progress = Current::<T>::get().ok_or(Error::<T>::NotInFlight)?;
+			ensure!(progress.phase == Phase::Running, Error::<T>::NotRunning);
+
+			progress.phase = Phase::Paused;
+			Self::deposit_event(Event::Paused(progress.id.clone()));
+			Current::<T>::put(progress);
+			Ok(())
+		}
+
+		/// Continue a paused or failed migration where it stopped.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn resume(origin: OriginFor<T>) -> DispatchResult {
+			T::ControlOrigin::ensure_origin(origin)?;
+			let mut progress = Current::<T>::get().ok_or(Error::<T>::NotInFlight)?;
+			ensure!(
+				matches!(progress.phase, Phase::Paused | Phase::Failed),
+				Error::<T>::NotStopped
+			);
+
+			progress.phase = Phase::Running;
+			Self::deposit_event(Event::Resumed(progress.id.clone()));
+			Current::<T>::put(progress);
+			Ok(())
+		}
+
+		/// Undo the migration in flight, step by step in `on_idle`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn rollback(origin: OriginFor<T>) -> DispatchResult {
+			T::ControlOrigin::ensure_origin(origin)?;
+			let mut progress = Current::<T>::get().ok_or(Error::<T>::NotInFlight)?;
+			ensure!(progress.phase != Phase::RollingBack, Error::<T>::RollingBack);
+
+			progress.phase = Phase::RollingBack;
+			Self::deposit_event(Event::RollbackStarted(progress.id.clone()));
+			Current::<T>::put(progress);
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn start_migration(id: Vec<u8>) {
+			log::info!(target: "runtime::lazy-migration", "Starting migration {:?}", id);
+			Self::deposit_event(Event::Started(id.clone()));
+			Current::<T>::put(Progress { id, phase: Phase::Running, cursor: None, steps: 0 });
+		}
+
+		/// Take a step of the migration, returns the weight used.
+		fn migrate(mut progress: Progress, limit: Weight) -> Weight {
+			let result = Self::transactional(|| T::Migration::step(progress.cursor.clone(), limit));
+			progress.steps = progress.steps.saturating_add(1);
+
+			match result {
+				Ok((Some(cursor), used)) => {
+					progress.cursor = Some(cursor);
+					Self::deposit_event(Event::Progressed(progress.id.clone(), progress.steps));
+					Current::<T>::put(progress);
+					used
+				},
+				Ok((None, used)) => {
+					log::info!(
+						target: "runtime::lazy-migration",
+						"Completed migration {:?} in {} steps",
+						progress.id,
+						progress.steps,
+					);
+					let now = frame_system::Pallet::<T>::block_number();
+					Outcomes::<T>::insert(&progress.id, Outcome::Completed(now));
+					Current::<T>::kill();
+					Self::deposit_event(Event::Completed(progress.id, progress.steps));
+					used
+				},
+				Err(e) => {
+					log::error!(
+						target: "runtime::lazy-migration",
+						"Step {} of migration {:?} failed: {}",
+						progress.steps,
+						progress.id,
+						e,
+					);
+					progress.phase = Phase::Failed;
+					Self::deposit_event(Event::Failed(progress.id.clone(), progress.steps));
+					Current::<T>::put(progress);
+					// The weight of a failed step is unknown.
+					limit
+				},
+			}
+		}
+
+		/// Undo a step of the migration, returns the weight used.
+		fn roll_back(mut progress: Progress, limit: Weight) -> Weight {
+			let result = match progress.cursor.clone() {
+				Some(cursor) => Self::transactional(|| T::Migration::rollback_step(cursor, limit)),
+				None => Ok((None, 0)),
+			};
+
+			match result {
+				Ok((Some(cursor), used)) => {
+					progress.cursor = Some(cursor);
+					Current::<T>::put(progress);
+					used
+				},
+				Ok((None, used)) => {
+					log::info!(
+						target: "runtime::lazy-migration",
+						"Rolled back migration {:?}",
+						progress.id,
+					);
+					let now = frame_system::Pallet::<T>::block_number();
+					Outcomes::<T>::insert(&progress.id, Outcome::RolledBack(now));
+					Current::<T>::kill();
+					Self::deposit_event(Event::RolledBack(progress.id));
+					used
+				},
+				Err(e) => {
+					log::error!(
+						target: "runtime::lazy-migration",
+						"Rolling back migration {:?} failed: {}",
+						progress.id,
+						e,
+					);
+					progress.phase = Phase::Failed;
+					Self::deposit_event(Event::Failed(progress.id.clone(), progress.steps));
+					Current::<T>::put(progress);
+					limit
+				},
+			}
+		}
+
+		/// Run `step`, reverting its changes if it fails.
+		fn transactional(
+			step: impl FnOnce() -> Result<(Option<Cursor>, Weight), &'static str>,
+		) -> Result<(Option<Cursor>, Weight), &'static str> {
+			with_transaction(|| match step() {
+				Ok(result) => TransactionOutcome::Commit(Ok(result)),
+				Err(e) => TransactionOutcome::Rollback(Err(e)),
+			})
+		}
+	}
+}
+
+/// Rejects the calls of the pallets affected by the migration in flight.
+///
+/// Use this as, or as part of, the `BaseCallFilter` of the runtime.
+pub struct MigrationCallFilter<T>(PhantomData<T>);
+
+impl<T: Config> Contains<<T as frame_system::Config>::Call> for MigrationCallFilter<T>
+where
+	<T as frame_system::Config>::Call: GetCallMetadata,
+{
+	fn contains(call: &<T as frame_system::Config>::Call) -> bool {
+		if !Current::<T>::exists() {
+			return true
+		}
+		let pallet = call.get_call_metadata().pallet_name;
+		!T::Migration::affected_pallets().contains(&pallet)
+	}
+}
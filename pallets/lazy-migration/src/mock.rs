@@ -0,0 +1,145 @@
+use crate as pallet_lazy_migration;
+use crate::{Cursor, SteppedMigration};
+use codec::{Decode, Encode};
+use frame_support::{parameter_types, storage::unhashed, weights::Weight};
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		LazyMigration: pallet_lazy_migration::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = pallet_lazy_migration::MigrationCallFilter<Test>;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+impl pallet_lazy_migration::Config for Test {
+	type Event = Event;
+	type Migration = DoubleItems;
+	type ControlOrigin = EnsureRoot<u64>;
+}
+
+/// The number of items [`DoubleItems`] migrates.
+pub const ITEMS: u32 = 10;
+/// The weight of migrating a single item.
+pub const ITEM_WEIGHT: Weight = 100;
+
+thread_local! {
+	/// Let [`DoubleItems`] fail when reaching this item.
+	pub static FAIL_AT: RefCell<Option<u32>> = RefCell::new(None);
+}
+
+fn item_key(index: u32) -> Vec<u8> {
+	(b"item", index).encode()
+}
+
+pub fn item(index: u32) -> u32 {
+	unhashed::get_or_default(&item_key(index))
+}
+
+/// Store the items in their old format, item `i` being `i + 1`.
+pub fn old_items() {
+	for index in 0..ITEMS {
+		unhashed::put(&item_key(index), &(index + 1));
+	}
+}
+
+fn decode_cursor(cursor: &[u8]) -> Result<u32, &'static str> {
+	u32::decode(&mut &cursor[..]).map_err(|_| "Invalid cursor")
+}
+
+/// Doubles all items, the cursor is the index of the next item.
+pub struct DoubleItems;
+
+impl SteppedMigration for DoubleItems {
+	fn id() -> Vec<u8> {
+		b"double-items".to_vec()
+	}
+
+	fn affected_pallets() -> Vec<&'static str> {
+		vec!["System"]
+	}
+
+	fn step(
+		cursor: Option<Cursor>,
+		limit: Weight,
+	) -> Result<(Option<Cursor>, Weight), &'static str> {
+		let start = cursor.map_or(Ok(0), |cursor| decode_cursor(&cursor))?;
+		let end = ITEMS.min(start + (limit / ITEM_WEIGHT) as u32);
+		for index in start..end {
+			if FAIL_AT.with(|fail_at| *fail_at.borrow() == Some(index)) {
+				return Err("Failing as requested")
+			}
+			unhashed::put(&item_key(index), &(item(index) * 2));
+		}
+		let next = if end == ITEMS { None } else { Some(end.encode()) };
+		Ok((next, (end - start) as Weight * ITEM_WEIGHT))
+	}
+
+	fn rollback_step(
+		cursor: Cursor,
+		limit: Weight,
+	) -> Result<(Option<Cursor>, Weight), &'static str> {
+		let end = decode_cursor(&cursor)?;
+		let start = end.saturating_sub((limit / ITEM_WEIGHT) as u32);
+		for index in start..end {
+			unhashed::put(&item_key(index), &(item(index) / 2));
+		}
+		let next = if start == 0 { None } else { Some(start.encode()) };
+		Ok((next, (end - start) as Weight * ITEM_WEIGHT))
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut ext: sp_io::TestExternalities =
+		frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		FAIL_AT.with(|fail_at| *fail_at.borrow_mut() = None);
+	});
+	ext
+}
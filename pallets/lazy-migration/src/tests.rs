@@ -0,0 +1,194 @@
+use crate::{mock::*, Error, Event as MigrationEvent, Outcome, Phase, Progress};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Contains, Hooks, OnRuntimeUpgrade},
+};
+use sp_runtime::traits::BadOrigin;
+
+const ID: &[u8] = b"double-items";
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn calls_allowed() -> bool {
+	<Test as frame_system::Config>::BaseCallFilter::contains(&remark())
+}
+
+fn idle(weight: u64) -> u64 {
+	LazyMigration::on_idle(System::block_number(), weight)
+}
+
+fn migration_events() -> Vec<MigrationEvent<Test>> {
+	System::events()
+		.into_iter()
+		.filter_map(|record| match record.event {
+			Event::LazyMigration(event) => Some(event),
+			_ => None,
+		})
+		.collect()
+}
+
+#[test]
+fn runtime_upgrade_starts_the_migration_once() {
+	new_test_ext().execute_with(|| {
+		LazyMigration::on_runtime_upgrade();
+		assert_eq!(
+			LazyMigration::progress(),
+			Some(Progress { id: ID.to_vec(), phase: Phase::Running, cursor: None, steps: 0 })
+		);
+
+		LazyMigration::on_runtime_upgrade();
+		assert_eq!(migration_events(), vec![MigrationEvent::Started(ID.to_vec())]);
+	});
+}
+
+#[test]
+fn migrates_across_blocks() {
+	new_test_ext().execute_with(|| {
+		old_items();
+		LazyMigration::on_runtime_upgrade();
+
+		assert_eq!(idle(4 * ITEM_WEIGHT), 4 * ITEM_WEIGHT);
+		assert_eq!(item(3), 8);
+		assert_eq!(item(4), 5);
+
+		System::set_block_number(2);
+		idle(4 * ITEM_WEIGHT);
+		System::set_block_number(3);
+		assert_eq!(idle(4 * ITEM_WEIGHT), 2 * ITEM_WEIGHT);
+
+		assert!((0..ITEMS).all(|index| item(index) == 2 * (index + 1)));
+		assert_eq!(LazyMigration::progress(), None);
+		assert_eq!(LazyMigration::outcome(ID.to_vec()), Some(Outcome::Completed(3)));
+		assert_eq!(
+			migration_events(),
+			vec![
+				MigrationEvent::Started(ID.to_vec()),
+				MigrationEvent::Progressed(ID.to_vec(), 1),
+				MigrationEvent::Progressed(ID.to_vec(), 2),
+				MigrationEvent::Completed(ID.to_vec(), 3),
+			]
+		);
+
+		// A completed migration is not started again.
+		LazyMigration::on_runtime_upgrade();
+		assert_eq!(LazyMigration::progress(), None);
+	});
+}
+
+#[test]
+fn blocks_affected_calls_while_in_flight() {
+	new_test_ext().execute_with(|| {
+		assert!(calls_allowed());
+
+		LazyMigration::on_runtime_upgrade();
+		assert!(!calls_allowed());
+		// The migration can still be controlled.
+		assert!(<Test as frame_system::Config>::BaseCallFilter::contains(&Call::LazyMigration(
+			crate::Call::pause {}
+		)));
+
+		assert_ok!(LazyMigration::pause(Origin::root()));
+		assert!(!calls_allowed());
+
+		assert_ok!(LazyMigration::resume(Origin::root()));
+		idle(ITEMS as u64 * ITEM_WEIGHT);
+		assert!(calls_allowed());
+	});
+}
+
+#[test]
+fn paused_migration_makes_no_progress() {
+	new_test_ext().execute_with(|| {
+		old_items();
+		LazyMigration::on_runtime_upgrade();
+		assert_ok!(LazyMigration::pause(Origin::root()));
+		assert_noop!(LazyMigration::pause(Origin::root()), Error::<Test>::NotRunning);
+
+		assert_eq!(idle(ITEMS as u64 * ITEM_WEIGHT), 0);
+		assert_eq!(item(0), 1);
+
+		assert_ok!(LazyMigration::resume(Origin::root()));
+		assert_noop!(LazyMigration::resume(Origin::root()), Error::<Test>::NotStopped);
+		idle(ITEMS as u64 * ITEM_WEIGHT);
+		assert_eq!(item(0), 2);
+		assert_eq!(LazyMigration::outcome(ID.to_vec()), Some(Outcome::Completed(1)));
+	});
+}
+
+#[test]
+fn failing_step_is_reverted_and_stops_the_migration() {
+	new_test_ext().execute_with(|| {
+		old_items();
+		LazyMigration::on_runtime_upgrade();
+		FAIL_AT.with(|fail_at| *fail_at.borrow_mut() = Some(5));
+
+		idle(ITEMS as u64 * ITEM_WEIGHT);
+		// The items migrated before the failure are reverted.
+		assert!((0..ITEMS).all(|index| item(index) == index + 1));
+		assert_eq!(LazyMigration::progress().map(|progress| progress.phase), Some(Phase::Failed));
+		assert_eq!(migration_events().last(), Some(&MigrationEvent::Failed(ID.to_vec(), 1)));
+		assert!(!calls_allowed());
+
+		FAIL_AT.with(|fail_at| *fail_at.borrow_mut() = None);
+		assert_ok!(LazyMigration::resume(Origin::root()));
+		idle(ITEMS as u64 * ITEM_WEIGHT);
+		assert!((0..ITEMS).all(|index| item(index) == 2 * (index + 1)));
+		assert!(calls_allowed());
+	});
+}
+
+#[test]
+fn rollback_restores_the_old_storage() {
+	new_test_ext().execute_with(|| {
+		old_items();
+		LazyMigration::on_runtime_upgrade();
+		idle(3 * ITEM_WEIGHT);
+		idle(3 * ITEM_WEIGHT);
+		assert_eq!(item(5), 12);
+
+		assert_ok!(LazyMigration::rollback(Origin::root()));
+		assert_noop!(LazyMigration::rollback(Origin::root()), Error::<Test>::RollingBack);
+		assert!(!calls_allowed());
+
+		idle(4 * ITEM_WEIGHT);
+		assert_eq!(item(5), 6);
+		assert_eq!(item(1), 4);
+		idle(4 * ITEM_WEIGHT);
+
+		assert!((0..ITEMS).all(|index| item(index) == index + 1));
+		assert_eq!(LazyMigration::progress(), None);
+		assert_eq!(LazyMigration::outcome(ID.to_vec()), Some(Outcome::RolledBack(1)));
+		assert_eq!(migration_events().last(), Some(&MigrationEvent::RolledBack(ID.to_vec())));
+		assert!(calls_allowed());
+
+		// A rolled back migration is only started again on request.
+		LazyMigration::on_runtime_upgrade();
+		assert_eq!(LazyMigration::progress(), None);
+		assert_ok!(LazyMigration::start(Origin::root()));
+		assert_noop!(LazyMigration::start(Origin::root()), Error::<Test>::InFlight);
+		idle(ITEMS as u64 * ITEM_WEIGHT);
+		assert_eq!(LazyMigration::outcome(ID.to_vec()), Some(Outcome::Completed(1)));
+	});
+}
+
+#[test]
+fn controlling_requires_the_control_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(LazyMigration::start(Origin::signed(1)), BadOrigin);
+		assert_noop!(LazyMigration::pause(Origin::signed(1)), BadOrigin);
+		assert_noop!(LazyMigration::resume(Origin::signed(1)), BadOrigin);
+		assert_noop!(LazyMigration::rollback(Origin::signed(1)), BadOrigin);
+		assert_noop!(LazyMigration::pause(Origin::root()), Error::<Test>::NotInFlight);
+	});
+}
+
+#[test]
+fn idle_without_migration_only_reads() {
+	new_test_ext().execute_with(|| {
+		old_items();
+		assert_eq!(idle(ITEMS as u64 * ITEM_WEIGHT), 0);
+		assert_eq!(item(0), 1);
+	});
+}
@@ -0,0 +1,285 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Randomness Beacon Pallet
+//!
+//! Produces randomness from secrets of the validators with a commit-reveal scheme. Unlike with
+//! `pallet_randomness_collective_flip`, a block author can not pick the seed by choosing the
+//! contents of its block.
+//!
+//! Time is divided into sessions of `SessionLength` blocks. During a session, validators commit
+//! to a secret with [`Call::commit`], reserving `CommitDeposit`. During the next session they
+//! reveal it with [`Call::reveal`], which returns the deposit. When the session after that
+//! starts, the revealed secrets are combined into the seed of the session and the deposits of
+//! validators that did not reveal are slashed. If the reveal session had no blocks, e.g. because
+//! `SessionLength` changed, the deposits are returned instead.
+//!
+//! The seed can still be biased:
+//! - The last validator to reveal knows the seed with and without its secret. By withholding its
+//!   secret it chooses between the two, at the cost of its deposit.
+//! - Block authors can leave out the reveals of other validators, which removes their secrets from
+//!   the seed and gets them slashed. Authors that produce all blocks of a reveal session choose
+//!   from as many seeds as they can censor combinations of reveals.
+//!
+//! Only use the seed where biasing it is worth less than `CommitDeposit`.
+//!
+//! [`Pallet::random`] derives random values for a subject from the latest seed and reports how
+//! many blocks it has been known, its lookahead. The pallet also implements `Randomness`.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::traits::{Currency, Get, OnUnbalanced, Randomness, ReservableCurrency};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{Hash, Saturating, UniqueSaturatedInto, Zero},
+	RuntimeDebug,
+};
+
+/// The index of a session, counted from genesis.
+pub type SessionIndex = u32;
+
+/// The balance of the currency of the deposits.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// A slashed deposit.
+pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// The seed of a session.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct Seed<Hash, BlockNumber> {
+	/// The session whose reveals make up the seed.
+	pub session: SessionIndex,
+	/// The combined secrets.
+	pub seed: Hash,
+	/// The block in which the seed was fixed.
+	pub fixed_at: BlockNumber,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency of the deposits.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The accounts that take part in the beacon.
+		type Validators: Contains<Self::AccountId>;
+
+		/// What to do with slashed deposits.
+		type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// The number of blocks of a session.
+		#[pallet::constant]
+		type SessionLength: Get<Self::BlockNumber>;
+
+		/// The deposit reserved by a commitment, slashed if the secret is not revealed.
+		#[pallet::constant]
+		type CommitDeposit: Get<BalanceOf<Self>>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// The session of the current block.
+	#[pallet::storage]
+	#[pallet::getter(fn session_index)]
+	pub type CurrentSession<T: Config> = StorageValue<_, SessionIndex, ValueQuery>;
+
+	/// The commitments that were not revealed yet and their deposits, by session and validator.
+	#[pallet::storage]
+	#[pallet::getter(fn commitment)]
+	pub type Commitments<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		SessionIndex,
+		Blake2_128Concat,
+		T::AccountId,
+		(T::Hash, BalanceOf<T>),
+		OptionQuery,
+	>;
+
+	/// The revealed secrets of a session combined so far, and the number of reveals.
+	#[pallet::storage]
+	pub type Reveals<T: Config> =
+		StorageMap<_, Twox64Concat, SessionIndex, (T::Hash, u32), OptionQuery>;
+
+	/// The seed of the latest session with reveals.
+	#[pallet::storage]
+	#[pallet::getter(fn seed)]
+	pub type LatestSeed<T: Config> = StorageValue<_, Seed<T::Hash, T::BlockNumber>, OptionQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A validator committed to a secret. [who, session]
+		Committed(T::AccountId, SessionIndex),
+		/// A validator revealed its secret. [who, session]
+		Revealed(T::AccountId, SessionIndex),
+		/// A validator did not reveal its secret and lost its deposit. [who, session, amount]
+		Slashed(T::AccountId, SessionIndex, BalanceOf<T>),
+		/// A commitment could not be revealed as its reveal session had no blocks, the deposit
+		/// was returned. [who, session]
+		Released(T::AccountId, SessionIndex),
+		/// The seed of a session was fixed. [session]
+		SeedFixed(SessionIndex),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Only validators take part in the beacon.
+		NotValidator,
+		/// The validator already committed in this session.
+		AlreadyCommitted,
+		/// The validator did not commit in the previous session.
+		NoCommitment,
+		/// The secret does not match the commitment.
+		BadReveal,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(n: T::BlockNumber) -> Weight {
+			let last = CurrentSession::<T>::get();
+			let session: SessionIndex = (n / T::SessionLength::get()).unique_saturated_into();
+			if session == last {
+				return T::DbWeight::get().reads(1)
+			}
+			CurrentSession::<T>::put(session);
+
+			// The reveal session of the commitments of `last - 1` was `last`, which had blocks.
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			if let Some(previous) = last.checked_sub(1) {
+				weight += Self::fix_seed(previous, n, true);
+			}
+			// Sessions only get skipped when `SessionLength` changes, the commitments of `last`
+			// were never revealable then.
+			if session != last.saturating_add(1) {
+				weight += Self::fix_seed(last, n, false);
+			}
+			weight
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Commit to a secret for the current session, reserving `CommitDeposit`.
+		///
+		/// `commitment` is the hash of the SCALE encoded secret and account of the validator, see
+		/// [`Pallet::commitment_of`]. The account is part of it so that validators can not copy
+		/// the commitments of others.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn commit(origin: OriginFor<T>, commitment: T::Hash) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(T::Validators::contains(&who), Error::<T>::NotValidator);
+			let session = Self::session_index();
+			ensure!(!Commitments::<T>::contains_key(session, &who), Error::<T>::AlreadyCommitted);
+
+			// `CommitDeposit` may change before the commitment is revealed.
+			let deposit = T::CommitDeposit::get();
+			T::Currency::reserve(&who, deposit)?;
+			Commitments::<T>::insert(session, &who, (commitment, deposit));
+			Self::deposit_event(Event::Committed(who, session));
+			Ok(())
+		}
+
+		/// Reveal the secret committed to in the previous session, returning the deposit.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn reveal(origin: OriginFor<T>, secret: [u8; 32]) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let session = Self::session_index().checked_sub(1).ok_or(Error::<T>::NoCommitment)?;
+			let (commitment, deposit) =
+				Commitments::<T>::get(session, &who).ok_or(Error::<T>::NoCommitment)?;
+			ensure!(Self::commitment_of(&secret, &who) == commitment, Error::<T>::BadReveal);
+
+			Commitments::<T>::remove(session, &who);
+			T::Currency::unreserve(&who, deposit);
+			Reveals::<T>::mutate(session, |reveals| {
+				let (combined, count) = reveals.get_or_insert_with(Default::default);
+				// XOR the hashes of the secrets, the seed does not depend on the order of reveals.
+				let hash = T::Hashing::hash(&secret);
+				for (byte, other) in combined.as_mut().iter_mut().zip(hash.as_ref()) {
+					*byte ^= other;
+				}
+				*count += 1;
+			});
+			Self::deposit_event(Event::Revealed(who, session));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// The commitment of `who` to `secret`.
+		pub fn commitment_of(secret: &[u8; 32], who: &T::AccountId) -> T::Hash {
+			T::Hashing::hash_of(&(secret, who))
+		}
+
+		/// A random value for `subject` and its lookahead, the number of blocks it has been known
+		/// for. `None` until the first seed is fixed.
+		///
+		/// The value changes once per session. Users that need a value nobody knew before should
+		/// only accept a lookahead smaller than the number of blocks since they committed to
+		/// using it.
+		pub fn random(subject: &[u8]) -> Option<(T::Hash, T::BlockNumber)> {
+			let seed = LatestSeed::<T>::get()?;
+			let now = frame_system::Pallet::<T>::block_number();
+			let output = T::Hashing::hash_of(&(seed.seed, subject));
+			Some((output, now.saturating_sub(seed.fixed_at)))
+		}
+
+		/// Fix the seed of `session`. The validators that did not reveal are slashed if the
+		/// reveal session of `session` had blocks, `revealable`, and released otherwise.
+		fn fix_seed(session: SessionIndex, now: T::BlockNumber, revealable: bool) -> Weight {
+			let mut slashed = 0u64;
+			for (who, (_, deposit)) in Commitments::<T>::drain_prefix(session) {
+				if revealable {
+					let (imbalance, missing) = T::Currency::slash_reserved(&who, deposit);
+					let amount = deposit.saturating_sub(missing);
+					Self::deposit_event(Event::Slashed(who, session, amount));
+					T::Slash::on_unbalanced(imbalance);
+				} else {
+					T::Currency::unreserve(&who, deposit);
+					Self::deposit_event(Event::Released(who, session));
+				}
+				slashed += 1;
+			}
+
+			if let Some((combined, _)) = Reveals::<T>::take(session) {
+				let seed = T::Hashing::hash_of(&(session, combined));
+				LatestSeed::<T>::put(Seed { session, seed, fixed_at: now });
+				Self::deposit_event(Event::SeedFixed(session));
+			}
+			T::DbWeight::get().reads_writes(2 + 2 * slashed, 2 + 2 * slashed)
+		}
+	}
+}
+
+impl<T: Config> Randomness<T::Hash, T::BlockNumber> for Pallet<T> {
+	/// The random value for `subject` and the block in which it became known, the zero hash if
+	/// no seed was fixed yet.
+	fn random(subject: &[u8]) -> (T::Hash, T::BlockNumber) {
+		match Self::random(subject) {
+			Some((output, lookahead)) => {
+				let now = frame_system::Pallet::<T>::block_number();
+				(output, now.saturating_sub(lookahead))
+			},
+			None => (Default::default(), Zero::zero()),
+		}
+	}
+}
@@ -0,0 +1,140 @@
+use crate as pallet_randomness_beacon;
+use frame_support::{
+	parameter_types,
+	traits::{Contains, Get},
+};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		RandomnessBeacon: pallet_randomness_beacon::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+/// Accounts 1 to 3 are validators.
+pub struct Validators;
+
+impl Contains<u64> for Validators {
+	fn contains(who: &u64) -> bool {
+		(1..=3).contains(who)
+	}
+}
+
+parameter_types! {
+	pub const SessionLength: u64 = 10;
+}
+
+thread_local! {
+	static COMMIT_DEPOSIT: RefCell<u64> = RefCell::new(100);
+}
+
+/// The deposit of commitments, 100 unless changed with [`set_commit_deposit`].
+pub struct CommitDeposit;
+
+impl Get<u64> for CommitDeposit {
+	fn get() -> u64 {
+		COMMIT_DEPOSIT.with(|deposit| *deposit.borrow())
+	}
+}
+
+/// Change the deposit of new commitments, like a runtime upgrade would.
+pub fn set_commit_deposit(deposit: u64) {
+	COMMIT_DEPOSIT.with(|current| *current.borrow_mut() = deposit);
+}
+
+impl pallet_randomness_beacon::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type Validators = Validators;
+	type Slash = ();
+	type SessionLength = SessionLength;
+	type CommitDeposit = CommitDeposit;
+}
+
+/// The balance of every account at genesis.
+pub const BALANCE: u64 = 1_000;
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: (1..=4).map(|who| (who, BALANCE)).collect(),
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+	let mut ext: sp_io::TestExternalities = storage.into();
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Run `on_initialize` of the beacon for every block up to `n`.
+pub fn run_to_block(n: u64) {
+	use frame_support::traits::Hooks;
+
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		RandomnessBeacon::on_initialize(System::block_number());
+	}
+}
@@ -0,0 +1,162 @@
+use crate::{mock::*, Error, Event as BeaconEvent};
+use frame_support::{assert_noop, assert_ok, traits::Randomness};
+use sp_core::H256;
+
+fn commit(who: u64, secret: [u8; 32]) {
+	let commitment = RandomnessBeacon::commitment_of(&secret, &who);
+	assert_ok!(RandomnessBeacon::commit(Origin::signed(who), commitment));
+}
+
+#[test]
+fn only_validators_commit_once_per_session() {
+	new_test_ext().execute_with(|| {
+		let commitment = RandomnessBeacon::commitment_of(&[4; 32], &4);
+		assert_noop!(
+			RandomnessBeacon::commit(Origin::signed(4), commitment),
+			Error::<Test>::NotValidator
+		);
+
+		commit(1, [1; 32]);
+		assert_eq!(Balances::reserved_balance(1), 100);
+		assert_noop!(
+			RandomnessBeacon::commit(Origin::signed(1), commitment),
+			Error::<Test>::AlreadyCommitted
+		);
+
+		// A new session takes new commitments.
+		run_to_block(10);
+		commit(1, [2; 32]);
+	});
+}
+
+#[test]
+fn secrets_are_revealed_in_the_next_session() {
+	new_test_ext().execute_with(|| {
+		commit(1, [1; 32]);
+		assert_noop!(
+			RandomnessBeacon::reveal(Origin::signed(1), [1; 32]),
+			Error::<Test>::NoCommitment
+		);
+
+		run_to_block(10);
+		assert_noop!(
+			RandomnessBeacon::reveal(Origin::signed(1), [2; 32]),
+			Error::<Test>::BadReveal
+		);
+		// Others can not reveal the secret of a validator as theirs.
+		assert_noop!(
+			RandomnessBeacon::reveal(Origin::signed(2), [1; 32]),
+			Error::<Test>::NoCommitment
+		);
+		assert_ok!(RandomnessBeacon::reveal(Origin::signed(1), [1; 32]));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		System::assert_last_event(BeaconEvent::Revealed(1, 0).into());
+	});
+}
+
+#[test]
+fn seeds_are_fixed_two_sessions_later() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(RandomnessBeacon::random(b"subject"), None);
+		commit(1, [1; 32]);
+		commit(2, [2; 32]);
+
+		run_to_block(15);
+		assert_ok!(RandomnessBeacon::reveal(Origin::signed(2), [2; 32]));
+		assert_ok!(RandomnessBeacon::reveal(Origin::signed(1), [1; 32]));
+		assert_eq!(RandomnessBeacon::random(b"subject"), None);
+
+		run_to_block(20);
+		System::assert_last_event(BeaconEvent::SeedFixed(0).into());
+		let (output, lookahead) = RandomnessBeacon::random(b"subject").unwrap();
+		assert_eq!(lookahead, 0);
+		assert_ne!(RandomnessBeacon::random(b"other").unwrap().0, output);
+
+		run_to_block(23);
+		assert_eq!(RandomnessBeacon::random(b"subject"), Some((output, 3)));
+		assert_eq!(<RandomnessBeacon as Randomness<H256, u64>>::random(b"subject"), (output, 20));
+	});
+}
+
+#[test]
+fn the_seed_does_not_depend_on_the_order_of_reveals() {
+	let seed = |first: u64, second: u64| {
+		new_test_ext().execute_with(|| {
+			commit(1, [1; 32]);
+			commit(2, [2; 32]);
+			run_to_block(10);
+			assert_ok!(RandomnessBeacon::reveal(Origin::signed(first), [first as u8; 32]));
+			assert_ok!(RandomnessBeacon::reveal(Origin::signed(second), [second as u8; 32]));
+			run_to_block(20);
+			RandomnessBeacon::seed().unwrap().seed
+		})
+	};
+	assert_eq!(seed(1, 2), seed(2, 1));
+}
+
+#[test]
+fn validators_that_do_not_reveal_are_slashed() {
+	new_test_ext().execute_with(|| {
+		commit(1, [1; 32]);
+		commit(2, [2; 32]);
+
+		run_to_block(10);
+		assert_ok!(RandomnessBeacon::reveal(Origin::signed(1), [1; 32]));
+
+		run_to_block(20);
+		assert!(System::events()
+			.iter()
+			.any(|record| record.event == BeaconEvent::Slashed(2, 0, 100).into()));
+		assert_eq!(Balances::total_balance(&2), BALANCE - 100);
+		assert_eq!(Balances::total_balance(&1), BALANCE);
+		assert_eq!(RandomnessBeacon::commitment(0, 2), None);
+	});
+}
+
+#[test]
+fn commitments_without_a_reveal_session_are_released() {
+	use frame_support::traits::Hooks;
+
+	new_test_ext().execute_with(|| {
+		commit(1, [1; 32]);
+		run_to_block(10);
+		commit(2, [2; 32]);
+
+		// Skip session 2, as after a change of `SessionLength`.
+		System::set_block_number(35);
+		RandomnessBeacon::on_initialize(35);
+		assert_eq!(RandomnessBeacon::session_index(), 3);
+
+		// Session 1 was the reveal session of 1, which did not reveal.
+		System::assert_has_event(BeaconEvent::Slashed(1, 0, 100).into());
+		assert_eq!(Balances::total_balance(&1), BALANCE - 100);
+		// 2 never had a reveal session.
+		System::assert_has_event(BeaconEvent::Released(2, 1).into());
+		assert_eq!(Balances::total_balance(&2), BALANCE);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(RandomnessBeacon::commitment(1, 2), None);
+	});
+}
+
+#[test]
+fn deposits_are_kept_with_their_commitments() {
+	new_test_ext().execute_with(|| {
+		commit(1, [1; 32]);
+		commit(2, [2; 32]);
+		set_commit_deposit(300);
+		commit(3, [3; 32]);
+		assert_eq!(Balances::reserved_balance(3), 300);
+
+		// The deposit reserved at commit time is returned or slashed.
+		run_to_block(10);
+		assert_ok!(RandomnessBeacon::reveal(Origin::signed(1), [1; 32]));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), BALANCE);
+
+		run_to_block(20);
+		System::assert_has_event(BeaconEvent::Slashed(2, 0, 100).into());
+		System::assert_has_event(BeaconEvent::Slashed(3, 0, 300).into());
+		assert_eq!(Balances::total_balance(&2), BALANCE - 100);
+		assert_eq!(Balances::total_balance(&3), BALANCE - 300);
+	});
+}
@@ -0,0 +1,447 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Sponsorship Pallet
+//!
+//! Lets an account pay the transaction fees of other accounts, e.g. a dApp paying for the calls
+//! of its users. A sponsor publishes a [`Policy`]: how much each user may spend on fees, which
+//! calls are sponsored and until which block. A user names the sponsor in the
+//! [`ChargeSponsored`] signed extension of a transaction, which checks the policy and charges
+//! the fee to the sponsor instead of the signer.
+//!
+//! [`ChargeSponsored`] wraps `pallet_transaction_payment::ChargeTransactionPayment` and takes
+//! its place in the signed extensions of the runtime. Transactions without a sponsor pay their
+//! own fees as before. Only calls accepted by `SponsorableCalls` can be sponsored at all.
+//!
+//! Contracts sponsor transactions like any other account. As a contract can not dispatch
+//! [`Call::set_policy`] itself, the `ForceOrigin` sets policies on behalf of other accounts.
+//!
+//! A sponsor reserves [`Pallet::policy_deposit`] for its policy. What a sponsor paid for a user
+//! is tagged with the generation of the policy, which [`Call::remove_policy`] bumps: removing a
+//! policy resets all allowances without touching the storage of every user.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{Contains, Currency, ReservableCurrency},
+	weights::{DispatchInfo, PostDispatchInfo},
+	RuntimeDebug,
+};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, Saturating, SignedExtension, Zero},
+	transaction_validity::{InvalidTransaction, TransactionValidity, TransactionValidityError},
+	DispatchResult, FixedPointOperand,
+};
+use sp_std::prelude::*;
+
+/// The balance fees are paid in.
+pub type BalanceOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::Balance;
+
+/// The balance of the currency of the policy deposits.
+pub type DepositBalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// The policy of a sponsor, as stored.
+pub type PolicyOf<T> = Policy<BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+
+/// A call, or all calls of a pallet, by their indices in the encoding of the runtime call.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct CallIndex {
+	/// The index of the pallet in `construct_runtime!`.
+	pub pallet: u8,
+	/// The index of the call in the pallet, `None` for all calls of the pallet.
+	pub call: Option<u8>,
+}
+
+/// What a sponsor pays for.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct Policy<Balance, BlockNumber> {
+	/// How much each user may spend on fees and tips in total.
+	pub allowance: Balance,
+	/// The sponsored calls, all sponsorable calls if empty.
+	pub calls: Vec<CallIndex>,
+	/// The last block in which transactions are sponsored.
+	pub expires: BlockNumber,
+}
+
+impl<Balance, BlockNumber> Policy<Balance, BlockNumber> {
+	/// Whether the policy covers the encoded runtime call `call`.
+	pub fn covers(&self, call: &[u8]) -> bool {
+		self.calls.is_empty() ||
+			self.calls.iter().any(|index| {
+				call.get(0) == Some(&index.pallet) &&
+					index.call.map_or(true, |index| call.get(1) == Some(&index))
+			})
+	}
+}
+
+/// Why a sponsored transaction is invalid, the code of `InvalidTransaction::Custom`.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[repr(u8)]
+pub enum InvalidSponsorship {
+	/// The call can not be sponsored in this runtime.
+	NotSponsorable = 1,
+	/// The sponsor has no policy.
+	NoPolicy = 2,
+	/// The policy of the sponsor expired.
+	Expired = 3,
+	/// The policy does not cover the call.
+	CallNotCovered = 4,
+	/// The fee exceeds what is left of the allowance of the signer.
+	AllowanceExceeded = 5,
+}
+
+impl From<InvalidSponsorship> for TransactionValidityError {
+	fn from(error: InvalidSponsorship) -> Self {
+		InvalidTransaction::Custom(error as u8).into()
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_transaction_payment::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The calls that can be sponsored.
+		type SponsorableCalls: Contains<<Self as frame_system::Config>::Call>;
+
+		/// The origin that may set the policy of any account.
+		type ForceOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The maximum number of calls in a policy.
+		#[pallet::constant]
+		type MaxCalls: Get<u32>;
+
+		/// The currency of the policy deposits.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The deposit reserved for every policy.
+		#[pallet::constant]
+		type DepositBase: Get<DepositBalanceOf<Self>>;
+
+		/// The deposit reserved per byte of a policy and its key.
+		#[pallet::constant]
+		type DepositPerByte: Get<DepositBalanceOf<Self>>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// The policies of the sponsors.
+	#[pallet::storage]
+	#[pallet::getter(fn policy)]
+	pub type Policies<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, PolicyOf<T>>;
+
+	/// The deposits reserved for the policies, by sponsor.
+	#[pallet::storage]
+	#[pallet::getter(fn deposit_of)]
+	pub type Deposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, DepositBalanceOf<T>>;
+
+	/// The generation of the policy of each sponsor, bumped whenever a policy is removed.
+	#[pallet::storage]
+	#[pallet::getter(fn generation)]
+	pub type Generations<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// What each sponsor paid for each user, and the generation of the policy it was paid under.
+	/// See [`Pallet::spent`] for what counts towards the allowance.
+	#[pallet::storage]
+	pub type Spent<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AccountId,
+		(u32, BalanceOf<T>),
+		ValueQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A sponsor set its policy. [sponsor]
+		PolicySet(T::AccountId),
+		/// A sponsor stopped sponsoring and got its deposit back. [sponsor, deposit]
+		PolicyRemoved(T::AccountId, DepositBalanceOf<T>),
+		/// A sponsor paid the fee of a transaction. [sponsor, user, fee]
+		FeePaid(T::AccountId, T::AccountId, BalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The policy lists more than `MaxCalls` calls.
+		TooManyCalls,
+		/// The account has no policy.
+		NoPolicy,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Sponsor transactions according to `policy`.
+		///
+		/// What users spent under a previous policy still counts towards their allowance. The
+		/// deposit is adjusted to the size of the new policy.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn set_policy(origin: OriginFor<T>, policy: PolicyOf<T>) -> DispatchResult {
+			let sponsor = ensure_signed(origin)?;
+			Self::do_set_policy(sponsor, policy)
+		}
+
+		/// Stop sponsoring transactions and return the deposit. This also resets the allowances
+		/// of all users.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 4))]
+		pub fn remove_policy(origin: OriginFor<T>) -> DispatchResult {
+			let sponsor = ensure_signed(origin)?;
+			Self::do_remove_policy(sponsor)
+		}
+
+		/// Set or remove the policy of `sponsor`, e.g. of a contract. The deposit is reserved from
+		/// `sponsor`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 4))]
+		pub fn force_set_policy(
+			origin: OriginFor<T>,
+			sponsor: T::AccountId,
+			policy: Option<PolicyOf<T>>,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+			match policy {
+				Some(policy) => Self::do_set_policy(sponsor, policy),
+				None => Self::do_remove_policy(sponsor),
+			}
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn do_set_policy(sponsor: T::AccountId, policy: PolicyOf<T>) -> DispatchResult {
+			ensure!(policy.calls.len() <= T::MaxCalls::get() as usize, Error::<T>::TooManyCalls);
+
+			// Reserve what the new policy needs beyond the deposit of the old one.
+			let deposit = Self::policy_deposit(&sponsor, &policy);
+			let old = Deposits::<T>::get(&sponsor).unwrap_or_default();
+			if deposit > old {
+				T::Currency::reserve(&sponsor, deposit - old)?;
+			} else {
+				T::Currency::unreserve(&sponsor, old - deposit);
+			}
+			Deposits::<T>::insert(&sponsor, deposit);
+
+			Policies::<T>::insert(&sponsor, policy);
+			Self::deposit_event(Event::PolicySet(sponsor));
+			Ok(())
+		}
+
+		fn do_remove_policy(sponsor: T::AccountId) -> DispatchResult {
+			Policies::<T>::take(&sponsor).ok_or(Error::<T>::NoPolicy)?;
+			let deposit = Deposits::<T>::take(&sponsor).unwrap_or_default();
+			T::Currency::unreserve(&sponsor, deposit);
+			// What was spent under the old generation no longer counts.
+			Generations::<T>::mutate(&sponsor, |generation| {
+				*generation = generation.wrapping_add(1)
+			});
+			Self::deposit_event(Event::PolicyRemoved(sponsor, deposit));
+			Ok(())
+		}
+
+		/// The deposit for the policy `policy` of `sponsor`: `DepositBase` plus `DepositPerByte`
+		/// for every byte of the key and the policy.
+		pub fn policy_deposit(sponsor: &T::AccountId, policy: &PolicyOf<T>) -> DepositBalanceOf<T> {
+			let bytes = (sponsor, policy).encoded_size() as u32;
+			T::DepositBase::get()
+				.saturating_add(T::DepositPerByte::get().saturating_mul(bytes.into()))
+		}
+
+		/// What `sponsor` paid for `user` under its current policy generation.
+		pub fn spent(sponsor: &T::AccountId, user: &T::AccountId) -> BalanceOf<T> {
+			match Spent::<T>::get(sponsor, user) {
+				(generation, spent) if generation == Generations::<T>::get(sponsor) => spent,
+				_ => Zero::zero(),
+			}
+		}
+
+		/// Add `fee` to what `sponsor` paid for `user` under its current policy generation.
+		fn add_spent(sponsor: &T::AccountId, user: &T::AccountId, fee: BalanceOf<T>) {
+			let spent = Self::spent(sponsor, user).saturating_add(fee);
+			Spent::<T>::insert(sponsor, user, (Generations::<T>::get(sponsor), spent));
+		}
+
+		/// Check that `sponsor` pays `fee` for `call` signed by `user`.
+		pub fn check_sponsorship(
+			sponsor: &T::AccountId,
+			user: &T::AccountId,
+			call: &<T as frame_system::Config>::Call,
+			fee: BalanceOf<T>,
+		) -> Result<(), InvalidSponsorship>
+		where
+			<T as frame_system::Config>::Call: Encode,
+		{
+			if !T::SponsorableCalls::contains(call) {
+				return Err(InvalidSponsorship::NotSponsorable)
+			}
+			let policy = Policies::<T>::get(sponsor).ok_or(InvalidSponsorship::NoPolicy)?;
+			if frame_system::Pallet::<T>::block_number() > policy.expires {
+				return Err(InvalidSponsorship::Expired)
+			}
+			if !call.using_encoded(|call| policy.covers(call)) {
+				return Err(InvalidSponsorship::CallNotCovered)
+			}
+			if Self::spent(sponsor, user).saturating_add(fee) > policy.allowance {
+				return Err(InvalidSponsorship::AllowanceExceeded)
+			}
+			Ok(())
+		}
+	}
+}
+
+/// Charges the transaction fee to the sponsor named in the transaction, or to the signer if
+/// there is none.
+///
+/// Takes the place of `ChargeTransactionPayment` in the signed extensions of the runtime.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeSponsored<T: Config> {
+	#[codec(compact)]
+	tip: BalanceOf<T>,
+	sponsor: Option<T::AccountId>,
+}
+
+impl<T: Config> ChargeSponsored<T> {
+	/// Pay `tip` on top of the fee, which `sponsor` pays if given.
+	pub fn new(tip: BalanceOf<T>, sponsor: Option<T::AccountId>) -> Self {
+		ChargeSponsored { tip, sponsor }
+	}
+
+	fn charge(&self) -> ChargeTransactionPayment<T> {
+		ChargeTransactionPayment::from(self.tip)
+	}
+}
+
+impl<T: Config> From<BalanceOf<T>> for ChargeSponsored<T> {
+	fn from(tip: BalanceOf<T>) -> Self {
+		ChargeSponsored::new(tip, None)
+	}
+}
+
+impl<T: Config> sp_std::fmt::Debug for ChargeSponsored<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "ChargeSponsored<{:?}, {:?}>", self.tip, self.sponsor)
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config> SignedExtension for ChargeSponsored<T>
+where
+	BalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+	<T as frame_system::Config>::Call:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo> + Encode,
+{
+	const IDENTIFIER: &'static str = "ChargeSponsored";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	/// The sponsor and the user of a sponsored transaction, and what `ChargeTransactionPayment`
+	/// needs after dispatch.
+	type Pre = (
+		Option<(T::AccountId, T::AccountId)>,
+		<ChargeTransactionPayment<T> as SignedExtension>::Pre,
+	);
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		match &self.sponsor {
+			Some(sponsor) => {
+				self.check(sponsor, who, call, info, len)?;
+				self.charge().validate(sponsor, call, info, len)
+			},
+			None => self.charge().validate(who, call, info, len),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		match &self.sponsor {
+			Some(sponsor) => {
+				self.check(sponsor, who, call, info, len)?;
+				let pre = self.charge().pre_dispatch(sponsor, call, info, len)?;
+				Ok((Some((sponsor.clone(), who.clone())), pre))
+			},
+			None => Ok((None, self.charge().pre_dispatch(who, call, info, len)?)),
+		}
+	}
+
+	fn post_dispatch(
+		(sponsorship, pre): Self::Pre,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		let tip = pre.0;
+		ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result)?;
+
+		if let Some((sponsor, user)) = sponsorship {
+			let fee = pallet_transaction_payment::Pallet::<T>::compute_actual_fee(
+				len as u32, info, post_info, tip,
+			);
+			if !fee.is_zero() {
+				Pallet::<T>::add_spent(&sponsor, &user, fee);
+				Pallet::<T>::deposit_event(Event::FeePaid(sponsor, user, fee));
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<T: Config> ChargeSponsored<T>
+where
+	BalanceOf<T>: FixedPointOperand,
+	<T as frame_system::Config>::Call:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo> + Encode,
+{
+	/// Check the policy of `sponsor` against the highest fee the transaction may cost.
+	fn check(
+		&self,
+		sponsor: &T::AccountId,
+		who: &T::AccountId,
+		call: &<T as frame_system::Config>::Call,
+		info: &DispatchInfoOf<<T as frame_system::Config>::Call>,
+		len: usize,
+	) -> Result<(), InvalidSponsorship> {
+		let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, self.tip);
+		Pallet::<T>::check_sponsorship(sponsor, who, call, fee)
+	}
+}
@@ -0,0 +1,193 @@
+use crate::{
+	mock::*, CallIndex, ChargeSponsored, Error, Event as SponsorshipEvent, InvalidSponsorship,
+	Policy,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use sp_runtime::{
+	traits::SignedExtension,
+	transaction_validity::{InvalidTransaction, TransactionValidityError},
+};
+
+const LEN: usize = 10;
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![] })
+}
+
+fn info() -> DispatchInfo {
+	DispatchInfo { weight: 100, ..Default::default() }
+}
+
+fn fee() -> u64 {
+	TransactionPayment::compute_fee(LEN as u32, &info(), 0)
+}
+
+/// The deposit of a policy without calls.
+fn deposit() -> u64 {
+	Sponsorship::policy_deposit(&SPONSOR, &policy(0, vec![]))
+}
+
+fn policy(allowance: u64, calls: Vec<CallIndex>) -> Policy<u64, u64> {
+	Policy { allowance, calls, expires: 10 }
+}
+
+/// Validate and dispatch a remark of `USER` paid by `sponsor`.
+fn sponsored_remark(sponsor: Option<u64>) -> Result<(), TransactionValidityError> {
+	let extension = ChargeSponsored::<Test>::new(0, sponsor);
+	extension.validate(&USER, &remark(), &info(), LEN)?;
+	let pre = extension.pre_dispatch(&USER, &remark(), &info(), LEN)?;
+	ChargeSponsored::<Test>::post_dispatch(pre, &info(), &PostDispatchInfo::default(), LEN, &Ok(()))
+}
+
+#[test]
+fn sponsor_pays_the_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(10 * fee(), vec![])));
+
+		assert_ok!(sponsored_remark(Some(SPONSOR)));
+		assert_eq!(Balances::free_balance(SPONSOR), SPONSOR_BALANCE - deposit() - fee());
+		assert_eq!(Sponsorship::spent(&SPONSOR, &USER), fee());
+		System::assert_last_event(SponsorshipEvent::FeePaid(SPONSOR, USER, fee()).into());
+	});
+}
+
+#[test]
+fn unsponsored_transactions_pay_their_own_fees() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(10 * fee(), vec![])));
+
+		assert_eq!(sponsored_remark(None), Err(InvalidTransaction::Payment.into()));
+		assert_eq!(Balances::free_balance(SPONSOR), SPONSOR_BALANCE - deposit());
+	});
+}
+
+#[test]
+fn allowance_caps_each_user() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(fee(), vec![])));
+
+		assert_ok!(sponsored_remark(Some(SPONSOR)));
+		assert_eq!(
+			sponsored_remark(Some(SPONSOR)),
+			Err(InvalidSponsorship::AllowanceExceeded.into())
+		);
+	});
+}
+
+#[test]
+fn policy_expires() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(10 * fee(), vec![])));
+
+		System::set_block_number(11);
+		assert_eq!(sponsored_remark(Some(SPONSOR)), Err(InvalidSponsorship::Expired.into()));
+	});
+}
+
+#[test]
+fn policy_filters_calls() {
+	new_test_ext().execute_with(|| {
+		// `remark` is the call with index 1 of `System` at index 0.
+		let balances = CallIndex { pallet: 1, call: None };
+		let set_heap_pages = CallIndex { pallet: 0, call: Some(2) };
+		let remark = CallIndex { pallet: 0, call: Some(1) };
+
+		let calls = vec![balances, set_heap_pages];
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(10 * fee(), calls)));
+		assert_eq!(sponsored_remark(Some(SPONSOR)), Err(InvalidSponsorship::CallNotCovered.into()));
+
+		let calls = vec![balances, remark];
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(10 * fee(), calls)));
+		assert_ok!(sponsored_remark(Some(SPONSOR)));
+
+		let too_many = vec![balances, remark, set_heap_pages];
+		assert_noop!(
+			Sponsorship::set_policy(Origin::signed(SPONSOR), policy(10 * fee(), too_many)),
+			Error::<Test>::TooManyCalls
+		);
+	});
+}
+
+#[test]
+fn only_sponsorable_calls_are_sponsored() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(10 * fee(), vec![])));
+
+		let call = Call::Balances(pallet_balances::Call::transfer { dest: SPONSOR, value: 1 });
+		let extension = ChargeSponsored::<Test>::new(0, Some(SPONSOR));
+		assert_eq!(
+			extension.validate(&USER, &call, &info(), LEN),
+			Err(InvalidSponsorship::NotSponsorable.into())
+		);
+	});
+}
+
+#[test]
+fn removing_the_policy_stops_sponsoring() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(sponsored_remark(Some(SPONSOR)), Err(InvalidSponsorship::NoPolicy.into()));
+		assert_noop!(Sponsorship::remove_policy(Origin::signed(SPONSOR)), Error::<Test>::NoPolicy);
+
+		assert_ok!(Sponsorship::force_set_policy(
+			Origin::root(),
+			SPONSOR,
+			Some(policy(10 * fee(), vec![]))
+		));
+		assert_ok!(sponsored_remark(Some(SPONSOR)));
+
+		assert_ok!(Sponsorship::remove_policy(Origin::signed(SPONSOR)));
+		assert_eq!(Sponsorship::spent(&SPONSOR, &USER), 0);
+		assert_eq!(sponsored_remark(Some(SPONSOR)), Err(InvalidSponsorship::NoPolicy.into()));
+	});
+}
+
+#[test]
+fn policies_reserve_a_deposit() {
+	new_test_ext().execute_with(|| {
+		let remark = CallIndex { pallet: 0, call: Some(1) };
+		let with_call = policy(10 * fee(), vec![remark]);
+		let deposit_with_call = Sponsorship::policy_deposit(&SPONSOR, &with_call);
+		assert!(deposit_with_call > deposit());
+
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), with_call));
+		assert_eq!(Balances::reserved_balance(SPONSOR), deposit_with_call);
+
+		// A smaller policy returns the difference.
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(10 * fee(), vec![])));
+		assert_eq!(Balances::reserved_balance(SPONSOR), deposit());
+		assert_eq!(Sponsorship::deposit_of(SPONSOR), Some(deposit()));
+
+		assert_ok!(Sponsorship::remove_policy(Origin::signed(SPONSOR)));
+		assert_eq!(Balances::reserved_balance(SPONSOR), 0);
+		System::assert_last_event(SponsorshipEvent::PolicyRemoved(SPONSOR, deposit()).into());
+
+		// Accounts without funds can not set policies.
+		assert!(Sponsorship::set_policy(Origin::signed(USER), policy(10 * fee(), vec![])).is_err());
+	});
+}
+
+#[test]
+fn new_policies_start_with_fresh_allowances() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(fee(), vec![])));
+		assert_ok!(sponsored_remark(Some(SPONSOR)));
+		assert_eq!(
+			sponsored_remark(Some(SPONSOR)),
+			Err(InvalidSponsorship::AllowanceExceeded.into())
+		);
+
+		// Updating the policy keeps what was spent.
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(fee(), vec![])));
+		assert_eq!(Sponsorship::spent(&SPONSOR, &USER), fee());
+
+		assert_ok!(Sponsorship::remove_policy(Origin::signed(SPONSOR)));
+		assert_eq!(Sponsorship::generation(SPONSOR), 1);
+		assert_ok!(Sponsorship::set_policy(Origin::signed(SPONSOR), policy(fee(), vec![])));
+		assert_eq!(Sponsorship::spent(&SPONSOR, &USER), 0);
+		assert_ok!(sponsored_remark(Some(SPONSOR)));
+		assert_eq!(Sponsorship::spent(&SPONSOR, &USER), fee());
+	});
+}
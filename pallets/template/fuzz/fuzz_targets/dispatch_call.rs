@@ -0,0 +1,36 @@
+//! Decode arbitrary bytes into a sequence of `(origin, call)` pairs and dispatch them against the
+//! mock runtime. Any panic is a bug: dispatchables must fail gracefully on every input.
+
+#![no_main]
+
+use codec::Decode;
+use frame_support::{traits::UnfilteredDispatchable, weights::GetDispatchInfo};
+use libfuzzer_sys::fuzz_target;
+use pallet_template::{
+	mock::{new_test_ext, Origin, System, Test},
+	Call,
+};
+
+fuzz_target!(|data: &[u8]| {
+	let mut input = data;
+
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+
+		while let Some((&selector, rest)) = input.split_first() {
+			input = rest;
+			let call = match Call::<Test>::decode(&mut input) {
+				Ok(call) => call,
+				Err(_) => return,
+			};
+			let origin = match selector % 3 {
+				0 => Origin::root(),
+				1 => Origin::none(),
+				_ => Origin::signed((selector / 3).into()),
+			};
+
+			let _ = call.get_dispatch_info();
+			let _ = call.dispatch_bypass_filter(origin);
+		}
+	});
+});
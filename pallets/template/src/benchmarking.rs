@@ -4,16 +4,55 @@ use super::*;
 
 #[allow(unused)]
 use crate::Pallet as Template;
-use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::{
+	sp_runtime::traits::Bounded,
+	traits::{Currency, UnfilteredDispatchable},
+};
 use frame_system::RawOrigin;
 
+/// A writer that can pay the deposit of a value.
+fn funded_caller<T: Config>() -> T::AccountId {
+	let caller: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 2u32.into());
+	Roles::<T>::insert(Role::Writer, &caller, ());
+	caller
+}
+
 benchmarks! {
 	do_something {
 		let s in 0 .. 100;
-		let caller: T::AccountId = whitelisted_caller();
-	}: _(RawOrigin::Signed(caller), s)
+		let caller = funded_caller::<T>();
+	}: _(RawOrigin::Signed(caller.clone()), s)
+	verify {
+		assert_eq!(Something::<T>::get(&caller), Some(s));
+	}
+
+	clear_something {
+		let caller = funded_caller::<T>();
+		Template::<T>::do_something(RawOrigin::Signed(caller.clone()).into(), 7)?;
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert_eq!(Something::<T>::get(&caller), None);
+	}
+
+	grant_role {
+		let origin = T::RoleOrigin::successful_origin();
+		let who: T::AccountId = account("who", 0, 0);
+		let call = Call::<T>::grant_role { role: Role::Writer, who: who.clone() };
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert!(Roles::<T>::contains_key(Role::Writer, &who));
+	}
+
+	revoke_role {
+		let origin = T::RoleOrigin::successful_origin();
+		let who: T::AccountId = account("who", 0, 0);
+		Roles::<T>::insert(Role::Writer, &who, ());
+		let call = Call::<T>::revoke_role { role: Role::Writer, who: who.clone() };
+	}: { call.dispatch_bypass_filter(origin)? }
 	verify {
-		assert_eq!(Something::<T>::get(), Some(s));
+		assert!(!Roles::<T>::contains_key(Role::Writer, &who));
 	}
 }
 
@@ -5,8 +5,15 @@
 /// <https://docs.substrate.io/v3/runtime/frame>
 pub use pallet::*;
 
-#[cfg(test)]
-mod mock;
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{sp_runtime::RuntimeDebug, sp_std::marker::PhantomData, traits::EnsureOrigin};
+use frame_system::RawOrigin;
+use scale_info::TypeInfo;
+
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod mock;
+
+pub mod migrations;
 
 #[cfg(test)]
 mod tests;
@@ -14,20 +21,88 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+/// The balance of the currency of the deposits.
+pub type BalanceOf<T> = <<T as Config>::Currency as frame_support::traits::Currency<
+	<T as frame_system::Config>::AccountId,
+>>::Balance;
+
+/// A role of an account, granted and revoked by `RoleOrigin`.
+///
+/// Contract accounts hold roles like any other account, and contracts read them through the
+/// template extension of the runtime.
+#[derive(Encode, Decode, MaxEncodedLen, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum Role {
+	/// Holds every other role, and may grant and revoke roles if the runtime lets it.
+	Admin,
+	/// May store and update values.
+	Writer,
+	/// Not checked by the pallet, for contracts and off-chain services that gate reads.
+	Reader,
+}
+
+/// Ensures that the origin is signed by an account with the [`Role::Admin`] role.
+pub struct EnsureAdmin<T>(PhantomData<T>);
+
+impl<T: Config> EnsureOrigin<T::Origin> for EnsureAdmin<T> {
+	type Success = T::AccountId;
+
+	fn try_origin(origin: T::Origin) -> Result<Self::Success, T::Origin> {
+		origin.into().and_then(|origin| match origin {
+			RawOrigin::Signed(who) if Pallet::<T>::has_role(Role::Admin, &who) => Ok(who),
+			origin => Err(T::Origin::from(origin)),
+		})
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn successful_origin() -> T::Origin {
+		let who = T::AccountId::default();
+		Roles::<T>::insert(Role::Admin, &who, ());
+		RawOrigin::Signed(who).into()
+	}
+}
+
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
+	use super::{BalanceOf, Role};
+	use codec::Encode;
+	use frame_support::{
+		dispatch::DispatchResult, pallet_prelude::*, sp_runtime::traits::Saturating,
+		traits::ReservableCurrency,
+	};
 	use frame_system::pallet_prelude::*;
 
+	use frame_support::sp_std::vec::Vec;
+
+	#[cfg(feature = "try-runtime")]
+	use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency of the storage deposits.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The deposit reserved for every stored value.
+		#[pallet::constant]
+		type DepositBase: Get<BalanceOf<Self>>;
+
+		/// The deposit reserved per byte of a stored value and its key.
+		#[pallet::constant]
+		type DepositPerByte: Get<BalanceOf<Self>>;
+
+		/// The origin that grants and revokes roles.
+		type RoleOrigin: EnsureOrigin<Self::Origin>;
 	}
 
+	/// The current storage version, see [`crate::migrations`] for how to change the storage.
+	pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	// The pallet's runtime storage items.
@@ -36,7 +111,40 @@ pub mod pallet {
 	#[pallet::getter(fn something)]
 	// Learn more about declaring storage items:
 	// https://docs.substrate.io/v3/runtime/storage#declaring-storage-items
-	pub type Something<T> = StorageValue<_, u32>;
+	pub type Something<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32>;
+
+	/// The deposits reserved for the values of `Something`, by account. Values stored before
+	/// deposits were introduced have none.
+	#[pallet::storage]
+	#[pallet::getter(fn deposit_of)]
+	pub type Deposits<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>>;
+
+	/// The accounts holding each role.
+	#[pallet::storage]
+	pub type Roles<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, Role, Blake2_128Concat, T::AccountId, ()>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// The roles accounts hold at genesis.
+		pub roles: Vec<(Role, T::AccountId)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { roles: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (role, who) in &self.roles {
+				<Roles<T>>::insert(role, who, ());
+			}
+		}
+	}
 
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/v3/runtime/events
@@ -46,6 +154,12 @@ pub mod pallet {
 		/// Event documentation should end with an array that provides descriptive names for event
 		/// parameters. [something, who]
 		SomethingStored(u32, T::AccountId),
+		/// A value was removed and its deposit returned. [who, deposit]
+		SomethingCleared(T::AccountId, BalanceOf<T>),
+		/// A role was granted to an account. [role, who]
+		RoleGranted(Role, T::AccountId),
+		/// A role was revoked from an account. [role, who]
+		RoleRevoked(Role, T::AccountId),
 	}
 
 	// Errors inform users that something went wrong.
@@ -55,6 +169,37 @@ pub mod pallet {
 		NoneValue,
 		/// Errors should have helpful documentation associated with them.
 		StorageOverflow,
+		/// The signer does not hold the role the call requires.
+		MissingRole,
+		/// The account already holds the role.
+		RoleAlreadyGranted,
+		/// The account does not hold the role.
+		RoleNotGranted,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		// Checks run by `try-runtime on-runtime-upgrade` around the migrations of the runtime.
+		// Upgrades must not lose the stored values.
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			let values: Vec<(T::AccountId, u32)> = <Something<T>>::iter().collect();
+			Self::set_temp_storage(values, "something");
+			Ok(())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			let before: Vec<(T::AccountId, u32)> = Self::get_temp_storage("something")
+				.ok_or("`Something` was not recorded before the upgrade")?;
+			for (who, value) in before {
+				ensure!(
+					<Something<T>>::get(&who) == Some(value),
+					"`Something` changed during the upgrade"
+				);
+			}
+			Ok(())
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -64,15 +209,27 @@ pub mod pallet {
 	impl<T: Config> Pallet<T> {
 		/// An example dispatchable that takes a singles value as a parameter, writes the value to
 		/// storage and emits an event. This function must be dispatched by a signed extrinsic.
-		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		///
+		/// Every account with the [`Role::Writer`] role stores its own value. The first value of an
+		/// account reserves [`Pallet::item_deposit`], which is returned by
+		/// [`Pallet::clear_something`].
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
 		pub fn do_something(origin: OriginFor<T>, something: u32) -> DispatchResult {
 			// Check that the extrinsic was signed and get the signer.
 			// This function will return an error if the extrinsic is not signed.
 			// https://docs.substrate.io/v3/runtime/origins
 			let who = ensure_signed(origin)?;
+			Self::ensure_role(Role::Writer, &who)?;
+
+			// Pay for the storage of a new value, overwriting a value is free.
+			if !<Something<T>>::contains_key(&who) {
+				let deposit = Self::item_deposit(&who);
+				T::Currency::reserve(&who, deposit)?;
+				<Deposits<T>>::insert(&who, deposit);
+			}
 
 			// Update storage.
-			<Something<T>>::put(something);
+			<Something<T>>::insert(&who, something);
 
 			// Emit an event.
 			Self::deposit_event(Event::SomethingStored(something, who));
@@ -80,23 +237,80 @@ pub mod pallet {
 			Ok(())
 		}
 
-		/// An example dispatchable that may throw a custom error.
-		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1,1))]
+		/// An example dispatchable that may throw a custom error. Requires the [`Role::Writer`]
+		/// role.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3,1))]
 		pub fn cause_error(origin: OriginFor<T>) -> DispatchResult {
-			let _who = ensure_signed(origin)?;
+			let who = ensure_signed(origin)?;
+			Self::ensure_role(Role::Writer, &who)?;
 
 			// Read a value from storage.
-			match <Something<T>>::get() {
+			match <Something<T>>::get(&who) {
 				// Return an error if the value has not been set.
 				None => Err(Error::<T>::NoneValue)?,
 				Some(old) => {
 					// Increment the value read from storage; will error in the event of overflow.
 					let new = old.checked_add(1).ok_or(Error::<T>::StorageOverflow)?;
 					// Update the value in storage with the incremented result.
-					<Something<T>>::put(new);
+					<Something<T>>::insert(&who, new);
 					Ok(())
 				},
 			}
 		}
+
+		/// Remove the value of the signer and return its deposit.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn clear_something(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			<Something<T>>::take(&who).ok_or(Error::<T>::NoneValue)?;
+			let deposit = <Deposits<T>>::take(&who).unwrap_or_default();
+			T::Currency::unreserve(&who, deposit);
+
+			Self::deposit_event(Event::SomethingCleared(who, deposit));
+			Ok(())
+		}
+
+		/// Grant `role` to `who`, which may be a contract account.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn grant_role(origin: OriginFor<T>, role: Role, who: T::AccountId) -> DispatchResult {
+			T::RoleOrigin::ensure_origin(origin)?;
+			ensure!(!<Roles<T>>::contains_key(role, &who), Error::<T>::RoleAlreadyGranted);
+
+			<Roles<T>>::insert(role, &who, ());
+			Self::deposit_event(Event::RoleGranted(role, who));
+			Ok(())
+		}
+
+		/// Revoke `role` from `who`. The values `who` stored are kept.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn revoke_role(origin: OriginFor<T>, role: Role, who: T::AccountId) -> DispatchResult {
+			T::RoleOrigin::ensure_origin(origin)?;
+			ensure!(<Roles<T>>::contains_key(role, &who), Error::<T>::RoleNotGranted);
+
+			<Roles<T>>::remove(role, &who);
+			Self::deposit_event(Event::RoleRevoked(role, who));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Whether `who` holds `role`, directly or as an admin.
+		pub fn has_role(role: Role, who: &T::AccountId) -> bool {
+			<Roles<T>>::contains_key(role, who) || <Roles<T>>::contains_key(Role::Admin, who)
+		}
+
+		fn ensure_role(role: Role, who: &T::AccountId) -> DispatchResult {
+			ensure!(Self::has_role(role, who), Error::<T>::MissingRole);
+			Ok(())
+		}
+
+		/// The deposit for storing a value of `who`: `DepositBase` plus `DepositPerByte` for
+		/// every byte of the key and the value.
+		pub fn item_deposit(who: &T::AccountId) -> BalanceOf<T> {
+			let bytes = (who, 0u32).encoded_size() as u32;
+			T::DepositBase::get()
+				.saturating_add(T::DepositPerByte::get().saturating_mul(bytes.into()))
+		}
 	}
 }
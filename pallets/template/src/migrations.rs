@@ -0,0 +1,106 @@
+//! Storage migrations of the template pallet.
+//!
+//! Every migration upgrades the storage from one version to the next. It only runs if the on-chain
+//! storage version of the pallet is the one it migrates from and bumps the version when done, so
+//! [`Migrations`] can be applied to a chain at any older version and is a no-op once the chain is
+//! at [`STORAGE_VERSION`](crate::STORAGE_VERSION).
+
+use crate::{Config, Pallet};
+use frame_support::{
+	pallet_prelude::PhantomData,
+	traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+
+/// All migrations of the pallet, oldest first.
+///
+/// Add this to the migrations the runtime passes to `frame_executive::Executive`. `LegacyOwner` is
+/// the account that gets the value of chains older than version 2, see [`v2`].
+pub type Migrations<T, LegacyOwner> = (v1::MigrateToV1<T>, v2::MigrateToV2<T, LegacyOwner>);
+
+/// Version 1 introduces the storage version of the pallet.
+///
+/// Chains that started before the pallet was versioned are at version 0. The layout of the storage
+/// is unchanged, so existing values are kept as they are.
+pub mod v1 {
+	use super::*;
+
+	/// Migrate the storage of the pallet from version 0 to version 1.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let on_chain = Pallet::<T>::on_chain_storage_version();
+			if on_chain != 0 {
+				log::info!(
+					target: "runtime::template",
+					"Skipping migration to v1, storage is at {:?}",
+					on_chain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			StorageVersion::new(1).put::<Pallet<T>>();
+			log::info!(target: "runtime::template", "Migrated storage to v1");
+			T::DbWeight::get().reads_writes(1, 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() >= 1,
+				"The storage of the template pallet was not migrated to v1"
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Version 2 stores a value per account, with a deposit.
+///
+/// Before, `Something` was a single value. It becomes the value of the `LegacyOwner` account,
+/// stored under the key of the map that the value occupied. The value was stored before deposits,
+/// so it has none, and clearing it returns nothing.
+pub mod v2 {
+	use super::*;
+	use crate::Something;
+	use frame_support::storage::{unhashed, StoragePrefixedMap};
+
+	/// Migrate the storage of the pallet from version 1 to version 2.
+	pub struct MigrateToV2<T, LegacyOwner>(PhantomData<(T, LegacyOwner)>);
+
+	impl<T: Config, LegacyOwner: Get<T::AccountId>> OnRuntimeUpgrade for MigrateToV2<T, LegacyOwner> {
+		fn on_runtime_upgrade() -> Weight {
+			let on_chain = Pallet::<T>::on_chain_storage_version();
+			if on_chain != 1 {
+				log::info!(
+					target: "runtime::template",
+					"Skipping migration to v2, storage is at {:?}",
+					on_chain,
+				);
+				return T::DbWeight::get().reads(1)
+			}
+
+			// The single value was stored at the prefix of the map.
+			if let Some(value) = unhashed::take::<u32>(&Something::<T>::final_prefix()) {
+				Something::<T>::insert(LegacyOwner::get(), value);
+			}
+			StorageVersion::new(2).put::<Pallet<T>>();
+			log::info!(target: "runtime::template", "Migrated storage to v2");
+			T::DbWeight::get().reads_writes(2, 3)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() >= 2,
+				"The storage of the template pallet was not migrated to v2"
+			);
+			frame_support::ensure!(
+				!unhashed::exists(&Something::<T>::final_prefix()),
+				"The single value of v1 was not migrated"
+			);
+			Ok(())
+		}
+	}
+}
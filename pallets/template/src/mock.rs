@@ -1,6 +1,7 @@
 use crate as pallet_template;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, traits::GenesisBuild};
 use frame_system as system;
+use pallet_template::{EnsureAdmin, Role};
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
@@ -18,7 +19,8 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		TemplateModule: pallet_template::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
@@ -45,7 +47,7 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -53,11 +55,53 @@ impl system::Config for Test {
 	type OnSetCode = ();
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const DepositBase: u64 = 10;
+	pub const DepositPerByte: u64 = 1;
+}
+
 impl pallet_template::Config for Test {
 	type Event = Event;
+	type Currency = Balances;
+	type DepositBase = DepositBase;
+	type DepositPerByte = DepositPerByte;
+	type RoleOrigin = system::EnsureOneOf<u64, system::EnsureRoot<u64>, EnsureAdmin<Test>>;
 }
 
+/// The deposit of a value: the base and 12 bytes for an account id and a `u32`.
+pub const DEPOSIT: u64 = 22;
+/// The balance of accounts 0 to 2 at genesis. Account 3 has none.
+pub const BALANCE: u64 = 100;
+/// The admin at genesis. Accounts 1 to 3 are writers and account 4 has no role.
+pub const ADMIN: u64 = 0;
+
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: (0..3).map(|who| (who, BALANCE)).collect() }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	let roles = (1..4)
+		.map(|who| (Role::Writer, who))
+		.chain(std::iter::once((Role::Admin, ADMIN)));
+	pallet_template::GenesisConfig::<Test> { roles: roles.collect() }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+	storage.into()
 }
@@ -1,5 +1,6 @@
-use crate::{mock::*, Error};
+use crate::{mock::*, Error, Event as TemplateEvent, Role};
 use frame_support::{assert_noop, assert_ok};
+use sp_runtime::traits::BadOrigin;
 
 #[test]
 fn it_works_for_default_value() {
@@ -7,7 +8,8 @@ fn it_works_for_default_value() {
 		// Dispatch a signed extrinsic.
 		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
 		// Read pallet storage and assert an expected result.
-		assert_eq!(TemplateModule::something(), Some(42));
+		assert_eq!(TemplateModule::something(1), Some(42));
+		assert_eq!(TemplateModule::something(2), None);
 	});
 }
 
@@ -16,5 +18,363 @@ fn correct_error_for_none_value() {
 	new_test_ext().execute_with(|| {
 		// Ensure the expected error is thrown when no value is present.
 		assert_noop!(TemplateModule::cause_error(Origin::signed(1)), Error::<Test>::NoneValue);
+		assert_ok!(TemplateModule::do_something(Origin::signed(2), 1));
+		assert_noop!(TemplateModule::cause_error(Origin::signed(1)), Error::<Test>::NoneValue);
+	});
+}
+
+#[test]
+fn correct_error_for_overflow() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::do_something(Origin::signed(1), u32::MAX));
+		// Ensure the value is not wrapped around on overflow.
+		assert_noop!(
+			TemplateModule::cause_error(Origin::signed(1)),
+			Error::<Test>::StorageOverflow
+		);
+		assert_eq!(TemplateModule::something(1), Some(u32::MAX));
 	});
 }
+
+#[test]
+fn values_reserve_a_deposit_once() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(TemplateModule::item_deposit(&1), DEPOSIT);
+
+		assert_ok!(TemplateModule::do_something(Origin::signed(1), 1));
+		assert_eq!(Balances::reserved_balance(1), DEPOSIT);
+		assert_eq!(TemplateModule::deposit_of(1), Some(DEPOSIT));
+
+		// Overwriting and incrementing the value reserve nothing more.
+		assert_ok!(TemplateModule::do_something(Origin::signed(1), 2));
+		assert_ok!(TemplateModule::cause_error(Origin::signed(1)));
+		assert_eq!(Balances::reserved_balance(1), DEPOSIT);
+		assert_eq!(Balances::free_balance(1), BALANCE - DEPOSIT);
+	});
+}
+
+#[test]
+fn values_need_the_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::do_something(Origin::signed(3), 1),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+		assert_eq!(TemplateModule::something(3), None);
+	});
+}
+
+#[test]
+fn clearing_returns_the_deposit() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(TemplateModule::clear_something(Origin::signed(1)), Error::<Test>::NoneValue);
+
+		assert_ok!(TemplateModule::do_something(Origin::signed(1), 1));
+		assert_ok!(TemplateModule::clear_something(Origin::signed(1)));
+		System::assert_last_event(TemplateEvent::SomethingCleared(1, DEPOSIT).into());
+		assert_eq!(TemplateModule::something(1), None);
+		assert_eq!(TemplateModule::deposit_of(1), None);
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::free_balance(1), BALANCE);
+	});
+}
+
+#[test]
+fn writing_needs_the_writer_role() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			TemplateModule::do_something(Origin::signed(4), 1),
+			Error::<Test>::MissingRole
+		);
+		assert_noop!(TemplateModule::cause_error(Origin::signed(4)), Error::<Test>::MissingRole);
+
+		// Admins hold every role.
+		assert_ok!(TemplateModule::do_something(Origin::signed(ADMIN), 1));
+		assert!(TemplateModule::has_role(Role::Reader, &ADMIN));
+		assert!(!TemplateModule::has_role(Role::Reader, &1));
+	});
+}
+
+#[test]
+fn roles_are_granted_and_revoked() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(TemplateModule::grant_role(Origin::root(), Role::Writer, 4));
+		System::assert_last_event(TemplateEvent::RoleGranted(Role::Writer, 4).into());
+		assert_noop!(
+			TemplateModule::grant_role(Origin::root(), Role::Writer, 4),
+			Error::<Test>::RoleAlreadyGranted
+		);
+		assert!(TemplateModule::has_role(Role::Writer, &4));
+
+		assert_ok!(TemplateModule::revoke_role(Origin::root(), Role::Writer, 1));
+		System::assert_last_event(TemplateEvent::RoleRevoked(Role::Writer, 1).into());
+		assert_noop!(
+			TemplateModule::revoke_role(Origin::root(), Role::Writer, 1),
+			Error::<Test>::RoleNotGranted
+		);
+		assert_noop!(
+			TemplateModule::do_something(Origin::signed(1), 1),
+			Error::<Test>::MissingRole
+		);
+	});
+}
+
+#[test]
+fn revoked_writers_keep_their_values() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::do_something(Origin::signed(1), 1));
+		assert_ok!(TemplateModule::revoke_role(Origin::root(), Role::Writer, 1));
+
+		assert_eq!(TemplateModule::something(1), Some(1));
+		assert_ok!(TemplateModule::clear_something(Origin::signed(1)));
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn admins_and_root_manage_roles() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(TemplateModule::grant_role(Origin::signed(1), Role::Writer, 4), BadOrigin);
+		assert_noop!(TemplateModule::revoke_role(Origin::signed(1), Role::Writer, 2), BadOrigin);
+
+		assert_ok!(TemplateModule::grant_role(Origin::signed(ADMIN), Role::Admin, 4));
+		assert_ok!(TemplateModule::revoke_role(Origin::signed(4), Role::Admin, ADMIN));
+		assert_noop!(TemplateModule::grant_role(Origin::signed(ADMIN), Role::Writer, 5), BadOrigin);
+	});
+}
+
+mod migrations {
+	use super::*;
+	use crate::{migrations::Migrations, Something, STORAGE_VERSION};
+	use frame_support::{
+		parameter_types,
+		storage::{unhashed, StoragePrefixedMap},
+		traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+	};
+
+	parameter_types! {
+		pub const LegacyOwner: u64 = 5;
+	}
+
+	type AllMigrations = Migrations<Test, LegacyOwner>;
+
+	/// Storage as written by the pallet before it was versioned, a single value.
+	fn v0_state(value: u32) {
+		unhashed::put(&Something::<Test>::final_prefix(), &value);
+		StorageVersion::new(0).put::<TemplateModule>();
+	}
+
+	/// Storage of version 1, which only differs from version 0 in its version.
+	fn v1_state(value: u32) {
+		v0_state(value);
+		StorageVersion::new(1).put::<TemplateModule>();
+	}
+
+	#[test]
+	fn migrates_unversioned_storage() {
+		new_test_ext().execute_with(|| {
+			v0_state(42);
+
+			<AllMigrations as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+			assert_eq!(TemplateModule::on_chain_storage_version(), STORAGE_VERSION);
+			assert_eq!(TemplateModule::something(LegacyOwner::get()), Some(42));
+		});
+	}
+
+	#[test]
+	fn migrates_the_single_value_to_the_legacy_owner() {
+		new_test_ext().execute_with(|| {
+			v1_state(42);
+
+			<AllMigrations as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+			assert_eq!(TemplateModule::on_chain_storage_version(), STORAGE_VERSION);
+			assert!(!unhashed::exists(&Something::<Test>::final_prefix()));
+			assert_eq!(
+				Something::<Test>::iter().collect::<Vec<_>>(),
+				vec![(LegacyOwner::get(), 42)]
+			);
+			// The legacy value has no deposit, clearing it returns nothing.
+			assert_eq!(TemplateModule::deposit_of(LegacyOwner::get()), None);
+			assert_ok!(TemplateModule::clear_something(Origin::signed(LegacyOwner::get())));
+			assert_eq!(Balances::reserved_balance(LegacyOwner::get()), 0);
+		});
+	}
+
+	#[test]
+	fn migrates_empty_storage() {
+		new_test_ext().execute_with(|| {
+			StorageVersion::new(0).put::<TemplateModule>();
+			<AllMigrations as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+			assert_eq!(TemplateModule::on_chain_storage_version(), STORAGE_VERSION);
+			assert_eq!(Something::<Test>::iter().count(), 0);
+		});
+	}
+
+	#[test]
+	fn migrations_are_idempotent() {
+		new_test_ext().execute_with(|| {
+			v0_state(7);
+			<AllMigrations as OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert_ok!(TemplateModule::do_something(Origin::signed(1), 8));
+			<AllMigrations as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+			assert_eq!(TemplateModule::on_chain_storage_version(), STORAGE_VERSION);
+			assert_eq!(TemplateModule::something(LegacyOwner::get()), Some(7));
+			assert_eq!(TemplateModule::something(1), Some(8));
+		});
+	}
+
+	#[test]
+	fn current_storage_version_matches_the_migrations() {
+		new_test_ext().execute_with(|| {
+			StorageVersion::new(0).put::<TemplateModule>();
+			<AllMigrations as OnRuntimeUpgrade>::on_runtime_upgrade();
+			assert_eq!(
+				TemplateModule::on_chain_storage_version(),
+				TemplateModule::current_storage_version()
+			);
+		});
+	}
+}
+
+mod model {
+	use super::*;
+	use frame_support::{dispatch::DispatchError, traits::UnfilteredDispatchable};
+	use proptest::prelude::*;
+	use std::collections::BTreeMap;
+
+	/// An origin the pallet's calls may be dispatched from.
+	#[derive(Debug, Clone)]
+	enum Caller {
+		Signed(u64),
+		Root,
+		None,
+	}
+
+	impl Caller {
+		fn origin(&self) -> Origin {
+			match self {
+				Caller::Signed(who) => Origin::signed(*who),
+				Caller::Root => Origin::root(),
+				Caller::None => Origin::none(),
+			}
+		}
+	}
+
+	#[derive(Debug, Clone)]
+	enum Op {
+		DoSomething(Caller, u32),
+		CauseError(Caller),
+		ClearSomething(Caller),
+	}
+
+	fn caller() -> impl Strategy<Value = Caller> {
+		prop_oneof![
+			8 => (0u64..5).prop_map(Caller::Signed),
+			1 => Just(Caller::Root),
+			1 => Just(Caller::None),
+		]
+	}
+
+	fn value() -> impl Strategy<Value = u32> {
+		// Bias towards the edge of the value range to hit the overflow path.
+		prop_oneof![any::<u32>(), (u32::MAX - 3..=u32::MAX), (0u32..3)]
+	}
+
+	fn op() -> impl Strategy<Value = Op> {
+		prop_oneof![
+			(caller(), value()).prop_map(|(caller, value)| Op::DoSomething(caller, value)),
+			caller().prop_map(Op::CauseError),
+			caller().prop_map(Op::ClearSomething),
+		]
+	}
+
+	/// What `op` should do to `model`, the expected values of `Something`.
+	fn apply(model: &mut BTreeMap<u64, u32>, op: &Op) -> Result<(), DispatchError> {
+		let caller = match op {
+			Op::DoSomething(caller, _) | Op::CauseError(caller) | Op::ClearSomething(caller) =>
+				caller,
+		};
+		let who = match caller {
+			Caller::Signed(who) => *who,
+			_ => return Err(DispatchError::BadOrigin),
+		};
+		// Account 4 has no role, clearing a value needs none.
+		if who == 4 && !matches!(op, Op::ClearSomething(_)) {
+			return Err(Error::<Test>::MissingRole.into())
+		}
+		match (op, model.get(&who).copied()) {
+			// Only accounts with a balance can pay the deposit of a new value.
+			(Op::DoSomething(..), None) if Balances::free_balance(who) < DEPOSIT =>
+				return Err(pallet_balances::Error::<Test>::InsufficientBalance.into()),
+			(Op::DoSomething(_, value), _) => {
+				model.insert(who, *value);
+			},
+			(Op::CauseError(_) | Op::ClearSomething(_), None) =>
+				return Err(Error::<Test>::NoneValue.into()),
+			(Op::CauseError(_), Some(u32::MAX)) =>
+				return Err(Error::<Test>::StorageOverflow.into()),
+			(Op::CauseError(_), Some(old)) => {
+				model.insert(who, old + 1);
+			},
+			(Op::ClearSomething(_), Some(_)) => {
+				model.remove(&who);
+			},
+		}
+		Ok(())
+	}
+
+	fn dispatch(op: &Op) -> Result<(), DispatchError> {
+		let (call, caller) = match op {
+			Op::DoSomething(caller, value) =>
+				(crate::Call::<Test>::do_something { something: *value }, caller),
+			Op::CauseError(caller) => (crate::Call::<Test>::cause_error {}, caller),
+			Op::ClearSomething(caller) => (crate::Call::<Test>::clear_something {}, caller),
+		};
+		call.dispatch_bypass_filter(caller.origin()).map(|_| ()).map_err(|e| e.error)
+	}
+
+	fn template_events() -> usize {
+		System::events()
+			.iter()
+			.filter(|record| matches!(record.event, Event::TemplateModule(_)))
+			.count()
+	}
+
+	proptest! {
+		#[test]
+		fn dispatchables_follow_the_model(ops in proptest::collection::vec(op(), 1..50)) {
+			new_test_ext().execute_with(|| {
+				// Events are only deposited from block 1 onwards.
+				System::set_block_number(1);
+				let mut model = BTreeMap::new();
+
+				for op in &ops {
+					let expected = apply(&mut model, op);
+					let events_before = template_events();
+
+					prop_assert_eq!(dispatch(op), expected);
+					prop_assert_eq!(crate::Something::<Test>::iter().collect::<BTreeMap<_, _>>(), model.clone());
+
+					// Every stored value holds exactly one deposit.
+					for who in 0..5 {
+						let reserved = if model.contains_key(&who) { DEPOSIT } else { 0 };
+						prop_assert_eq!(Balances::reserved_balance(who), reserved);
+					}
+
+					// Exactly the successful `do_something` and `clear_something` calls emit an
+					// event.
+					let emitted = template_events() - events_before;
+					let emits = !matches!(op, Op::CauseError(..)) && expected.is_ok();
+					prop_assert_eq!(emitted, emits as usize);
+				}
+				Ok(())
+			})?;
+		}
+	}
+}
@@ -0,0 +1,167 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # Uniques Bridge Pallet
+//!
+//! Lets contracts mint and transfer unique items of `pallet_uniques`, see the uniques chain
+//! extension of the runtime.
+//!
+//! Minting is limited per class: the owner of a class, or the `ForceOrigin`, designates the
+//! contracts that may mint into it with [`Call::add_minter`]. Contracts transfer the items they
+//! own. Every mint and transfer of a contract emits an event naming the contract, so that the
+//! items of a class can be traced back to the contracts that moved them.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use frame_support::traits::tokens::nonfungibles::{Inspect, Mutate, Transfer};
+
+/// The identifier of a class of items.
+pub type ClassIdOf<T> =
+	<<T as Config>::Uniques as Inspect<<T as frame_system::Config>::AccountId>>::ClassId;
+
+/// The identifier of an item within its class.
+pub type InstanceIdOf<T> =
+	<<T as Config>::Uniques as Inspect<<T as frame_system::Config>::AccountId>>::InstanceId;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The unique items contracts mint and transfer.
+		type Uniques: Mutate<Self::AccountId> + Transfer<Self::AccountId>;
+
+		/// The origin that designates minters of any class.
+		type ForceOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// The contracts that may mint items of a class, by class and contract.
+	#[pallet::storage]
+	pub type Minters<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		ClassIdOf<T>,
+		Blake2_128Concat,
+		T::AccountId,
+		(),
+		OptionQuery,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A contract may mint items of a class. [class, contract]
+		MinterAdded(ClassIdOf<T>, T::AccountId),
+		/// A contract may no longer mint items of a class. [class, contract]
+		MinterRemoved(ClassIdOf<T>, T::AccountId),
+		/// A contract minted an item. [contract, class, instance, owner]
+		Minted(T::AccountId, ClassIdOf<T>, InstanceIdOf<T>, T::AccountId),
+		/// A contract transferred one of its items. [contract, class, instance, dest]
+		Transferred(T::AccountId, ClassIdOf<T>, InstanceIdOf<T>, T::AccountId),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The origin is neither the owner of the class nor the `ForceOrigin`.
+		NoPermission,
+		/// The class does not exist.
+		UnknownClass,
+		/// The contract is not a minter of the class.
+		NotMinter,
+		/// The contract does not own the item.
+		NotOwner,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Let `contract` mint items of `class`.
+		///
+		/// The origin must be the owner of `class` or the `ForceOrigin`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn add_minter(
+			origin: OriginFor<T>,
+			class: ClassIdOf<T>,
+			contract: T::AccountId,
+		) -> DispatchResult {
+			Self::ensure_class_admin(origin, class)?;
+			Minters::<T>::insert(class, &contract, ());
+			Self::deposit_event(Event::MinterAdded(class, contract));
+			Ok(())
+		}
+
+		/// Stop `contract` from minting items of `class`.
+		///
+		/// The origin must be the owner of `class` or the `ForceOrigin`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn remove_minter(
+			origin: OriginFor<T>,
+			class: ClassIdOf<T>,
+			contract: T::AccountId,
+		) -> DispatchResult {
+			Self::ensure_class_admin(origin, class)?;
+			Minters::<T>::take(class, &contract).ok_or(Error::<T>::NotMinter)?;
+			Self::deposit_event(Event::MinterRemoved(class, contract));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn ensure_class_admin(origin: OriginFor<T>, class: ClassIdOf<T>) -> DispatchResult {
+			let origin = match T::ForceOrigin::try_origin(origin) {
+				Ok(_) => return Ok(()),
+				Err(origin) => origin,
+			};
+			let who = ensure_signed(origin)?;
+			let owner = T::Uniques::class_owner(&class).ok_or(Error::<T>::UnknownClass)?;
+			ensure!(owner == who, Error::<T>::NoPermission);
+			Ok(())
+		}
+
+		/// Whether `contract` may mint items of `class`.
+		pub fn is_minter(class: ClassIdOf<T>, contract: &T::AccountId) -> bool {
+			Minters::<T>::contains_key(class, contract)
+		}
+
+		/// Mint `instance` of `class` to `owner` on behalf of `contract`.
+		pub fn mint(
+			contract: T::AccountId,
+			class: ClassIdOf<T>,
+			instance: InstanceIdOf<T>,
+			owner: T::AccountId,
+		) -> DispatchResult {
+			ensure!(Self::is_minter(class, &contract), Error::<T>::NotMinter);
+			T::Uniques::mint_into(&class, &instance, &owner)?;
+			Self::deposit_event(Event::Minted(contract, class, instance, owner));
+			Ok(())
+		}
+
+		/// Transfer `instance` of `class`, which is owned by `contract`, to `dest`.
+		pub fn transfer(
+			contract: T::AccountId,
+			class: ClassIdOf<T>,
+			instance: InstanceIdOf<T>,
+			dest: T::AccountId,
+		) -> DispatchResult {
+			let owner = T::Uniques::owner(&class, &instance);
+			ensure!(owner.as_ref() == Some(&contract), Error::<T>::NotOwner);
+			<T::Uniques as Transfer<T::AccountId>>::transfer(&class, &instance, &dest)?;
+			Self::deposit_event(Event::Transferred(contract, class, instance, dest));
+			Ok(())
+		}
+	}
+}
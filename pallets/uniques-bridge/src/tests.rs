@@ -0,0 +1,72 @@
+use crate::{mock::*, Error, Event as BridgeEvent};
+use frame_support::{assert_noop, assert_ok, traits::tokens::nonfungibles::Inspect};
+use sp_runtime::DispatchError;
+
+#[test]
+fn class_owners_designate_minters() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			UniquesBridge::add_minter(Origin::signed(USER), CLASS, CONTRACT),
+			Error::<Test>::NoPermission
+		);
+		assert_noop!(
+			UniquesBridge::add_minter(Origin::signed(OWNER), CLASS + 1, CONTRACT),
+			Error::<Test>::UnknownClass
+		);
+
+		assert_ok!(UniquesBridge::add_minter(Origin::signed(OWNER), CLASS, CONTRACT));
+		System::assert_last_event(BridgeEvent::MinterAdded(CLASS, CONTRACT).into());
+		assert!(UniquesBridge::is_minter(CLASS, &CONTRACT));
+
+		assert_ok!(UniquesBridge::remove_minter(Origin::root(), CLASS, CONTRACT));
+		assert!(!UniquesBridge::is_minter(CLASS, &CONTRACT));
+		assert_noop!(
+			UniquesBridge::remove_minter(Origin::root(), CLASS, CONTRACT),
+			Error::<Test>::NotMinter
+		);
+	});
+}
+
+#[test]
+fn only_minters_mint() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(UniquesBridge::mint(CONTRACT, CLASS, 7, USER), Error::<Test>::NotMinter);
+
+		assert_ok!(UniquesBridge::add_minter(Origin::signed(OWNER), CLASS, CONTRACT));
+		assert_ok!(UniquesBridge::mint(CONTRACT, CLASS, 7, USER));
+		assert_eq!(Uniques::owner(&CLASS, &7), Some(USER));
+		System::assert_last_event(BridgeEvent::Minted(CONTRACT, CLASS, 7, USER).into());
+
+		assert_noop!(
+			UniquesBridge::mint(CONTRACT, CLASS, 7, USER),
+			pallet_uniques::Error::<Test>::AlreadyExists
+		);
+	});
+}
+
+#[test]
+fn contracts_transfer_their_items() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(UniquesBridge::add_minter(Origin::signed(OWNER), CLASS, CONTRACT));
+		assert_ok!(UniquesBridge::mint(CONTRACT, CLASS, 7, CONTRACT));
+		assert_ok!(UniquesBridge::mint(CONTRACT, CLASS, 8, USER));
+
+		assert_ok!(UniquesBridge::transfer(CONTRACT, CLASS, 7, USER));
+		assert_eq!(Uniques::owner(&CLASS, &7), Some(USER));
+		System::assert_last_event(BridgeEvent::Transferred(CONTRACT, CLASS, 7, USER).into());
+
+		assert_noop!(UniquesBridge::transfer(CONTRACT, CLASS, 8, OWNER), Error::<Test>::NotOwner);
+		assert_noop!(UniquesBridge::transfer(CONTRACT, CLASS, 9, OWNER), Error::<Test>::NotOwner);
+	});
+}
+
+#[test]
+fn minters_are_managed_by_signed_or_force_origins() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			UniquesBridge::add_minter(Origin::none(), CLASS, CONTRACT),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(UniquesBridge::add_minter(Origin::root(), CLASS, CONTRACT));
+	});
+}
@@ -0,0 +1,179 @@
+//! Assets of `pallet_assets` for contracts.
+//!
+//! Transfers and approvals act as the calling contract, i.e. they move the assets of the contract
+//! and let others spend them. The functions, with their SCALE encoded input and output:
+//!
+//! | Function | Input | Output |
+//! |----------|-------|--------|
+//! | [`BALANCE_OF`] | `(AssetId, AccountId)` | `Balance` |
+//! | [`TOTAL_SUPPLY`] | `AssetId` | `Balance` |
+//! | [`ALLOWANCE`] | `(AssetId, AccountId, AccountId)` of the owner and spender | `Balance` |
+//! | [`TRANSFER`] | `(AssetId, AccountId, Balance)` of the recipient | |
+//! | [`APPROVE`] | `(AssetId, AccountId, Balance)` of the spender | |
+//! | [`TRANSFER_FROM`] | `(AssetId, AccountId, AccountId, Balance)` of the owner and recipient | |
+//!
+//! Transfers and approvals report failures as an [`AssetsError`] status code.
+
+use crate::{AccountId, AssetId, Assets, Balance, Origin, Runtime};
+use codec::Encode;
+use frame_support::{
+	dispatch::DispatchResult,
+	storage::{storage_prefix, unhashed, with_transaction},
+	traits::{tokens::fungibles::Inspect, Get, PalletInfoAccess},
+	Blake2_128Concat, StorageHasher,
+};
+use pallet_assets::WeightInfo;
+use pallet_contracts::chain_extension::{
+	Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use sp_runtime::{DispatchError, TokenError, TransactionOutcome};
+
+/// The balance of an account.
+pub const BALANCE_OF: u16 = 0x0000;
+/// The total issuance of an asset.
+pub const TOTAL_SUPPLY: u16 = 0x0001;
+/// The amount a spender may transfer from the account of an owner.
+pub const ALLOWANCE: u16 = 0x0002;
+/// Transfer from the contract to a recipient.
+pub const TRANSFER: u16 = 0x0003;
+/// Let a spender transfer up to an amount from the contract, replacing any previous approval.
+pub const APPROVE: u16 = 0x0004;
+/// Transfer from an owner that approved the contract to a recipient.
+pub const TRANSFER_FROM: u16 = 0x0005;
+
+/// Why a transfer or approval failed, the status code returned to the contract.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum AssetsError {
+	/// The balance of the sender is too low.
+	BalanceLow = 1,
+	/// The asset does not exist.
+	Unknown = 2,
+	/// The contract was not approved to transfer the amount.
+	Unapproved = 3,
+	/// The asset or the account is frozen.
+	Frozen = 4,
+	/// The transfer would leave an account below the minimum balance of the asset.
+	BelowMinimum = 5,
+	/// Any other error of `pallet_assets`.
+	Other = 6,
+}
+
+impl From<DispatchError> for AssetsError {
+	fn from(error: DispatchError) -> Self {
+		use pallet_assets::Error;
+
+		let is = |e: Error<Runtime>| error == e.into();
+		match error {
+			DispatchError::Token(TokenError::NoFunds) => AssetsError::BalanceLow,
+			DispatchError::Token(TokenError::UnknownAsset) => AssetsError::Unknown,
+			DispatchError::Token(TokenError::Frozen) => AssetsError::Frozen,
+			DispatchError::Token(TokenError::BelowMinimum) |
+			DispatchError::Token(TokenError::WouldDie) => AssetsError::BelowMinimum,
+			_ if is(Error::BalanceLow) || is(Error::BalanceZero) => AssetsError::BalanceLow,
+			_ if is(Error::Unknown) => AssetsError::Unknown,
+			_ if is(Error::Unapproved) => AssetsError::Unapproved,
+			_ if is(Error::Frozen) => AssetsError::Frozen,
+			_ if is(Error::WouldDie) => AssetsError::BelowMinimum,
+			_ => AssetsError::Other,
+		}
+	}
+}
+
+/// Call `function` of the assets extension.
+pub fn call<E>(function: u16, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+where
+	E: Ext<T = Runtime>,
+	<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+{
+	let mut env = env.buf_in_buf_out();
+	let read = <Runtime as frame_system::Config>::DbWeight::get().reads(1);
+	match function {
+		BALANCE_OF => {
+			env.charge_weight(read)?;
+			let (asset, owner): (AssetId, AccountId) = env.read_as()?;
+			env.write(&Assets::balance(asset, owner).encode(), false, None)?;
+		},
+		TOTAL_SUPPLY => {
+			env.charge_weight(read)?;
+			let asset: AssetId = env.read_as()?;
+			env.write(&Assets::total_issuance(asset).encode(), false, None)?;
+		},
+		ALLOWANCE => {
+			env.charge_weight(read)?;
+			let (asset, owner, spender): (AssetId, AccountId, AccountId) = env.read_as()?;
+			env.write(&allowance(asset, &owner, &spender).encode(), false, None)?;
+		},
+		TRANSFER => {
+			env.charge_weight(<Runtime as pallet_assets::Config>::WeightInfo::transfer())?;
+			let (asset, to, value): (AssetId, AccountId, Balance) = env.read_as()?;
+			let contract = env.ext().address().clone();
+			return status(Assets::transfer(Origin::signed(contract), asset, to.into(), value))
+		},
+		APPROVE => {
+			env.charge_weight(
+				read + <Runtime as pallet_assets::Config>::WeightInfo::cancel_approval() +
+					<Runtime as pallet_assets::Config>::WeightInfo::approve_transfer(),
+			)?;
+			let (asset, spender, value): (AssetId, AccountId, Balance) = env.read_as()?;
+			let contract = env.ext().address().clone();
+			return status(approve(contract, asset, spender, value))
+		},
+		TRANSFER_FROM => {
+			env.charge_weight(<Runtime as pallet_assets::Config>::WeightInfo::transfer_approved())?;
+			let (asset, from, to, value): (AssetId, AccountId, AccountId, Balance) =
+				env.read_as()?;
+			let contract = env.ext().address().clone();
+			return status(Assets::transfer_approved(
+				Origin::signed(contract),
+				asset,
+				from.into(),
+				to.into(),
+				value,
+			))
+		},
+		_ => return Err(DispatchError::Other("unknown assets extension function")),
+	}
+	Ok(RetVal::Converging(0))
+}
+
+fn status(result: DispatchResult) -> Result<RetVal, DispatchError> {
+	Ok(RetVal::Converging(result.map_or_else(|e| AssetsError::from(e) as u32, |()| 0)))
+}
+
+/// Approvals of `pallet_assets` add up, cancel the previous one to set the allowance to `value`.
+///
+/// Both happen in one storage transaction, a failed approval keeps the previous one.
+fn approve(owner: AccountId, asset: AssetId, spender: AccountId, value: Balance) -> DispatchResult {
+	with_transaction(|| match replace_approval(owner, asset, spender, value) {
+		Ok(()) => TransactionOutcome::Commit(Ok(())),
+		Err(e) => TransactionOutcome::Rollback(Err(e)),
+	})
+}
+
+fn replace_approval(
+	owner: AccountId,
+	asset: AssetId,
+	spender: AccountId,
+	value: Balance,
+) -> DispatchResult {
+	if allowance(asset, &owner, &spender) > 0 {
+		Assets::cancel_approval(Origin::signed(owner.clone()), asset, spender.clone().into())?;
+	}
+	if value > 0 {
+		Assets::approve_transfer(Origin::signed(owner), asset, spender.into(), value)?;
+	}
+	Ok(())
+}
+
+/// The amount `spender` may transfer from `owner`.
+///
+/// `pallet_assets` keeps its approvals private, read the `Approvals` map directly. It is keyed by
+/// the asset, the owner and the spender, and its values start with the approved amount.
+fn allowance(asset: AssetId, owner: &AccountId, spender: &AccountId) -> Balance {
+	let mut key = storage_prefix(Assets::name().as_bytes(), b"Approvals").to_vec();
+	key.extend(Blake2_128Concat::hash(&asset.encode()));
+	key.extend(Blake2_128Concat::hash(&owner.encode()));
+	key.extend(Blake2_128Concat::hash(&spender.encode()));
+	unhashed::get::<(Balance, Balance)>(&key).map_or(0, |(amount, _deposit)| amount)
+}
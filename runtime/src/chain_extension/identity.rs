@@ -0,0 +1,101 @@
+//! Identities of `pallet_identity` for contracts.
+//!
+//! | Function | Input | Output |
+//! |----------|-------|--------|
+//! | [`IDENTITY_OF`] | `AccountId` | `Option<(Vec<u8>, Judgement)>` |
+//!
+//! The output is `None` for accounts without an identity, and otherwise its display name and the
+//! [`Judgement`] of its registrars. Display names that are hashes rather than raw bytes are
+//! returned empty.
+
+use crate::{AccountId, Identity, Runtime};
+use codec::Encode;
+use frame_support::traits::Get;
+use pallet_contracts::chain_extension::{
+	Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use pallet_identity::{Data, Judgement as RegistrarJudgement};
+use sp_runtime::DispatchError;
+use sp_std::vec::Vec;
+
+/// The display name and judgement of an account.
+pub const IDENTITY_OF: u16 = 0x0000;
+
+/// The judgement of the registrars of an identity, encoded as a single byte.
+///
+/// An identity any registrar found erroneous is [`Judgement::Erroneous`]. Otherwise the best
+/// positive judgement counts, then the least bad negative one, see [`Judgement::of`]. Identities
+/// that are waiting for a judgement have none.
+///
+/// The byte values rank the judgements of the registrars of an identity against each other, the
+/// highest counts. That is not an order by quality: [`Judgement::Erroneous`] has the highest value
+/// so it outweighs all others. Contracts check for [`Judgement::Reasonable`] or
+/// [`Judgement::KnownGood`] like [`Judgement::is_positive`] instead of comparing bytes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Encode)]
+#[repr(u8)]
+pub enum Judgement {
+	/// No registrar judged the identity.
+	None = 0,
+	/// A registrar found the identity out of date.
+	OutOfDate = 1,
+	/// A registrar found the identity of low quality.
+	LowQuality = 2,
+	/// A registrar found the identity reasonable.
+	Reasonable = 3,
+	/// A registrar verified the identity.
+	KnownGood = 4,
+	/// A registrar found the identity erroneous.
+	Erroneous = 5,
+}
+
+impl Judgement {
+	/// Whether a registrar vouched for the identity.
+	pub fn is_positive(self) -> bool {
+		matches!(self, Judgement::Reasonable | Judgement::KnownGood)
+	}
+
+	/// The judgement of an identity judged with `judgements`, the one with the highest byte
+	/// value.
+	pub fn of<'a, Balance: 'a>(
+		judgements: impl IntoIterator<Item = &'a RegistrarJudgement<Balance>>,
+	) -> Self {
+		judgements
+			.into_iter()
+			.map(|judgement| match judgement {
+				RegistrarJudgement::Unknown | RegistrarJudgement::FeePaid(_) => Judgement::None,
+				RegistrarJudgement::OutOfDate => Judgement::OutOfDate,
+				RegistrarJudgement::LowQuality => Judgement::LowQuality,
+				RegistrarJudgement::Reasonable => Judgement::Reasonable,
+				RegistrarJudgement::KnownGood => Judgement::KnownGood,
+				RegistrarJudgement::Erroneous => Judgement::Erroneous,
+			})
+			.max_by_key(|judgement| *judgement as u8)
+			.unwrap_or(Judgement::None)
+	}
+}
+
+/// Call `function` of the identity extension.
+pub fn call<E>(function: u16, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+where
+	E: Ext<T = Runtime>,
+	<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+{
+	let mut env = env.buf_in_buf_out();
+	match function {
+		IDENTITY_OF => {
+			env.charge_weight(<Runtime as frame_system::Config>::DbWeight::get().reads(1))?;
+			let who: AccountId = env.read_as()?;
+			let identity = Identity::identity(&who).map(|registration| {
+				let display = match registration.info.display {
+					Data::Raw(display) => display.into_inner(),
+					_ => Vec::new(),
+				};
+				let judgement = Judgement::of(registration.judgements.iter().map(|(_, j)| j));
+				(display, judgement)
+			});
+			env.write(&identity.encode(), false, None)?;
+		},
+		_ => return Err(DispatchError::Other("unknown identity extension function")),
+	}
+	Ok(RetVal::Converging(0))
+}
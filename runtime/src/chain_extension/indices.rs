@@ -0,0 +1,55 @@
+//! Account indices of `pallet_indices` for contracts.
+//!
+//! | Function | Input | Output |
+//! |----------|-------|--------|
+//! | [`LOOKUP_INDEX`] | `AccountIndex` | `Option<AccountId>` |
+//! | [`LOOKUP_ADDRESS`] | `Address` | `Option<AccountId>` |
+//!
+//! [`LOOKUP_ADDRESS`] resolves addresses the way the runtime resolves the accounts of calls, so
+//! contracts can take an `Address` wherever users pass accounts to them. Only account ids and
+//! indices resolve, longer input is not decoded.
+
+use crate::{AccountIndex, Address, Indices, Runtime};
+use codec::Encode;
+use frame_support::traits::Get;
+use pallet_contracts::chain_extension::{
+	Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use sp_runtime::{traits::StaticLookup, DispatchError};
+
+/// The account of an index.
+pub const LOOKUP_INDEX: u16 = 0x0000;
+/// The account of an address, an account id or an index.
+pub const LOOKUP_ADDRESS: u16 = 0x0001;
+
+/// The length of an encoded `Address::Id`, the longest address that resolves.
+const MAX_ADDRESS_LEN: u32 = 1 + 32;
+
+/// Call `function` of the indices extension.
+pub fn call<E>(function: u16, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+where
+	E: Ext<T = Runtime>,
+	<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+{
+	let mut env = env.buf_in_buf_out();
+	env.charge_weight(<Runtime as frame_system::Config>::DbWeight::get().reads(1))?;
+	let account = match function {
+		LOOKUP_INDEX => {
+			let index: AccountIndex = env.read_as()?;
+			Indices::lookup_index(index)
+		},
+		LOOKUP_ADDRESS => {
+			// `Address` has unbounded variants, which never resolve.
+			let len = env.in_len();
+			if len > MAX_ADDRESS_LEN {
+				None
+			} else {
+				let address: Address = env.read_as_unbounded(len)?;
+				Indices::lookup(address).ok()
+			}
+		},
+		_ => return Err(DispatchError::Other("unknown indices extension function")),
+	};
+	env.write(&account.encode(), false, None)?;
+	Ok(RetVal::Converging(0))
+}
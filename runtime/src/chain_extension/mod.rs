@@ -0,0 +1,60 @@
+//! The chain extension of contracts.
+//!
+//! A contract calls into the runtime through `seal_call_chain_extension` with a function id. The
+//! upper 16 bits of the id select one of the extensions below, the lower 16 bits the function of
+//! that extension. The functions are documented with their extension.
+//!
+//! Functions report failures the contract is expected to handle through the status code, zero
+//! for success. Invalid input and unknown functions trap the contract.
+
+pub mod assets;
+pub mod identity;
+pub mod indices;
+pub mod randomness;
+pub mod scheduler;
+pub mod template;
+pub mod uniques;
+
+use crate::Runtime;
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use sp_runtime::DispatchError;
+
+/// Extension id of [`assets`].
+pub const ASSETS: u16 = 0x0001;
+/// Extension id of [`uniques`].
+pub const UNIQUES: u16 = 0x0002;
+/// Extension id of [`randomness`].
+pub const RANDOMNESS: u16 = 0x0003;
+/// Extension id of [`scheduler`].
+pub const SCHEDULER: u16 = 0x0004;
+/// Extension id of [`identity`].
+pub const IDENTITY: u16 = 0x0005;
+/// Extension id of [`indices`].
+pub const INDICES: u16 = 0x0006;
+/// Extension id of [`template`].
+pub const TEMPLATE: u16 = 0x0007;
+
+/// The chain extension of the runtime, dispatching to the extension selected by the function id.
+pub struct RuntimeExtension;
+
+impl ChainExtension<Runtime> for RuntimeExtension {
+	fn call<E>(func_id: u32, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+	where
+		E: Ext<T = Runtime>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		let function = func_id as u16;
+		match (func_id >> 16) as u16 {
+			ASSETS => assets::call(function, env),
+			UNIQUES => uniques::call(function, env),
+			RANDOMNESS => randomness::call(function, env),
+			SCHEDULER => scheduler::call(function, env),
+			IDENTITY => identity::call(function, env),
+			INDICES => indices::call(function, env),
+			TEMPLATE => template::call(function, env),
+			_ => Err(DispatchError::Other("unknown chain extension")),
+		}
+	}
+}
@@ -0,0 +1,51 @@
+//! Randomness of `pallet_randomness_beacon` for contracts.
+//!
+//! | Function | Input | Output |
+//! |----------|-------|--------|
+//! | [`RANDOM`] | the subject, up to [`MAX_SUBJECT_LEN`] raw bytes | `(Hash, BlockNumber)` |
+//!
+//! The output is the random value for the subject and its lookahead, the number of blocks it has
+//! been known for. Contracts should reject values with a lookahead that reaches back before the
+//! outcome they are deciding was fixed. The status code is [`NO_SEED`] until the beacon fixed its
+//! first seed.
+
+use crate::{RandomnessBeacon, Runtime};
+use codec::Encode;
+use frame_support::traits::Get;
+use pallet_contracts::chain_extension::{
+	Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use sp_runtime::DispatchError;
+
+/// A random value for a subject.
+pub const RANDOM: u16 = 0x0000;
+
+/// The longest subject, like the subjects of `seal_random`.
+pub const MAX_SUBJECT_LEN: u32 = 32;
+
+/// The status code if the beacon has no seed yet.
+pub const NO_SEED: u32 = 1;
+
+/// Call `function` of the randomness extension.
+pub fn call<E>(function: u16, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+where
+	E: Ext<T = Runtime>,
+	<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+{
+	let mut env = env.buf_in_buf_out();
+	match function {
+		RANDOM => {
+			env.charge_weight(<Runtime as frame_system::Config>::DbWeight::get().reads(1))?;
+			if env.in_len() > MAX_SUBJECT_LEN {
+				return Err(DispatchError::Other("randomness subject too long"))
+			}
+			let subject = env.read(MAX_SUBJECT_LEN)?;
+			match RandomnessBeacon::random(&subject) {
+				Some(random) => env.write(&random.encode(), false, None)?,
+				None => return Ok(RetVal::Converging(NO_SEED)),
+			}
+		},
+		_ => return Err(DispatchError::Other("unknown randomness extension function")),
+	}
+	Ok(RetVal::Converging(0))
+}
@@ -0,0 +1,128 @@
+//! Calls contracts schedule to themselves with `pallet_contract_scheduler`.
+//!
+//! The functions, with their SCALE encoded input and output:
+//!
+//! | Function | Input | Output |
+//! |----------|-------|--------|
+//! | [`SCHEDULE`] | `(DispatchTime<BlockNumber>, Option<(BlockNumber, u32)>, Weight, [u8; 4], Vec<u8>)` | `ScheduleId` |
+//! | [`CANCEL`] | `ScheduleId` | |
+//!
+//! [`SCHEDULE`] takes the block, or the delay, of the first run, the period and number of runs of
+//! periodic schedules, the gas limit of every run and the selector and input of the message. The
+//! contract is called with itself as caller, so messages meant to be scheduled should check that
+//! the caller is the contract. The fee for the gas limit of all runs and a deposit per byte of the
+//! selector and input are reserved from the balance of the contract. Contracts may cancel a
+//! schedule from one of its runs. Failures are reported as a [`SchedulerError`] status code.
+
+use crate::{BlockNumber, ContractScheduler, Runtime};
+use codec::Encode;
+use frame_support::{
+	dispatch::DispatchResult,
+	traits::{schedule::DispatchTime, Get},
+	weights::Weight,
+};
+use pallet_contract_scheduler::ScheduleId;
+use pallet_contracts::chain_extension::{
+	Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use pallet_scheduler::WeightInfo;
+use sp_runtime::DispatchError;
+use sp_std::vec::Vec;
+
+/// Schedule a call of the contract to itself.
+pub const SCHEDULE: u16 = 0x0000;
+/// Cancel a schedule of the contract.
+pub const CANCEL: u16 = 0x0001;
+
+/// Why scheduling or cancelling failed, the status code returned to the contract.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum SchedulerError {
+	/// The gas limit is higher than the limit of scheduled calls.
+	GasLimitTooHigh = 1,
+	/// The block of the first run is not in the future.
+	InPast = 2,
+	/// The period is zero or there are too many runs.
+	InvalidPeriod = 3,
+	/// The contract can not pay the fees of all runs.
+	BalanceLow = 4,
+	/// The schedule does not exist or has no runs left.
+	UnknownSchedule = 5,
+	/// The schedule belongs to another contract.
+	NotOwner = 6,
+	/// Any other error.
+	Other = 7,
+	/// The selector and input are longer than the limit of scheduled calls.
+	DataTooLong = 8,
+}
+
+impl From<DispatchError> for SchedulerError {
+	fn from(error: DispatchError) -> Self {
+		use pallet_balances::Error as Balances;
+		use pallet_contract_scheduler::Error as Scheduler;
+
+		let is = |e: Scheduler<Runtime>| error == e.into();
+		if is(Scheduler::GasLimitTooHigh) {
+			SchedulerError::GasLimitTooHigh
+		} else if is(Scheduler::InPast) {
+			SchedulerError::InPast
+		} else if is(Scheduler::ZeroPeriod) || is(Scheduler::TooManyRuns) {
+			SchedulerError::InvalidPeriod
+		} else if error == Balances::<Runtime>::InsufficientBalance.into() {
+			SchedulerError::BalanceLow
+		} else if is(Scheduler::UnknownSchedule) {
+			SchedulerError::UnknownSchedule
+		} else if is(Scheduler::NotOwner) {
+			SchedulerError::NotOwner
+		} else if is(Scheduler::DataTooLong) {
+			SchedulerError::DataTooLong
+		} else {
+			SchedulerError::Other
+		}
+	}
+}
+
+/// Call `function` of the scheduler extension.
+pub fn call<E>(function: u16, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+where
+	E: Ext<T = Runtime>,
+	<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+{
+	let mut env = env.buf_in_buf_out();
+	let reads_writes = <Runtime as frame_system::Config>::DbWeight::get().reads_writes(3, 4);
+	let scheduled = <Runtime as pallet_scheduler::Config>::MaxScheduledPerBlock::get();
+	match function {
+		SCHEDULE => {
+			let weight =
+				<Runtime as pallet_scheduler::Config>::WeightInfo::schedule_named(scheduled);
+			env.charge_weight(reads_writes + weight)?;
+			let len = env.in_len();
+			let (when, periodic, gas_limit, selector, input): (
+				DispatchTime<BlockNumber>,
+				Option<(BlockNumber, u32)>,
+				Weight,
+				[u8; 4],
+				Vec<u8>,
+			) = env.read_as_unbounded(len)?;
+			let contract = env.ext().address().clone();
+			let data = selector.iter().chain(&input).copied().collect();
+			match ContractScheduler::schedule(contract, when, periodic, gas_limit, data) {
+				Ok(id) => env.write(&id.encode(), false, None)?,
+				Err(error) => return Ok(RetVal::Converging(SchedulerError::from(error) as u32)),
+			}
+		},
+		CANCEL => {
+			let weight = <Runtime as pallet_scheduler::Config>::WeightInfo::cancel_named(scheduled);
+			env.charge_weight(reads_writes + weight)?;
+			let id: ScheduleId = env.read_as()?;
+			let contract = env.ext().address().clone();
+			return status(ContractScheduler::cancel(contract, id))
+		},
+		_ => return Err(DispatchError::Other("unknown scheduler extension function")),
+	}
+	Ok(RetVal::Converging(0))
+}
+
+fn status(result: DispatchResult) -> Result<RetVal, DispatchError> {
+	Ok(RetVal::Converging(result.map_or_else(|e| SchedulerError::from(e) as u32, |()| 0)))
+}
@@ -0,0 +1,38 @@
+//! Roles of `pallet_template` for contracts.
+//!
+//! | Function | Input | Output |
+//! |----------|-------|--------|
+//! | [`HAS_ROLE`] | `(Role, AccountId)` | `bool` |
+//!
+//! Admins hold every role. Contracts gate their messages on the roles of their callers, or check
+//! the roles granted to themselves.
+
+use crate::{AccountId, Runtime, TemplateModule};
+use codec::Encode;
+use frame_support::traits::Get;
+use pallet_contracts::chain_extension::{
+	Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use pallet_template::Role;
+use sp_runtime::DispatchError;
+
+/// Whether an account holds a role.
+pub const HAS_ROLE: u16 = 0x0000;
+
+/// Call `function` of the template extension.
+pub fn call<E>(function: u16, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+where
+	E: Ext<T = Runtime>,
+	<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+{
+	let mut env = env.buf_in_buf_out();
+	match function {
+		HAS_ROLE => {
+			env.charge_weight(<Runtime as frame_system::Config>::DbWeight::get().reads(2))?;
+			let (role, who): (Role, AccountId) = env.read_as()?;
+			env.write(&TemplateModule::has_role(role, &who).encode(), false, None)?;
+		},
+		_ => return Err(DispatchError::Other("unknown template extension function")),
+	}
+	Ok(RetVal::Converging(0))
+}
@@ -0,0 +1,123 @@
+//! Unique items of `pallet_uniques` for contracts.
+//!
+//! Contracts mint items of the classes they were made minter of in `pallet_uniques_bridge` and
+//! transfer the items they own. The functions, with their SCALE encoded input and output:
+//!
+//! | Function | Input | Output |
+//! |----------|-------|--------|
+//! | [`MINT`] | `(ClassId, InstanceId, AccountId)` of the owner | |
+//! | [`TRANSFER`] | `(ClassId, InstanceId, AccountId)` of the recipient | |
+//! | [`OWNER_OF`] | `(ClassId, InstanceId)` | `Option<AccountId>` |
+//! | [`ATTRIBUTE`] | `(ClassId, Option<InstanceId>, Vec<u8>)` with the key | `Option<Vec<u8>>` |
+//!
+//! [`ATTRIBUTE`] reads an attribute of the class itself if no instance is given. Mints and
+//! transfers report failures as an [`UniquesError`] status code.
+
+use crate::{AccountId, ClassId, InstanceId, Runtime, Uniques, UniquesBridge};
+use codec::Encode;
+use frame_support::{
+	dispatch::DispatchResult,
+	traits::{tokens::nonfungibles::Inspect, Get},
+};
+use pallet_contracts::chain_extension::{
+	Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use pallet_uniques::WeightInfo;
+use sp_runtime::DispatchError;
+use sp_std::vec::Vec;
+
+/// Mint an item of a class the contract is a minter of.
+pub const MINT: u16 = 0x0000;
+/// Transfer an item owned by the contract.
+pub const TRANSFER: u16 = 0x0001;
+/// The owner of an item.
+pub const OWNER_OF: u16 = 0x0002;
+/// An attribute of an item or a class.
+pub const ATTRIBUTE: u16 = 0x0003;
+
+/// Why a mint or transfer failed, the status code returned to the contract.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u32)]
+pub enum UniquesError {
+	/// The contract is not a minter of the class.
+	NotMinter = 1,
+	/// The contract does not own the item.
+	NotOwner = 2,
+	/// The item already exists.
+	AlreadyExists = 3,
+	/// The class does not exist.
+	UnknownClass = 4,
+	/// The class or the item is frozen.
+	Frozen = 5,
+	/// Any other error of `pallet_uniques`.
+	Other = 6,
+}
+
+impl From<DispatchError> for UniquesError {
+	fn from(error: DispatchError) -> Self {
+		use pallet_uniques::Error as Uniques;
+		use pallet_uniques_bridge::Error as Bridge;
+
+		let is_uniques = |e: Uniques<Runtime>| error == e.into();
+		let is_bridge = |e: Bridge<Runtime>| error == e.into();
+		if is_bridge(Bridge::NotMinter) {
+			UniquesError::NotMinter
+		} else if is_bridge(Bridge::NotOwner) {
+			UniquesError::NotOwner
+		} else if is_uniques(Uniques::AlreadyExists) {
+			UniquesError::AlreadyExists
+		} else if is_uniques(Uniques::Unknown) {
+			UniquesError::UnknownClass
+		} else if is_uniques(Uniques::Frozen) {
+			UniquesError::Frozen
+		} else {
+			UniquesError::Other
+		}
+	}
+}
+
+/// Call `function` of the uniques extension.
+pub fn call<E>(function: u16, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+where
+	E: Ext<T = Runtime>,
+	<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+{
+	let mut env = env.buf_in_buf_out();
+	let read = <Runtime as frame_system::Config>::DbWeight::get().reads(1);
+	match function {
+		MINT => {
+			env.charge_weight(read + <Runtime as pallet_uniques::Config>::WeightInfo::mint())?;
+			let (class, instance, owner): (ClassId, InstanceId, AccountId) = env.read_as()?;
+			let contract = env.ext().address().clone();
+			return status(UniquesBridge::mint(contract, class, instance, owner))
+		},
+		TRANSFER => {
+			env.charge_weight(read + <Runtime as pallet_uniques::Config>::WeightInfo::transfer())?;
+			let (class, instance, dest): (ClassId, InstanceId, AccountId) = env.read_as()?;
+			let contract = env.ext().address().clone();
+			return status(UniquesBridge::transfer(contract, class, instance, dest))
+		},
+		OWNER_OF => {
+			env.charge_weight(read)?;
+			let (class, instance): (ClassId, InstanceId) = env.read_as()?;
+			env.write(&Uniques::owner(&class, &instance).encode(), false, None)?;
+		},
+		ATTRIBUTE => {
+			env.charge_weight(read)?;
+			let len = env.in_len();
+			let (class, instance, key): (ClassId, Option<InstanceId>, Vec<u8>) =
+				env.read_as_unbounded(len)?;
+			let value = match instance {
+				Some(instance) => Uniques::attribute(&class, &instance, &key),
+				None => Uniques::class_attribute(&class, &key),
+			};
+			env.write(&value.encode(), false, None)?;
+		},
+		_ => return Err(DispatchError::Other("unknown uniques extension function")),
+	}
+	Ok(RetVal::Converging(0))
+}
+
+fn status(result: DispatchResult) -> Result<RetVal, DispatchError> {
+	Ok(RetVal::Converging(result.map_or_else(|e| UniquesError::from(e) as u32, |()| 0)))
+}
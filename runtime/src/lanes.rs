@@ -0,0 +1,170 @@
+//! Priority lanes of the transaction pool.
+//!
+//! The pool orders transactions by the priority their validation reports. [`CheckLane`] sorts
+//! every transaction into a [`Lane`] and adds the base priority of its lane, so that the lanes
+//! are ordered before the fees and tips order the transactions within a lane. It also limits how
+//! long a transaction stays valid in the pool, e.g. stale offchain updates are worthless.
+//!
+//! The priority from fees and tips comes from the extension charging them, which [`CheckLane`]
+//! wraps to cap that priority below the next lane.
+
+use crate::Call;
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, PostDispatchInfoOf, SignedExtension, SignedExtensionMetadata},
+	transaction_validity::{
+		TransactionLongevity, TransactionPriority, TransactionValidity, TransactionValidityError,
+		ValidTransaction,
+	},
+	DispatchResult,
+};
+use sp_std::vec::Vec;
+
+/// The distance between the base priorities of two lanes. The priority derived from fees and
+/// tips is capped below it.
+const LANE_PRIORITY_STEP: TransactionPriority = 1 << 60;
+
+/// A class of transactions sharing a base priority and a longevity.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, TypeInfo)]
+pub enum Lane {
+	/// Everything else.
+	Default,
+	/// Calls of `pallet_template`.
+	Template,
+	/// Calls of `pallet_contracts`.
+	Contract,
+	/// Oracle and offchain worker updates, i.e. unsigned transactions.
+	Oracle,
+}
+
+impl Lane {
+	/// All lanes, from the lowest to the highest priority.
+	pub const ALL: [Lane; 4] = [Lane::Default, Lane::Template, Lane::Contract, Lane::Oracle];
+
+	/// The lane of a signed transaction dispatching `call`.
+	pub fn of(call: &Call) -> Lane {
+		match call {
+			Call::TemplateModule(..) => Lane::Template,
+			Call::Contracts(..) => Lane::Contract,
+			_ => Lane::Default,
+		}
+	}
+
+	/// The lane of an unsigned transaction, which is submitted by offchain workers.
+	pub fn of_unsigned(_call: &Call) -> Lane {
+		Lane::Oracle
+	}
+
+	/// The name of the lane, as used on the command line of the node.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Lane::Default => "default",
+			Lane::Template => "template",
+			Lane::Contract => "contract",
+			Lane::Oracle => "oracle",
+		}
+	}
+
+	/// The priority added to every transaction of the lane.
+	pub fn priority(&self) -> TransactionPriority {
+		LANE_PRIORITY_STEP * *self as TransactionPriority
+	}
+
+	/// The number of blocks a transaction of the lane stays valid in the pool.
+	pub fn longevity(&self) -> TransactionLongevity {
+		match self {
+			Lane::Default => TransactionLongevity::max_value(),
+			Lane::Template | Lane::Contract => 64,
+			Lane::Oracle => 8,
+		}
+	}
+
+	/// Put a transaction validated as `validity` into the lane.
+	fn validity(&self, validity: ValidTransaction) -> ValidTransaction {
+		ValidTransaction {
+			priority: self.priority() + validity.priority.min(LANE_PRIORITY_STEP - 1),
+			longevity: validity.longevity.min(self.longevity()),
+			..validity
+		}
+	}
+}
+
+/// Sets the priority and longevity of transactions according to their [`Lane`].
+///
+/// Wraps the extension `S` charging the fees, whose priority is capped to stay within the lane.
+/// It encodes like `S` followed by an extension without data, so extrinsics are the same as with
+/// the two extensions side by side.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+pub struct CheckLane<S>(pub S);
+
+impl<S> SignedExtension for CheckLane<S>
+where
+	S: SignedExtension<AccountId = crate::AccountId, Call = Call>,
+{
+	const IDENTIFIER: &'static str = "CheckLane";
+	type AccountId = crate::AccountId;
+	type Call = Call;
+	type AdditionalSigned = S::AdditionalSigned;
+	type Pre = S::Pre;
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		self.0.additional_signed()
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> TransactionValidity {
+		Ok(Lane::of(call).validity(self.0.validate(who, call, info, len)?))
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.0.pre_dispatch(who, call, info, len)
+	}
+
+	fn validate_unsigned(
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> TransactionValidity {
+		Ok(Lane::of_unsigned(call).validity(S::validate_unsigned(call, info, len)?))
+	}
+
+	fn pre_dispatch_unsigned(
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<(), TransactionValidityError> {
+		S::pre_dispatch_unsigned(call, info, len)
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<Call>,
+		post_info: &PostDispatchInfoOf<Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		S::post_dispatch(pre, info, post_info, len, result)
+	}
+
+	fn metadata() -> Vec<SignedExtensionMetadata> {
+		let mut metadata = S::metadata();
+		metadata.push(SignedExtensionMetadata {
+			identifier: Self::IDENTIFIER,
+			ty: scale_info::meta_type::<()>(),
+			additional_signed: scale_info::meta_type::<()>(),
+		});
+		metadata
+	}
+}
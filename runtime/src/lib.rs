@@ -6,6 +6,9 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+pub mod chain_extension;
+pub mod lanes;
+
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
@@ -14,9 +17,9 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
+	traits::{BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
 	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	ApplyExtrinsicResult, DispatchError, MultiSignature,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -53,6 +56,9 @@ pub type Signature = MultiSignature;
 /// to the public key of our transaction signing scheme.
 pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
 
+/// A short numeric alias of an account, see `pallet_indices`.
+pub type AccountIndex = u32;
+
 /// Balance of an account.
 pub type Balance = u128;
 
@@ -62,6 +68,15 @@ pub type Index = u32;
 /// A hash of some data used by the chain.
 pub type Hash = sp_core::H256;
 
+/// Identifier of an asset.
+pub type AssetId = u32;
+
+/// Identifier of a class of unique items.
+pub type ClassId = u32;
+
+/// Identifier of a unique item within its class.
+pub type InstanceId = u32;
+
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
 /// of data like extrinsics, allowing for them to continue syncing the network through upgrades
@@ -96,12 +111,14 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// The version of the runtime specification. A full node will not attempt to use its native
 	//   runtime in substitute for the on-chain Wasm runtime unless all of `spec_name`,
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
-	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
+	// This value started at 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	// Increment it with every change of the storage or the logic of the runtime, `set_code`
+	//   rejects runtimes that don't and their migrations never run.
+	spec_version: 122,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 4,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -143,8 +160,9 @@ parameter_types! {
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
-	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = frame_support::traits::Everything;
+	/// The basic call filter to use in dispatchable. Rejects the calls of pallets with storage that
+	/// is being migrated by `LazyMigration`.
+	type BaseCallFilter = pallet_lazy_migration::MigrationCallFilter<Runtime>;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = BlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -153,8 +171,9 @@ impl frame_system::Config for Runtime {
 	type AccountId = AccountId;
 	/// The aggregated dispatch type that is available for extrinsics.
 	type Call = Call;
-	/// The lookup mechanism to get account ID from whatever is passed in dispatchers.
-	type Lookup = AccountIdLookup<AccountId, ()>;
+	/// The lookup mechanism to get account ID from whatever is passed in dispatchers, account
+	/// ids or account indices.
+	type Lookup = Indices;
 	/// The index type for storing how many extrinsics an account has signed.
 	type Index = Index;
 	/// The index type for blocks.
@@ -205,6 +224,34 @@ impl pallet_aura::Config for Runtime {
 	type MaxAuthorities = MaxAuthorities;
 }
 
+/// The accounts of the Aura authorities, whose keys are also account keys.
+pub struct AuraValidators;
+
+impl frame_support::traits::Contains<AccountId> for AuraValidators {
+	fn contains(who: &AccountId) -> bool {
+		let who: &[u8] = who.as_ref();
+		Aura::authorities()
+			.iter()
+			.any(|authority| AsRef::<[u8]>::as_ref(authority) == who)
+	}
+}
+
+parameter_types! {
+	pub const BeaconSessionLength: BlockNumber = HOURS;
+	/// A sixteenth of the balance of the dev validators, withholding a secret has to hurt.
+	pub const CommitDeposit: Balance = 1 << 56;
+}
+
+impl pallet_randomness_beacon::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type Validators = AuraValidators;
+	/// Slashed deposits are burned.
+	type Slash = ();
+	type SessionLength = BeaconSessionLength;
+	type CommitDeposit = CommitDeposit;
+}
+
 impl pallet_grandpa::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
@@ -256,25 +303,269 @@ impl pallet_balances::Config for Runtime {
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 }
 
+parameter_types! {
+	pub IndexDeposit: Balance = 100 * ExistentialDeposit::get();
+}
+
+impl pallet_indices::Config for Runtime {
+	type AccountIndex = AccountIndex;
+	type Currency = Balances;
+	type Deposit = IndexDeposit;
+	type Event = Event;
+	type WeightInfo = pallet_indices::weights::SubstrateWeight<Runtime>;
+}
+
 parameter_types! {
 	pub const TransactionByteFee: Balance = 1;
 }
 
 impl pallet_transaction_payment::Config for Runtime {
-	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
+	/// Fees are paid in the fee asset of the payer, or in `Balances` if it has none.
+	type OnChargeTransaction =
+		pallet_asset_fees::AssetsOrNative<Runtime, CurrencyAdapter<Balances, ()>>;
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = IdentityFee<Balance>;
 	type FeeMultiplierUpdate = ();
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 50_000;
+	pub const AssetMetadataDepositBase: Balance = 5_000;
+	pub const AssetMetadataDepositPerByte: Balance = 500;
+	pub const AssetApprovalDeposit: Balance = 500;
+	pub const AssetStringLimit: u32 = 50;
+}
+
+impl pallet_assets::Config for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = AssetId;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = AssetMetadataDepositBase;
+	type MetadataDepositPerByte = AssetMetadataDepositPerByte;
+	type ApprovalDeposit = AssetApprovalDeposit;
+	type StringLimit = AssetStringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const ClassDeposit: Balance = 50_000;
+	pub const InstanceDeposit: Balance = 500;
+	pub const UniquesMetadataDepositBase: Balance = 5_000;
+	pub const AttributeDepositBase: Balance = 5_000;
+	pub const UniquesDepositPerByte: Balance = 50;
+	pub const UniquesStringLimit: u32 = 128;
+	pub const KeyLimit: u32 = 32;
+	pub const ValueLimit: u32 = 256;
+}
+
+impl pallet_uniques::Config for Runtime {
+	type Event = Event;
+	type ClassId = ClassId;
+	type InstanceId = InstanceId;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type ClassDeposit = ClassDeposit;
+	type InstanceDeposit = InstanceDeposit;
+	type MetadataDepositBase = UniquesMetadataDepositBase;
+	type AttributeDepositBase = AttributeDepositBase;
+	type DepositPerByte = UniquesDepositPerByte;
+	type StringLimit = UniquesStringLimit;
+	type KeyLimit = KeyLimit;
+	type ValueLimit = ValueLimit;
+	type WeightInfo = pallet_uniques::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_uniques_bridge::Config for Runtime {
+	type Event = Event;
+	type Uniques = Uniques;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+}
+
+/// Whether `bare_call` and `bare_instantiate` of the contracts runtime API return debug messages.
+const CONTRACTS_DEBUG_OUTPUT: bool = true;
+
+parameter_types! {
+	pub ContractDeposit: Balance = 100 * ExistentialDeposit::get();
+	/// Up to a tenth of a block removes the storage of terminated contracts.
+	pub DeletionWeightLimit: Weight = Perbill::from_percent(10) * BlockWeights::get().max_block;
+	pub DeletionQueueDepth: u32 = 128;
+	pub Schedule: pallet_contracts::Schedule<Runtime> = Default::default();
+}
+
+impl pallet_contracts::Config for Runtime {
+	type Time = Timestamp;
+	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
+	type Event = Event;
+	type Call = Call;
+	/// Contracts reach the runtime through the chain extension, not by dispatching calls.
+	type CallFilter = frame_support::traits::Nothing;
+	type ContractDeposit = ContractDeposit;
+	type CallStack = [pallet_contracts::Frame<Self>; 31];
+	type WeightPrice = pallet_transaction_payment::Pallet<Self>;
+	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Self>;
+	type ChainExtension = chain_extension::RuntimeExtension;
+	type DeletionQueueDepth = DeletionQueueDepth;
+	type DeletionWeightLimit = DeletionWeightLimit;
+	type Schedule = Schedule;
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * BlockWeights::get().max_block;
+	pub const MaxScheduledPerBlock: u32 = 50;
+}
+
+impl pallet_scheduler::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type OriginPrivilegeCmp = frame_support::traits::EqualPrivilegeOnly;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+}
+
+/// Calls contracts with themselves as caller, for the calls they scheduled.
+pub struct ScheduledContractCalls;
+
+impl pallet_contract_scheduler::CallContract<AccountId> for ScheduledContractCalls {
+	fn call(
+		contract: &AccountId,
+		gas_limit: Weight,
+		data: Vec<u8>,
+	) -> (Weight, sp_runtime::DispatchResult) {
+		let origin = contract.clone();
+		let result = Contracts::bare_call(origin, contract.clone(), 0, gas_limit, data, false);
+		let outcome = match result.result {
+			Ok(value) if value.did_revert() => Err(DispatchError::Other("contract reverted")),
+			Ok(_) => Ok(()),
+			Err(error) => Err(error),
+		};
+		(result.gas_consumed, outcome)
+	}
+}
+
+parameter_types! {
+	/// A scheduled call of a contract uses up to a tenth of a block.
+	pub ScheduledCallGasLimit: Weight = Perbill::from_percent(10) * BlockWeights::get().max_block;
+	pub const MaxScheduledRuns: u32 = 100;
+	pub const MaxScheduledDataLen: u32 = 256;
+	pub const ScheduledDataDepositPerByte: Balance = 10;
+}
+
+impl pallet_contract_scheduler::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type WeightToFee = IdentityFee<Balance>;
+	/// Fees of scheduled calls are burned, like transaction fees.
+	type Fees = ();
+	type Contracts = ScheduledContractCalls;
+	type ScheduledCall = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type MaxGasLimit = ScheduledCallGasLimit;
+	type MaxRuns = MaxScheduledRuns;
+	type MaxDataLen = MaxScheduledDataLen;
+	type DataDepositPerByte = ScheduledDataDepositPerByte;
+}
+
+parameter_types! {
+	pub const BasicDeposit: Balance = 10_000;
+	pub const FieldDeposit: Balance = 2_500;
+	pub const SubAccountDeposit: Balance = 2_000;
+	pub const MaxSubAccounts: u32 = 100;
+	pub const MaxAdditionalFields: u32 = 100;
+	pub const MaxRegistrars: u32 = 20;
+}
+
+impl pallet_identity::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BasicDeposit = BasicDeposit;
+	type FieldDeposit = FieldDeposit;
+	type SubAccountDeposit = SubAccountDeposit;
+	type MaxSubAccounts = MaxSubAccounts;
+	type MaxAdditionalFields = MaxAdditionalFields;
+	type MaxRegistrars = MaxRegistrars;
+	/// Deposits of identities slashed by registrars are burned.
+	type Slashed = ();
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type RegistrarOrigin = frame_system::EnsureRoot<AccountId>;
+	type WeightInfo = pallet_identity::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_identity_registrars::Config for Runtime {}
+
+impl pallet_asset_fees::Config for Runtime {
+	type Event = Event;
+	type Assets = Assets;
+	type RateOrigin = frame_system::EnsureRoot<AccountId>;
+}
+
+/// The calls whose fees can be paid by a sponsor.
+pub struct SponsorableCalls;
+
+impl frame_support::traits::Contains<Call> for SponsorableCalls {
+	fn contains(call: &Call) -> bool {
+		matches!(call, Call::TemplateModule(..) | Call::Contracts(..))
+	}
+}
+
+parameter_types! {
+	pub const MaxSponsoredCalls: u32 = 16;
+	pub const SponsorshipDepositBase: Balance = 5_000;
+	pub const SponsorshipDepositPerByte: Balance = 10;
+}
+
+impl pallet_sponsorship::Config for Runtime {
+	type Event = Event;
+	type SponsorableCalls = SponsorableCalls;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxCalls = MaxSponsoredCalls;
+	type Currency = Balances;
+	type DepositBase = SponsorshipDepositBase;
+	type DepositPerByte = SponsorshipDepositPerByte;
+}
+
 impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 }
 
+parameter_types! {
+	pub const TemplateDepositBase: Balance = 5_000;
+	pub const TemplateDepositPerByte: Balance = 10;
+	/// The account that owns the value the template pallet stored before values were per account.
+	pub TemplateLegacyOwner: AccountId = Sudo::key();
+}
+
 /// Configure the pallet-template in pallets/template.
 impl pallet_template::Config for Runtime {
 	type Event = Event;
+	type Currency = Balances;
+	type DepositBase = TemplateDepositBase;
+	type DepositPerByte = TemplateDepositPerByte;
+	/// Roles are managed by sudo and by the accounts with the admin role.
+	type RoleOrigin = frame_system::EnsureOneOf<
+		AccountId,
+		frame_system::EnsureRoot<AccountId>,
+		pallet_template::EnsureAdmin<Runtime>,
+	>;
+}
+
+impl pallet_lazy_migration::Config for Runtime {
+	type Event = Event;
+	/// No migration is in flight. Set this to a `SteppedMigration` for storage too large to
+	/// migrate in a single block.
+	type Migration = ();
+	type ControlOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -293,12 +584,25 @@ construct_runtime!(
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the pallet-template in the runtime.
-		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
+		TemplateModule: pallet_template::{Pallet, Call, Storage, Config<T>, Event<T>},
+		LazyMigration: pallet_lazy_migration::{Pallet, Call, Storage, Event<T>},
+		Sponsorship: pallet_sponsorship::{Pallet, Call, Storage, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
+		AssetFees: pallet_asset_fees::{Pallet, Call, Storage, Event<T>},
+		Contracts: pallet_contracts::{Pallet, Call, Storage, Event<T>},
+		Uniques: pallet_uniques::{Pallet, Call, Storage, Event<T>},
+		UniquesBridge: pallet_uniques_bridge::{Pallet, Call, Storage, Event<T>},
+		RandomnessBeacon: pallet_randomness_beacon::{Pallet, Call, Storage, Event<T>},
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
+		ContractScheduler: pallet_contract_scheduler::{Pallet, Call, Storage, Event<T>},
+		Identity: pallet_identity::{Pallet, Call, Storage, Event<T>},
+		IdentityRegistrars: pallet_identity_registrars::{Pallet, Config<T>},
+		Indices: pallet_indices::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
 /// The address format for describing accounts.
-pub type Address = sp_runtime::MultiAddress<AccountId, ()>;
+pub type Address = sp_runtime::MultiAddress<AccountId, AccountIndex>;
 /// Block header type as expected by this runtime.
 pub type Header = generic::Header<BlockNumber, BlakeTwo256>;
 /// Block type as expected by this runtime.
@@ -311,10 +615,13 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	lanes::CheckLane<pallet_sponsorship::ChargeSponsored<Runtime>>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+/// The storage migrations to run on runtime upgrades, before the `on_runtime_upgrade` hooks of the
+/// pallets. Migrations that ran on all chains can be removed.
+pub type Migrations = (pallet_template::migrations::Migrations<Runtime, TemplateLegacyOwner>,);
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -322,6 +629,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPallets,
+	Migrations,
 >;
 
 impl_runtime_apis! {
@@ -455,6 +763,90 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_asset_fees_runtime_api::AssetFeesApi<Block, Balance, AssetId> for Runtime {
+		fn query_info_in_asset(
+			uxt: <Block as BlockT>::Extrinsic,
+			len: u32,
+			asset: AssetId,
+		) -> Option<pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance>> {
+			let info = TransactionPayment::query_info(uxt, len);
+			AssetFees::quote(asset, info.partial_fee)
+				.map(|partial_fee| pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo {
+					partial_fee,
+					..info
+				})
+		}
+
+		fn quote(asset: AssetId, fee: Balance) -> Option<Balance> {
+			AssetFees::quote(asset, fee)
+		}
+	}
+
+	impl pallet_contracts_rpc_runtime_api::ContractsApi<Block, AccountId, Balance, BlockNumber, Hash>
+		for Runtime
+	{
+		fn call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			input_data: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractExecResult {
+			Contracts::bare_call(origin, dest, value, gas_limit, input_data, CONTRACTS_DEBUG_OUTPUT)
+		}
+
+		fn instantiate(
+			origin: AccountId,
+			endowment: Balance,
+			gas_limit: u64,
+			code: pallet_contracts_primitives::Code<Hash>,
+			data: Vec<u8>,
+			salt: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId> {
+			Contracts::bare_instantiate(
+				origin,
+				endowment,
+				gas_limit,
+				code,
+				data,
+				salt,
+				CONTRACTS_DEBUG_OUTPUT,
+			)
+		}
+
+		fn get_storage(
+			address: AccountId,
+			key: [u8; 32],
+		) -> pallet_contracts_primitives::GetStorageResult {
+			Contracts::get_storage(address, key)
+		}
+	}
+
+	impl pallet_lazy_migration_runtime_api::LazyMigrationApi<Block, BlockNumber> for Runtime {
+		fn progress() -> Option<pallet_lazy_migration::Progress> {
+			LazyMigration::progress()
+		}
+
+		fn outcome(id: Vec<u8>) -> Option<pallet_lazy_migration::Outcome<BlockNumber>> {
+			LazyMigration::outcome(id)
+		}
+	}
+
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade() -> (Weight, Weight) {
+			// NOTE: intentional unwrap: we don't want to propagate the error backwards, and want to
+			// have a backtrace here. If any of the pre/post migration checks fail, we shall stop
+			// right here and right now.
+			let weight = Executive::try_runtime_upgrade().unwrap();
+			(weight, BlockWeights::get().max_block)
+		}
+
+		fn execute_block_no_check(block: Block) -> Weight {
+			Executive::execute_block_no_check(block)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
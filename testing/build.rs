@@ -0,0 +1,35 @@
+//! Build the contracts of the repository for the end-to-end tests.
+//!
+//! The contracts are not part of the workspace, they are built with `cargo contract` into the
+//! output directory of this crate. The path of a built contract is passed to the crate in an
+//! environment variable, `PSP22_ASSET_WASM` for `contracts/psp22-asset`.
+
+use std::{env, path::PathBuf, process::Command};
+
+fn main() {
+	let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+	let contract = manifest_dir.join("../contracts/psp22-asset");
+	let target_dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join("contracts");
+
+	println!("cargo:rerun-if-changed={}", contract.join("lib.rs").display());
+	println!("cargo:rerun-if-changed={}", contract.join("Cargo.toml").display());
+
+	// The flags and toolchain of this build do not apply to the contract.
+	let status = Command::new("cargo")
+		.args(&["+nightly", "contract", "build", "--manifest-path"])
+		.arg(contract.join("Cargo.toml"))
+		.env("CARGO_TARGET_DIR", &target_dir)
+		.env_remove("RUSTFLAGS")
+		.env_remove("CARGO_ENCODED_RUSTFLAGS")
+		.env_remove("RUSTUP_TOOLCHAIN")
+		.status()
+		.expect(
+			"`cargo contract` builds the contracts, install it with `cargo install cargo-contract`",
+		);
+	assert!(status.success(), "`cargo contract build` failed for {}", contract.display());
+
+	println!(
+		"cargo:rustc-env=PSP22_ASSET_WASM={}",
+		target_dir.join("ink/psp22_asset.wasm").display()
+	);
+}
@@ -0,0 +1,44 @@
+;; Forwards calls to the chain extension of the runtime.
+;;
+;; The input is the function id as a little endian u32 followed by the input of the function.
+;; The output is the status code as a little endian u32 followed by the output of the function.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_call_chain_extension"
+		(func $seal_call_chain_extension (param i32 i32 i32 i32 i32) (result i32))
+	)
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) length of the input buffer, 1024
+	(data (i32.const 0) "\00\04")
+
+	;; [4, 8) length of the output buffer, 1024
+	(data (i32.const 4) "\00\04")
+
+	;; [8, 1032) input buffer: the function id, followed by the input of the function
+
+	;; [2044, 2048) status code, directly followed by
+	;; [2048, 3072) output buffer
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 8) (i32.const 0))
+		(i32.store
+			(i32.const 2044)
+			(call $seal_call_chain_extension
+				(i32.load (i32.const 8))
+				(i32.const 12)
+				(i32.sub (i32.load (i32.const 0)) (i32.const 4))
+				(i32.const 2048)
+				(i32.const 4)
+			)
+		)
+		(call $seal_return
+			(i32.const 0)
+			(i32.const 2044)
+			(i32.add (i32.load (i32.const 4)) (i32.const 4))
+		)
+	)
+)
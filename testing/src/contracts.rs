@@ -0,0 +1,85 @@
+//! Deploying and calling contracts in [`ExtBuilder`](crate::ext::ExtBuilder) externalities.
+//!
+//! The chain extension of the runtime is exercised through the extension proxy fixture, a
+//! contract that forwards its input to `seal_call_chain_extension` and returns the status code
+//! and output of the extension.
+
+use codec::{Decode, Encode};
+use node_template_runtime::{AccountId, Balance, Contracts, Weight};
+use pallet_contracts_primitives::{Code, ExecReturnValue};
+use sp_runtime::DispatchError;
+
+use crate::Keyring;
+
+/// The gas limit of calls into contracts, generous for the fixtures.
+pub const GAS_LIMIT: Weight = 100_000_000_000;
+
+/// The balance a contract is endowed with on instantiation.
+pub const ENDOWMENT: Balance = 1 << 40;
+
+/// The extension proxy fixture, in the WebAssembly text format.
+pub const EXTENSION_PROXY: &str = include_str!("../fixtures/extension_proxy.wat");
+
+/// The `contracts/psp22-asset` token, built by `cargo contract` in the build script.
+pub const PSP22_ASSET: &[u8] = include_bytes!(env!("PSP22_ASSET_WASM"));
+
+/// Instantiate the contract in the WebAssembly text format `wat` by `owner`, with `salt` to tell
+/// several instances apart.
+pub fn deploy_wat(wat: &str, owner: Keyring, salt: u8) -> AccountId {
+	let code = wat::parse_str(wat).expect("the fixture is valid WebAssembly text; qed");
+	deploy(code, owner, Vec::new(), salt)
+}
+
+/// Upload and instantiate `code` by `owner`, calling its constructor with `data`.
+pub fn deploy(code: Vec<u8>, owner: Keyring, data: Vec<u8>, salt: u8) -> AccountId {
+	Contracts::bare_instantiate(
+		owner.to_account_id(),
+		ENDOWMENT,
+		GAS_LIMIT,
+		Code::Upload(code.into()),
+		data,
+		vec![salt],
+		false,
+	)
+	.result
+	.expect("the contract instantiates")
+	.account_id
+}
+
+/// Call `contract` by `caller` with `input`.
+pub fn call(
+	contract: &AccountId,
+	caller: Keyring,
+	input: Vec<u8>,
+) -> Result<ExecReturnValue, DispatchError> {
+	Contracts::bare_call(caller.to_account_id(), contract.clone(), 0, GAS_LIMIT, input, false)
+		.result
+}
+
+/// Call the function `func_id` of the chain extension with `input` through the extension proxy
+/// `proxy`, returning the status code and the output of the function.
+pub fn call_extension(
+	proxy: &AccountId,
+	caller: Keyring,
+	func_id: u32,
+	input: impl Encode,
+) -> Result<(u32, Vec<u8>), DispatchError> {
+	let mut data = func_id.encode();
+	input.encode_to(&mut data);
+	let output = call(proxy, caller, data)?.data.0;
+	let status = u32::decode(&mut &output[..]).expect("the proxy returns the status code; qed");
+	Ok((status, output[4..].to_vec()))
+}
+
+/// Call a function of the chain extension that succeeds and has an output of type `T`.
+pub fn read_extension<T: Decode>(
+	proxy: &AccountId,
+	caller: Keyring,
+	func_id: u32,
+	input: impl Encode,
+) -> T {
+	let (status, output) =
+		call_extension(proxy, caller, func_id, input).expect("the extension does not trap");
+	assert_eq!(status, 0, "the extension succeeds");
+	T::decode(&mut &output[..]).expect("the extension returns a `T`")
+}
@@ -0,0 +1,149 @@
+//! Test externalities for the real runtime.
+//!
+//! Unlike the mock runtime of `pallet_template`, [`ExtBuilder`] builds the genesis state of
+//! `node_template_runtime::Runtime` through `chain_spec::testnet_genesis`, and [`run_to_block`]
+//! drives blocks through `Executive` with an Aura pre-runtime digest and the timestamp inherent.
+//! This allows unit tests of behaviour that spans several pallets, such as transaction fees.
+
+use codec::Encode;
+use node_template::{
+	chain_spec::{authority_keys_from_seed, get_account_id_from_seed, testnet_genesis},
+	extrinsic::{create_signed, ExtrinsicParams},
+};
+use node_template_runtime::{
+	AccountId, BlockNumber, BuildStorage, Call, Executive, Hash, Header, System, TimestampCall,
+	UncheckedExtrinsic, SLOT_DURATION, VERSION, WASM_BINARY,
+};
+use sp_consensus_aura::{sr25519::AuthorityId as AuraId, Slot, AURA_ENGINE_ID};
+use sp_core::sr25519;
+use sp_finality_grandpa::AuthorityId as GrandpaId;
+use sp_runtime::{
+	generic::{Digest, DigestItem, Era},
+	traits::Header as HeaderT,
+	ApplyExtrinsicResult,
+};
+
+use crate::Keyring;
+
+/// Builds the genesis state of the runtime like the chain specs of the node do.
+pub struct ExtBuilder {
+	authorities: Vec<(AuraId, GrandpaId)>,
+	root_key: AccountId,
+	endowed_accounts: Vec<AccountId>,
+}
+
+/// The same genesis as the `dev` chain.
+impl Default for ExtBuilder {
+	fn default() -> Self {
+		ExtBuilder {
+			authorities: vec![authority_keys_from_seed("Alice")],
+			root_key: get_account_id_from_seed::<sr25519::Public>("Alice"),
+			endowed_accounts: vec![
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				get_account_id_from_seed::<sr25519::Public>("Bob"),
+				get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
+				get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
+			],
+		}
+	}
+}
+
+impl ExtBuilder {
+	/// Use the Aura and GRANDPA keys derived from `seeds` as the initial authorities.
+	pub fn authorities(mut self, seeds: &[&str]) -> Self {
+		self.authorities = seeds.iter().map(|seed| authority_keys_from_seed(seed)).collect();
+		self
+	}
+
+	/// Set the sudo key.
+	pub fn sudo(mut self, root_key: AccountId) -> Self {
+		self.root_key = root_key;
+		self
+	}
+
+	/// Set the accounts that are endowed with an initial balance.
+	pub fn endowed_accounts(mut self, endowed_accounts: Vec<AccountId>) -> Self {
+		self.endowed_accounts = endowed_accounts;
+		self
+	}
+
+	/// Build the externalities, with block 1 initialized and ready for dispatching calls.
+	pub fn build(self) -> sp_io::TestExternalities {
+		let storage = testnet_genesis(
+			WASM_BINARY.unwrap_or_default(),
+			self.authorities,
+			self.root_key,
+			self.endowed_accounts,
+			false,
+		)
+		.build_storage()
+		.expect("the testnet genesis is valid; qed");
+
+		let mut ext = sp_io::TestExternalities::new(storage);
+		ext.execute_with(|| run_to_block(1));
+		ext
+	}
+}
+
+/// Finalize the current block and initialize blocks until block `n` is reached.
+///
+/// Every block is initialized with an Aura pre-runtime digest and gets the timestamp inherent of
+/// its slot applied, so pallets see the same state as in blocks authored by the node. Block `n`
+/// is left open so that calls dispatched afterwards end up in it.
+pub fn run_to_block(n: BlockNumber) {
+	while System::block_number() < n {
+		let number = System::block_number();
+		let parent_hash =
+			if number == 0 { System::block_hash(0) } else { Executive::finalize_block().hash() };
+		initialize_block(number + 1, parent_hash);
+	}
+}
+
+fn initialize_block(number: BlockNumber, parent_hash: Hash) {
+	// Use the block number as slot, slots only have to increase.
+	let slot = Slot::from(number as u64);
+	let digest = Digest { logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode())] };
+	let header = Header::new(number, Default::default(), Default::default(), parent_hash, digest);
+	Executive::initialize_block(&header);
+
+	let now = *slot * SLOT_DURATION;
+	let timestamp = UncheckedExtrinsic::new_unsigned(Call::Timestamp(TimestampCall::set { now }));
+	Executive::apply_extrinsic(timestamp)
+		.expect("the timestamp inherent is valid; qed")
+		.expect("the timestamp inherent dispatches; qed");
+}
+
+/// Sign `call` by `signer` with its next nonce, immortal and without tip.
+pub fn signed_extrinsic(call: Call, signer: Keyring) -> UncheckedExtrinsic {
+	sponsored_extrinsic(call, signer, None)
+}
+
+/// Like [`signed_extrinsic`], with the fee paid by `sponsor` if given.
+pub fn sponsored_extrinsic(
+	call: Call,
+	signer: Keyring,
+	sponsor: Option<Keyring>,
+) -> UncheckedExtrinsic {
+	let genesis_hash = System::block_hash(0);
+	let params = ExtrinsicParams {
+		nonce: System::account_nonce(signer.to_account_id()),
+		tip: 0,
+		sponsor: sponsor.map(|sponsor| sponsor.to_account_id()),
+		era: Era::Immortal,
+		era_block_hash: genesis_hash,
+		genesis_hash,
+		spec_version: VERSION.spec_version,
+		transaction_version: VERSION.transaction_version,
+	};
+	create_signed(call, &signer.pair(), &params)
+}
+
+/// Apply `call` signed by `signer` in the current block, including fee payment.
+pub fn apply_signed(call: Call, signer: Keyring) -> ApplyExtrinsicResult {
+	Executive::apply_extrinsic(signed_extrinsic(call, signer))
+}
+
+/// Apply `call` signed by `signer` in the current block, with the fee paid by `sponsor`.
+pub fn apply_sponsored(call: Call, signer: Keyring, sponsor: Keyring) -> ApplyExtrinsicResult {
+	Executive::apply_extrinsic(sponsored_extrinsic(call, signer, Some(sponsor)))
+}
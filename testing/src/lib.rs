@@ -0,0 +1,286 @@
+//! End-to-end testing of the node template.
+//!
+//! [`TestNode`] boots a full node in-process on top of a temporary database. Blocks are only
+//! produced when a test asks for them, which makes it possible to submit extrinsics through the
+//! transaction pool, seal them into a block and inspect the resulting state deterministically.
+//!
+//! For tests that don't need a node, [`ext::ExtBuilder`] provides externalities for the runtime
+//! with the genesis state of the development chain.
+
+pub mod contracts;
+pub mod ext;
+pub mod service;
+
+use codec::{Decode, Encode};
+use frame_system::{AccountInfo, EventRecord};
+use futures::{channel::oneshot, executor::block_on, SinkExt, StreamExt};
+use node_template::{
+	cli::Cli,
+	decode::storage_prefix,
+	extrinsic::{create_signed, ExtrinsicParams},
+	service::FullClient,
+};
+use node_template_runtime::{opaque::Block, AccountId, Balance, Call, Event, Hash, Index};
+use sc_cli::SubstrateCli;
+use sc_client_api::StorageProvider;
+use sc_consensus_manual_seal::EngineCommand;
+use sc_transaction_pool_api::{
+	TransactionPool, TransactionSource, TransactionStatus, TransactionStatusStreamFor,
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::blake2_128, storage::StorageKey};
+use sp_runtime::generic::{BlockId, Era};
+use std::{
+	collections::HashMap,
+	pin::Pin,
+	sync::{Arc, Mutex},
+};
+use structopt::StructOpt;
+use substrate_frame_rpc_system::AccountNonceApi;
+
+pub use service::{new_manual_seal, ManualSealNode};
+pub use sp_keyring::AccountKeyring as Keyring;
+
+/// The transaction pool of [`TestNode`].
+pub type FullPool = sc_transaction_pool::FullPool<Block, FullClient>;
+
+/// A development chain node running in the current process.
+///
+/// Dropping the node shuts it down and removes its database.
+pub struct TestNode {
+	node: ManualSealNode,
+	nonces: Mutex<HashMap<AccountId, Index>>,
+	// The node has to shut down before its tasks lose their executor and its database is removed,
+	// keep these after `node`.
+	_runtime: tokio::runtime::Runtime,
+	_base_path: tempfile::TempDir,
+}
+
+impl TestNode {
+	/// Start a node on the `dev` chain with a fresh database.
+	pub fn new() -> Self {
+		let runtime = tokio::runtime::Builder::new_multi_thread()
+			.enable_all()
+			.build()
+			.expect("failed to start the tokio runtime");
+		let base_path = tempfile::tempdir().expect("failed to create a temporary directory");
+
+		let cli = Cli::from_iter(&[
+			"node-template",
+			"--dev",
+			"--base-path",
+			base_path.path().to_str().expect("temporary directories have utf-8 paths; qed"),
+			"--port",
+			"0",
+		]);
+		let mut config = cli
+			.create_configuration(&cli.run, runtime.handle().clone())
+			.expect("the dev chain configuration is valid; qed");
+		// Several test nodes may run in parallel, don't let them compete for ports.
+		config.rpc_http = None;
+		config.rpc_ws = None;
+		config.rpc_ipc = None;
+		config.prometheus_config = None;
+		config.telemetry_endpoints = None;
+
+		let node = {
+			let _guard = runtime.enter();
+			new_manual_seal(config).expect("failed to start the test node")
+		};
+
+		TestNode { node, nonces: Default::default(), _runtime: runtime, _base_path: base_path }
+	}
+
+	/// The client of the node.
+	pub fn client(&self) -> &Arc<FullClient> {
+		&self.node.client
+	}
+
+	/// The transaction pool of the node.
+	pub fn transaction_pool(&self) -> &Arc<FullPool> {
+		&self.node.transaction_pool
+	}
+
+	/// The hash of the best block.
+	pub fn best_hash(&self) -> Hash {
+		self.node.client.info().best_hash
+	}
+
+	/// Sign `call` by `signer` and submit it to the transaction pool.
+	///
+	/// Nonces are tracked by the node, so several extrinsics of the same signer can be submitted
+	/// before sealing a block.
+	pub fn submit(&self, call: Call, signer: Keyring) -> Result<TransactionWatcher, String> {
+		let at = BlockId::Hash(self.best_hash());
+		let version = self.node.client.runtime_version_at(&at).map_err(|e| e.to_string())?;
+		let genesis_hash = self.node.client.info().genesis_hash;
+
+		let params = ExtrinsicParams {
+			nonce: self.next_nonce(&signer.to_account_id(), &at)?,
+			tip: 0,
+			sponsor: None,
+			era: Era::Immortal,
+			era_block_hash: genesis_hash,
+			genesis_hash,
+			spec_version: version.spec_version,
+			transaction_version: version.transaction_version,
+		};
+		let extrinsic = create_signed(call, &signer.pair(), &params);
+
+		let status = block_on(self.node.transaction_pool.submit_and_watch(
+			&at,
+			TransactionSource::External,
+			extrinsic.into(),
+		))
+		.map_err(|e| e.to_string())?;
+		Ok(TransactionWatcher { status })
+	}
+
+	fn next_nonce(&self, who: &AccountId, at: &BlockId<Block>) -> Result<Index, String> {
+		let on_chain = self
+			.node
+			.client
+			.runtime_api()
+			.account_nonce(at, who.clone())
+			.map_err(|e| e.to_string())?;
+		let mut nonces = self.nonces.lock().expect("no panics while holding the lock; qed");
+		let nonce = nonces.get(who).map_or(on_chain, |local| on_chain.max(*local));
+		nonces.insert(who.clone(), nonce + 1);
+		Ok(nonce)
+	}
+
+	/// Seal a new block on top of the best block, including all ready extrinsics.
+	pub fn seal_block(&self) -> Result<Hash, String> {
+		self.seal(false)
+	}
+
+	/// Seal a new block on top of the best block and finalize it right away.
+	pub fn seal_and_finalize_block(&self) -> Result<Hash, String> {
+		self.seal(true)
+	}
+
+	fn seal(&self, finalize: bool) -> Result<Hash, String> {
+		let (sender, receiver) = oneshot::channel();
+		let command = EngineCommand::SealNewBlock {
+			create_empty: true,
+			finalize,
+			parent_hash: None,
+			sender: Some(sender),
+		};
+		block_on(async {
+			self.node.commands.clone().send(command).await.map_err(|e| e.to_string())?;
+			match receiver.await {
+				Ok(result) => result.map(|created| created.hash).map_err(|e| e.to_string()),
+				Err(_) => Err("The block authoring task shut down".into()),
+			}
+		})
+	}
+
+	/// Finalize the block `hash` and all of its ancestors.
+	pub fn finalize_block(&self, hash: Hash) -> Result<(), String> {
+		let (sender, receiver) = oneshot::channel();
+		let command =
+			EngineCommand::FinalizeBlock { hash, sender: Some(sender), justification: None };
+		block_on(async {
+			self.node.commands.clone().send(command).await.map_err(|e| e.to_string())?;
+			match receiver.await {
+				Ok(result) => result.map_err(|e| e.to_string()),
+				Err(_) => Err("The block authoring task shut down".into()),
+			}
+		})
+	}
+
+	/// Read and decode the raw storage entry `key` at the best block.
+	pub fn storage<T: Decode>(&self, key: Vec<u8>) -> Option<T> {
+		let at = BlockId::Hash(self.best_hash());
+		let raw = self
+			.node
+			.client
+			.storage(&at, &StorageKey(key))
+			.expect("the best block has state; qed")?;
+		Some(T::decode(&mut &raw.0[..]).expect("storage entries decode as their type; qed"))
+	}
+
+	/// Read a plain storage item, e.g. `storage_value::<u64>("Timestamp", "Now")`.
+	pub fn storage_value<T: Decode>(&self, pallet: &str, item: &str) -> Option<T> {
+		self.storage(storage_prefix(pallet, item))
+	}
+
+	/// Read an entry of a storage map hashed with `Blake2_128Concat`.
+	pub fn storage_map<K: Encode, T: Decode>(
+		&self,
+		pallet: &str,
+		item: &str,
+		key: &K,
+	) -> Option<T> {
+		let key = key.encode();
+		self.storage([storage_prefix(pallet, item), blake2_128(&key).to_vec(), key].concat())
+	}
+
+	/// The free balance of `who` at the best block.
+	pub fn free_balance(&self, who: &AccountId) -> Balance {
+		self.storage_map::<_, AccountInfo<Index, pallet_balances::AccountData<Balance>>>(
+			"System", "Account", who,
+		)
+		.map_or(0, |info| info.data.free)
+	}
+
+	/// The events deposited in the best block.
+	pub fn events(&self) -> Vec<EventRecord<Event, Hash>> {
+		self.storage_value("System", "Events").unwrap_or_default()
+	}
+}
+
+impl Default for TestNode {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Follows the status of a submitted extrinsic.
+pub struct TransactionWatcher {
+	status: Pin<Box<TransactionStatusStreamFor<FullPool>>>,
+}
+
+impl TransactionWatcher {
+	/// Wait until the extrinsic is included in a block, returns that block's hash.
+	///
+	/// This does not produce blocks, seal one before waiting.
+	pub fn wait_for_inclusion(&mut self) -> Result<Hash, String> {
+		self.wait_for(|status| match status {
+			TransactionStatus::InBlock(hash) => Some(*hash),
+			_ => None,
+		})
+	}
+
+	/// Wait until the block including the extrinsic is finalized, returns that block's hash.
+	pub fn wait_for_finality(&mut self) -> Result<Hash, String> {
+		self.wait_for(|status| match status {
+			TransactionStatus::Finalized(hash) => Some(*hash),
+			_ => None,
+		})
+	}
+
+	fn wait_for(
+		&mut self,
+		matches: impl Fn(&TransactionStatus<Hash, Hash>) -> Option<Hash>,
+	) -> Result<Hash, String> {
+		block_on(async {
+			while let Some(status) = self.status.next().await {
+				if let Some(hash) = matches(&status) {
+					return Ok(hash)
+				}
+				match status {
+					TransactionStatus::Usurped(_) |
+					TransactionStatus::Dropped |
+					TransactionStatus::Invalid |
+					TransactionStatus::FinalityTimeout(_) =>
+						return Err(format!("Transaction was not included: {:?}", status)),
+					_ => {},
+				}
+			}
+			Err("The transaction pool stopped reporting the status".into())
+		})
+	}
+}
@@ -0,0 +1,118 @@
+//! A full node that only produces blocks when asked to.
+
+use futures::channel::mpsc;
+use node_template::service::{new_executor, FullClient};
+use node_template_runtime::{opaque::Block, Hash, RuntimeApi};
+use sc_consensus_manual_seal::{
+	consensus::{aura::AuraConsensusDataProvider, timestamp::SlotTimestampProvider},
+	EngineCommand, ManualSealParams,
+};
+use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
+use std::sync::Arc;
+
+/// The handles of a running manual seal node.
+pub struct ManualSealNode {
+	/// Owns all tasks of the node, the node shuts down once this is dropped.
+	pub task_manager: TaskManager,
+	/// The client of the node.
+	pub client: Arc<FullClient>,
+	/// The transaction pool of the node.
+	pub transaction_pool: Arc<sc_transaction_pool::FullPool<Block, FullClient>>,
+	/// Send commands to the block authoring task through this.
+	pub commands: mpsc::Sender<EngineCommand<Hash>>,
+}
+
+/// Build a full node that seals and finalizes blocks on request.
+///
+/// Blocks still carry an Aura pre-runtime digest with a slot matching their timestamp, so the
+/// runtime executes them exactly like blocks authored by `service::new_full`.
+pub fn new_manual_seal(config: Configuration) -> Result<ManualSealNode, ServiceError> {
+	let executor = new_executor(&config);
+
+	let (client, backend, keystore_container, mut task_manager) =
+		sc_service::new_full_parts::<Block, RuntimeApi, _>(&config, None, executor)?;
+	let client = Arc::new(client);
+
+	let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+	let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+		config.transaction_pool.clone(),
+		config.role.is_authority().into(),
+		config.prometheus_registry(),
+		task_manager.spawn_essential_handle(),
+		client.clone(),
+	);
+
+	let import_queue = sc_consensus_manual_seal::import_queue(
+		Box::new(client.clone()),
+		&task_manager.spawn_essential_handle(),
+		config.prometheus_registry(),
+	);
+
+	let (network, system_rpc_tx, network_starter) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue,
+			on_demand: None,
+			block_announce_validator_builder: None,
+			warp_sync: None,
+		})?;
+
+	let prometheus_registry = config.prometheus_registry().cloned();
+
+	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		network,
+		client: client.clone(),
+		keystore: keystore_container.sync_keystore(),
+		task_manager: &mut task_manager,
+		transaction_pool: transaction_pool.clone(),
+		rpc_extensions_builder: Box::new(|_, _| Ok(())),
+		on_demand: None,
+		remote_blockchain: None,
+		backend,
+		system_rpc_tx,
+		config,
+		telemetry: None,
+	})?;
+
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+		None,
+	);
+
+	let (commands, commands_stream) = mpsc::channel(1024);
+
+	let client_for_inherents = client.clone();
+	let authorship = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+		block_import: client.clone(),
+		env: proposer_factory,
+		client: client.clone(),
+		pool: transaction_pool.pool().clone(),
+		commands_stream,
+		select_chain,
+		consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client.clone()))),
+		create_inherent_data_providers: move |_, ()| {
+			let client = client_for_inherents.clone();
+			async move {
+				// Every block gets the timestamp of the next slot, independent of the wall clock.
+				let timestamp = SlotTimestampProvider::new_aura(client)
+					.map_err(|e| format!("Failed to create timestamp provider: {:?}", e))?;
+				Ok(timestamp)
+			}
+		},
+	});
+
+	// the authorship task is considered essential, i.e. if it
+	// fails we take down the service with it.
+	task_manager.spawn_essential_handle().spawn_blocking("manual-seal", authorship);
+
+	network_starter.start_network();
+
+	Ok(ManualSealNode { task_manager, client, transaction_pool, commands })
+}
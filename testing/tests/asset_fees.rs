@@ -0,0 +1,40 @@
+use node_template_runtime::{
+	pallet_template, AssetFees, Assets, Balances, Call, Event, Origin, System,
+};
+use node_template_testing::{
+	ext::{apply_signed, ExtBuilder},
+	Keyring,
+};
+use sp_runtime::{FixedPointNumber, FixedU128};
+
+const ASSET: u32 = 1;
+
+fn do_something(something: u32) -> Call {
+	Call::TemplateModule(pallet_template::Call::do_something { something })
+}
+
+#[test]
+fn fees_are_paid_in_the_chosen_asset() {
+	ExtBuilder::default().build().execute_with(|| {
+		let alice = Keyring::Alice.to_account_id();
+		let bob = Keyring::Bob.to_account_id();
+		Assets::force_create(Origin::root(), ASSET, alice.clone().into(), true, 1).unwrap();
+		Assets::mint(Origin::signed(alice), ASSET, bob.clone().into(), 1 << 60).unwrap();
+		AssetFees::set_rate(Origin::root(), ASSET, Some(FixedU128::from_integer(3))).unwrap();
+
+		let set_fee_asset =
+			Call::AssetFees(pallet_asset_fees::Call::set_fee_asset { asset: Some(ASSET) });
+		assert_eq!(apply_signed(set_fee_asset, Keyring::Bob), Ok(Ok(())));
+
+		let native_before = Balances::total_balance(&bob);
+		let assets_before = Assets::balance(ASSET, &bob);
+		assert_eq!(apply_signed(do_something(7), Keyring::Bob), Ok(Ok(())));
+
+		assert_eq!(Balances::total_balance(&bob), native_before);
+		let paid = assets_before - Assets::balance(ASSET, &bob);
+		assert!(paid > 0);
+		assert_eq!(paid % 3, 0);
+		assert!(System::events().iter().any(|record| record.event ==
+			Event::AssetFees(pallet_asset_fees::Event::AssetFeePaid(bob.clone(), ASSET, paid))));
+	});
+}
@@ -0,0 +1,146 @@
+use node_template_runtime::{
+	chain_extension::{assets::*, ASSETS},
+	AccountId, Assets, Balance, Origin,
+};
+use node_template_testing::{
+	contracts::{call_extension, deploy_wat, read_extension, EXTENSION_PROXY},
+	ext::ExtBuilder,
+	Keyring,
+};
+
+const ASSET: u32 = 1;
+
+fn func_id(function: u16) -> u32 {
+	(ASSETS as u32) << 16 | function as u32
+}
+
+/// Deploy the extension proxy, create `ASSET` and mint 1000 to the proxy and 500 to Bob.
+fn setup() -> AccountId {
+	let alice = Keyring::Alice.to_account_id();
+	let proxy = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+	Assets::force_create(Origin::root(), ASSET, alice.clone().into(), true, 1).unwrap();
+	Assets::mint(Origin::signed(alice.clone()), ASSET, proxy.clone().into(), 1000).unwrap();
+	Assets::mint(Origin::signed(alice), ASSET, Keyring::Bob.to_account_id().into(), 500).unwrap();
+	proxy
+}
+
+fn balance_of(proxy: &AccountId, owner: &AccountId) -> Balance {
+	read_extension(proxy, Keyring::Alice, func_id(BALANCE_OF), (ASSET, owner))
+}
+
+fn allowance(proxy: &AccountId, owner: &AccountId, spender: &AccountId) -> Balance {
+	read_extension(proxy, Keyring::Alice, func_id(ALLOWANCE), (ASSET, owner, spender))
+}
+
+#[test]
+fn contracts_read_balances_and_supply() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = setup();
+
+		assert_eq!(balance_of(&proxy, &proxy), 1000);
+		assert_eq!(balance_of(&proxy, &Keyring::Bob.to_account_id()), 500);
+		assert_eq!(balance_of(&proxy, &Keyring::Charlie.to_account_id()), 0);
+		let supply: Balance = read_extension(&proxy, Keyring::Alice, func_id(TOTAL_SUPPLY), ASSET);
+		assert_eq!(supply, 1500);
+	});
+}
+
+#[test]
+fn contracts_transfer_their_own_assets() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = setup();
+		let bob = Keyring::Bob.to_account_id();
+
+		let transfer = |value: Balance| {
+			call_extension(&proxy, Keyring::Alice, func_id(TRANSFER), (ASSET, &bob, value))
+				.unwrap()
+				.0
+		};
+		assert_eq!(transfer(100), 0);
+		assert_eq!(Assets::balance(ASSET, &proxy), 900);
+		assert_eq!(Assets::balance(ASSET, &bob), 600);
+
+		assert_eq!(transfer(1000), AssetsError::BalanceLow as u32);
+		assert_eq!(Assets::balance(ASSET, &proxy), 900);
+	});
+}
+
+#[test]
+fn contracts_approve_spenders_of_their_assets() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = setup();
+		let bob = Keyring::Bob.to_account_id();
+
+		let approve = |value: Balance| {
+			call_extension(&proxy, Keyring::Alice, func_id(APPROVE), (ASSET, &bob, value))
+				.unwrap()
+				.0
+		};
+		assert_eq!(approve(50), 0);
+		assert_eq!(allowance(&proxy, &proxy, &bob), 50);
+
+		// Approving again replaces the allowance instead of adding to it.
+		assert_eq!(approve(20), 0);
+		assert_eq!(allowance(&proxy, &proxy, &bob), 20);
+		assert_eq!(approve(0), 0);
+		assert_eq!(allowance(&proxy, &proxy, &bob), 0);
+	});
+}
+
+#[test]
+fn failed_approvals_keep_the_previous_allowance() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = setup();
+		let bob = Keyring::Bob.to_account_id();
+
+		let approve = |value: Balance| {
+			call_extension(&proxy, Keyring::Alice, func_id(APPROVE), (ASSET, &bob, value))
+				.unwrap()
+				.0
+		};
+		assert_eq!(approve(50), 0);
+
+		// Frozen assets can not be approved, the cancelled approval is restored.
+		Assets::freeze_asset(Origin::signed(Keyring::Alice.to_account_id()), ASSET).unwrap();
+		assert_eq!(approve(20), AssetsError::Frozen as u32);
+		assert_eq!(allowance(&proxy, &proxy, &bob), 50);
+	});
+}
+
+#[test]
+fn contracts_transfer_approved_assets() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = setup();
+		let alice = Keyring::Alice.to_account_id();
+		let bob = Keyring::Bob.to_account_id();
+		Assets::approve_transfer(Origin::signed(bob.clone()), ASSET, proxy.clone().into(), 30)
+			.unwrap();
+		assert_eq!(allowance(&proxy, &bob, &proxy), 30);
+
+		let transfer_from = |value: Balance| {
+			call_extension(
+				&proxy,
+				Keyring::Alice,
+				func_id(TRANSFER_FROM),
+				(ASSET, &bob, &alice, value),
+			)
+			.unwrap()
+			.0
+		};
+		assert_eq!(transfer_from(30), 0);
+		assert_eq!(Assets::balance(ASSET, &bob), 470);
+		assert_eq!(Assets::balance(ASSET, &alice), 30);
+
+		assert_eq!(transfer_from(1), AssetsError::Unapproved as u32);
+	});
+}
+
+#[test]
+fn unknown_functions_trap() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = setup();
+
+		assert!(call_extension(&proxy, Keyring::Alice, func_id(0xffff), ()).is_err());
+		assert!(call_extension(&proxy, Keyring::Alice, 0xffff << 16, ()).is_err());
+	});
+}
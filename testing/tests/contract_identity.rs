@@ -0,0 +1,73 @@
+use node_template_runtime::{
+	chain_extension::{identity::*, IDENTITY},
+	AccountId, Identity, Origin,
+};
+use node_template_testing::{
+	contracts::{deploy_wat, read_extension, EXTENSION_PROXY},
+	ext::ExtBuilder,
+	Keyring,
+};
+use pallet_identity::{Data, IdentityInfo, Judgement as RegistrarJudgement};
+use std::convert::TryInto;
+
+fn identity_of(proxy: &AccountId, who: &AccountId) -> Option<(Vec<u8>, u8)> {
+	let func_id = (IDENTITY as u32) << 16 | IDENTITY_OF as u32;
+	read_extension(proxy, Keyring::Alice, func_id, who)
+}
+
+fn set_identity(who: Keyring, display: &[u8]) {
+	let info = IdentityInfo {
+		additional: Default::default(),
+		display: Data::Raw(display.to_vec().try_into().unwrap()),
+		legal: Data::None,
+		web: Data::None,
+		riot: Data::None,
+		email: Data::None,
+		pgp_fingerprint: None,
+		image: Data::None,
+		twitter: Data::None,
+	};
+	Identity::set_identity(Origin::signed(who.to_account_id()), Box::new(info)).unwrap();
+}
+
+#[test]
+fn the_dev_registrar_is_alice() {
+	ExtBuilder::default().build().execute_with(|| {
+		let registrar = Identity::registrars()[0].clone().unwrap();
+		assert_eq!(registrar.account, Keyring::Alice.to_account_id());
+	});
+}
+
+#[test]
+fn contracts_read_display_names_and_judgements() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+		let bob = Keyring::Bob.to_account_id();
+		assert_eq!(identity_of(&proxy, &bob), None);
+
+		set_identity(Keyring::Bob, b"bob");
+		assert_eq!(identity_of(&proxy, &bob), Some((b"bob".to_vec(), Judgement::None as u8)));
+
+		// Alice is the registrar of dev chains.
+		Identity::request_judgement(Origin::signed(bob.clone()), 0, 0).unwrap();
+		let alice = Origin::signed(Keyring::Alice.to_account_id());
+		Identity::provide_judgement(alice, 0, bob.clone().into(), RegistrarJudgement::KnownGood)
+			.unwrap();
+		assert_eq!(identity_of(&proxy, &bob), Some((b"bob".to_vec(), Judgement::KnownGood as u8)));
+	});
+}
+
+#[test]
+fn erroneous_judgements_outweigh_positive_ones() {
+	let judgements = [RegistrarJudgement::Reasonable, RegistrarJudgement::<u128>::LowQuality];
+	assert_eq!(Judgement::of(&judgements), Judgement::Reasonable);
+	let judgements = [RegistrarJudgement::KnownGood, RegistrarJudgement::<u128>::Erroneous];
+	assert_eq!(Judgement::of(&judgements), Judgement::Erroneous);
+	assert_eq!(Judgement::of(&[RegistrarJudgement::<u128>::FeePaid(1)]), Judgement::None);
+
+	// Erroneous has the highest byte value but is not positive.
+	assert!(Judgement::KnownGood.is_positive());
+	assert!(Judgement::Reasonable.is_positive());
+	assert!(!Judgement::Erroneous.is_positive());
+	assert!(!Judgement::LowQuality.is_positive());
+}
@@ -0,0 +1,64 @@
+use codec::Decode;
+use node_template_runtime::{
+	chain_extension::{randomness::*, RANDOMNESS},
+	AccountId, BeaconSessionLength, BlockNumber, Call, Hash, RandomnessBeacon, Runtime,
+};
+use node_template_testing::{
+	contracts::{call_extension, deploy_wat, EXTENSION_PROXY},
+	ext::{apply_signed, run_to_block, ExtBuilder},
+	Keyring,
+};
+use pallet_randomness_beacon::Error;
+
+const SECRET: [u8; 32] = [7; 32];
+
+/// The subject is passed as raw bytes, arrays are encoded without a length prefix.
+fn random(proxy: &AccountId, subject: [u8; 8]) -> (u32, Option<(Hash, BlockNumber)>) {
+	let func_id = (RANDOMNESS as u32) << 16 | RANDOM as u32;
+	let (status, output) = call_extension(proxy, Keyring::Alice, func_id, subject).unwrap();
+	(status, Decode::decode(&mut &output[..]).ok())
+}
+
+#[test]
+fn contracts_read_the_seed_revealed_by_validators() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+		let alice = Keyring::Alice.to_account_id();
+		let session = BeaconSessionLength::get();
+		assert_eq!(random(&proxy, *b"subject_"), (NO_SEED, None));
+
+		let commitment = RandomnessBeacon::commitment_of(&SECRET, &alice);
+		let commit = pallet_randomness_beacon::Call::commit { commitment };
+		apply_signed(Call::RandomnessBeacon(commit), Keyring::Alice).unwrap().unwrap();
+		run_to_block(session);
+		let reveal = pallet_randomness_beacon::Call::reveal { secret: SECRET };
+		apply_signed(Call::RandomnessBeacon(reveal), Keyring::Alice).unwrap().unwrap();
+
+		run_to_block(2 * session + 3);
+		let (status, output) = random(&proxy, *b"subject_");
+		assert_eq!(status, 0);
+		let (value, lookahead) = output.unwrap();
+		assert_eq!(lookahead, 3);
+		assert_eq!(Some((value, lookahead)), RandomnessBeacon::random(b"subject_"));
+		assert_ne!(random(&proxy, *b"others__").1.unwrap().0, value);
+	});
+}
+
+#[test]
+fn only_aura_authorities_commit() {
+	ExtBuilder::default().build().execute_with(|| {
+		let commitment = RandomnessBeacon::commitment_of(&SECRET, &Keyring::Bob.to_account_id());
+		let commit = pallet_randomness_beacon::Call::commit { commitment };
+		let result = apply_signed(Call::RandomnessBeacon(commit), Keyring::Bob).unwrap();
+		assert_eq!(result, Err(Error::<Runtime>::NotValidator.into()));
+	});
+}
+
+#[test]
+fn subjects_are_limited_to_32_bytes() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+		let func_id = (RANDOMNESS as u32) << 16 | RANDOM as u32;
+		assert!(call_extension(&proxy, Keyring::Alice, func_id, [0u8; 33]).is_err());
+	});
+}
@@ -0,0 +1,50 @@
+use node_template_runtime::{
+	chain_extension::{template::*, TEMPLATE},
+	pallet_template::{self, Role},
+	AccountId, Call, TemplateModule,
+};
+use node_template_testing::{
+	contracts::{deploy_wat, read_extension, EXTENSION_PROXY},
+	ext::{apply_signed, ExtBuilder},
+	Keyring,
+};
+
+fn has_role(proxy: &AccountId, role: Role, who: &AccountId) -> bool {
+	let func_id = (TEMPLATE as u32) << 16 | HAS_ROLE as u32;
+	read_extension(proxy, Keyring::Alice, func_id, (role, who))
+}
+
+#[test]
+fn dev_chains_start_with_an_admin_and_writers() {
+	ExtBuilder::default().build().execute_with(|| {
+		let alice = Keyring::Alice.to_account_id();
+		let bob = Keyring::Bob.to_account_id();
+		assert!(TemplateModule::has_role(Role::Admin, &alice));
+		assert!(TemplateModule::has_role(Role::Writer, &bob));
+		assert!(!TemplateModule::has_role(Role::Admin, &bob));
+		assert!(!TemplateModule::has_role(Role::Writer, &Keyring::Charlie.to_account_id()));
+	});
+}
+
+#[test]
+fn contracts_read_roles() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+		let alice = Keyring::Alice.to_account_id();
+		let bob = Keyring::Bob.to_account_id();
+		assert!(has_role(&proxy, Role::Reader, &alice));
+		assert!(has_role(&proxy, Role::Writer, &bob));
+		assert!(!has_role(&proxy, Role::Reader, &bob));
+		assert!(!has_role(&proxy, Role::Reader, &proxy));
+
+		// Admins grant roles to contract accounts like to any other account.
+		let grant = pallet_template::Call::grant_role { role: Role::Reader, who: proxy.clone() };
+		assert_eq!(apply_signed(Call::TemplateModule(grant), Keyring::Alice), Ok(Ok(())));
+		assert!(has_role(&proxy, Role::Reader, &proxy));
+
+		// Only admins and root do.
+		let grant = pallet_template::Call::grant_role { role: Role::Admin, who: bob.clone() };
+		assert!(apply_signed(Call::TemplateModule(grant), Keyring::Bob).unwrap().is_err());
+		assert!(!has_role(&proxy, Role::Admin, &bob));
+	});
+}
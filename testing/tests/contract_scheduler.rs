@@ -0,0 +1,117 @@
+use codec::{Decode, Encode};
+use frame_support::traits::schedule::DispatchTime;
+use node_template_runtime::{
+	chain_extension::{randomness, scheduler::*, RANDOMNESS, SCHEDULER},
+	AccountId, Balances, BlockNumber, ContractScheduler, Event, MaxScheduledDataLen, Runtime,
+	ScheduledDataDepositPerByte, System, Weight,
+};
+use node_template_testing::{
+	contracts::{call_extension, deploy_wat, EXTENSION_PROXY},
+	ext::{run_to_block, ExtBuilder},
+	Keyring,
+};
+use pallet_contract_scheduler::{Event as SchedulerEvent, ScheduleId};
+
+/// The gas limit of every run of the scheduled calls.
+const GAS: Weight = 10_000_000_000;
+
+fn func_id(extension: u16, function: u16) -> u32 {
+	(extension as u32) << 16 | function as u32
+}
+
+/// Schedule a call of the extension proxy `proxy` to itself, which calls the extension function
+/// `selector` with `input`.
+fn schedule(
+	proxy: &AccountId,
+	at: BlockNumber,
+	periodic: Option<(BlockNumber, u32)>,
+	gas_limit: Weight,
+	selector: u32,
+	input: Vec<u8>,
+) -> (u32, Option<ScheduleId>) {
+	let when = DispatchTime::At(at);
+	let input = (when, periodic, gas_limit, selector.to_le_bytes(), input);
+	let (status, output) =
+		call_extension(proxy, Keyring::Alice, func_id(SCHEDULER, SCHEDULE), input).unwrap();
+	(status, Decode::decode(&mut &output[..]).ok())
+}
+
+fn has_event(event: SchedulerEvent<Runtime>) -> bool {
+	System::events()
+		.iter()
+		.any(|record| record.event == Event::ContractScheduler(event.clone()))
+}
+
+#[test]
+fn scheduled_calls_reach_the_contract() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+		let random = func_id(RANDOMNESS, randomness::RANDOM);
+		let cancel = func_id(SCHEDULER, CANCEL);
+
+		// A periodic call, cancelled by a call scheduled later.
+		assert_eq!(schedule(&proxy, 3, Some((1, 10)), GAS, random, vec![]), (0, Some(0)));
+		// The fees of all runs and the deposit for the selector.
+		let deposit = 4 * ScheduledDataDepositPerByte::get();
+		assert_eq!(Balances::reserved_balance(&proxy), 10 * GAS as u128 + deposit);
+		assert_eq!(schedule(&proxy, 5, None, GAS, cancel, 0u64.encode()), (0, Some(1)));
+
+		run_to_block(7);
+		assert!(has_event(SchedulerEvent::Cancelled(0, proxy.clone())));
+		assert!(ContractScheduler::schedule_of(0).is_none());
+		assert!(ContractScheduler::schedule_of(1).is_none());
+		assert_eq!(Balances::reserved_balance(&proxy), 0);
+	});
+}
+
+#[test]
+fn failed_calls_emit_events() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+		// The proxy traps on the unknown extension.
+		assert_eq!(schedule(&proxy, 3, None, GAS, u32::MAX, vec![]), (0, Some(0)));
+
+		run_to_block(3);
+		assert!(System::events().iter().any(|record| matches!(
+			&record.event,
+			Event::ContractScheduler(SchedulerEvent::CallFailed(0, contract, _)) if *contract == proxy
+		)));
+	});
+}
+
+#[test]
+fn invalid_schedules_report_errors() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+		let other = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 1);
+		let too_much_gas = 1_000_000_000_000;
+
+		assert_eq!(
+			schedule(&proxy, 3, None, too_much_gas, 0, vec![]),
+			(SchedulerError::GasLimitTooHigh as u32, None)
+		);
+		assert_eq!(
+			schedule(&proxy, 1, None, GAS, 0, vec![]),
+			(SchedulerError::InPast as u32, None)
+		);
+		assert_eq!(
+			schedule(&proxy, 3, Some((0, 2)), GAS, 0, vec![]),
+			(SchedulerError::InvalidPeriod as u32, None)
+		);
+		let too_long = vec![0; MaxScheduledDataLen::get() as usize];
+		assert_eq!(
+			schedule(&proxy, 3, None, GAS, 0, too_long),
+			(SchedulerError::DataTooLong as u32, None)
+		);
+
+		assert_eq!(schedule(&proxy, 3, None, GAS, 0, vec![]), (0, Some(0)));
+		let cancel = |proxy: &AccountId| {
+			call_extension(proxy, Keyring::Alice, func_id(SCHEDULER, CANCEL), 0u64)
+				.unwrap()
+				.0
+		};
+		assert_eq!(cancel(&other), SchedulerError::NotOwner as u32);
+		assert_eq!(cancel(&proxy), 0);
+		assert_eq!(cancel(&proxy), SchedulerError::UnknownSchedule as u32);
+	});
+}
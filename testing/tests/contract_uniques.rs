@@ -0,0 +1,104 @@
+use node_template_runtime::{
+	chain_extension::{uniques::*, UNIQUES},
+	AccountId, Event, Origin, System, Uniques, UniquesBridge,
+};
+use node_template_testing::{
+	contracts::{call_extension, deploy_wat, read_extension, EXTENSION_PROXY},
+	ext::ExtBuilder,
+	Keyring,
+};
+use std::convert::TryInto;
+
+const CLASS: u32 = 5;
+
+fn func_id(function: u16) -> u32 {
+	(UNIQUES as u32) << 16 | function as u32
+}
+
+/// Deploy two extension proxies and create `CLASS` owned by Alice with the first as minter.
+fn setup() -> (AccountId, AccountId) {
+	let alice = Keyring::Alice.to_account_id();
+	let minter = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+	let other = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 1);
+	Uniques::force_create(Origin::root(), CLASS, alice.clone().into(), true).unwrap();
+	UniquesBridge::add_minter(Origin::signed(alice), CLASS, minter.clone()).unwrap();
+	(minter, other)
+}
+
+fn mint(proxy: &AccountId, instance: u32, owner: &AccountId) -> u32 {
+	call_extension(proxy, Keyring::Alice, func_id(MINT), (CLASS, instance, owner))
+		.unwrap()
+		.0
+}
+
+fn owner_of(proxy: &AccountId, instance: u32) -> Option<AccountId> {
+	read_extension(proxy, Keyring::Alice, func_id(OWNER_OF), (CLASS, instance))
+}
+
+#[test]
+fn only_designated_contracts_mint() {
+	ExtBuilder::default().build().execute_with(|| {
+		let (minter, other) = setup();
+		let bob = Keyring::Bob.to_account_id();
+
+		assert_eq!(mint(&other, 1, &bob), UniquesError::NotMinter as u32);
+		assert_eq!(owner_of(&other, 1), None);
+
+		assert_eq!(mint(&minter, 1, &bob), 0);
+		assert_eq!(owner_of(&other, 1), Some(bob.clone()));
+		assert_eq!(mint(&minter, 1, &bob), UniquesError::AlreadyExists as u32);
+
+		// The event names the contract that minted the item.
+		let minted = pallet_uniques_bridge::Event::Minted(minter, CLASS, 1, bob);
+		assert!(System::events()
+			.iter()
+			.any(|record| record.event == Event::UniquesBridge(minted.clone())));
+	});
+}
+
+#[test]
+fn contracts_transfer_their_items() {
+	ExtBuilder::default().build().execute_with(|| {
+		let (minter, other) = setup();
+		let bob = Keyring::Bob.to_account_id();
+		assert_eq!(mint(&minter, 1, &other), 0);
+
+		let transfer = |proxy: &AccountId| {
+			call_extension(proxy, Keyring::Alice, func_id(TRANSFER), (CLASS, 1u32, &bob))
+				.unwrap()
+				.0
+		};
+		assert_eq!(transfer(&minter), UniquesError::NotOwner as u32);
+		assert_eq!(transfer(&other), 0);
+		assert_eq!(owner_of(&minter, 1), Some(bob));
+	});
+}
+
+#[test]
+fn contracts_read_attributes() {
+	ExtBuilder::default().build().execute_with(|| {
+		let (minter, _) = setup();
+		let alice = Keyring::Alice.to_account_id();
+		assert_eq!(mint(&minter, 1, &alice), 0);
+
+		let set_attribute = |instance: Option<u32>, value: &[u8]| {
+			Uniques::set_attribute(
+				Origin::signed(alice.clone()),
+				CLASS,
+				instance,
+				b"level".to_vec().try_into().unwrap(),
+				value.to_vec().try_into().unwrap(),
+			)
+			.unwrap()
+		};
+		set_attribute(None, b"class");
+		set_attribute(Some(1), b"item");
+
+		let attribute = |instance: Option<u32>, key: &[u8]| -> Option<Vec<u8>> {
+			read_extension(&minter, Keyring::Alice, func_id(ATTRIBUTE), (CLASS, instance, key))
+		};
+		assert_eq!(attribute(None, b"level"), Some(b"class".to_vec()));
+		assert_eq!(attribute(Some(1), b"level"), Some(b"item".to_vec()));
+		assert_eq!(attribute(Some(1), b"rarity"), None);
+	});
+}
@@ -0,0 +1,62 @@
+use codec::Encode;
+use node_template::decode::Metadata;
+use node_template_runtime::{pallet_template, BalancesCall, Call, Runtime};
+use node_template_testing::Keyring;
+use serde_json::json;
+use sp_core::crypto::Ss58Codec;
+
+fn metadata() -> Metadata {
+	Metadata::from_bytes(&Runtime::metadata().encode()).unwrap()
+}
+
+#[test]
+fn encodes_calls_like_the_runtime() {
+	let metadata = metadata();
+	let bob = Keyring::Bob.to_account_id();
+
+	let transfer = metadata
+		.encode_call(
+			"Balances",
+			"transfer",
+			&json!({ "dest": bob.to_ss58check(), "value": "1000000000000000000000" }),
+		)
+		.unwrap();
+	let expected = Call::Balances(BalancesCall::transfer {
+		dest: bob.clone().into(),
+		value: 1_000_000_000_000_000_000_000,
+	});
+	assert_eq!(transfer, expected.encode());
+
+	let do_something = metadata.encode_call("TemplateModule", "do_something", &json!(42)).unwrap();
+	let expected = Call::TemplateModule(pallet_template::Call::do_something { something: 42 });
+	assert_eq!(do_something, expected.encode());
+
+	let remark = metadata
+		.encode_call("System", "remark", &json!({ "remark": "0x0102" }))
+		.unwrap();
+	let expected = Call::System(frame_system::Call::remark { remark: vec![1, 2] });
+	assert_eq!(remark, expected.encode());
+}
+
+#[test]
+fn decoding_reverses_encoding() {
+	let metadata = metadata();
+	let bob = Keyring::Bob.to_account_id().to_ss58check();
+	let args = json!({ "dest": { "Id": bob }, "value": 1000 });
+	let encoded = metadata.encode_call("Balances", "transfer_keep_alive", &args).unwrap();
+
+	let (pallet, call) = metadata.decode_call(&mut &encoded[..]).unwrap();
+	assert_eq!(pallet, "Balances");
+	// Unnamed fields decode as arrays, which encode again.
+	let decoded = json!({ "dest": { "Id": [bob] }, "value": 1000 });
+	assert_eq!(call.to_json(), json!({ "transfer_keep_alive": decoded }));
+	assert_eq!(metadata.encode_call("Balances", "transfer_keep_alive", &decoded).unwrap(), encoded);
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+	let metadata = metadata();
+	assert!(metadata.encode_call("Balances", "transfer", &json!({ "value": 1 })).is_err());
+	assert!(metadata.encode_call("TemplateModule", "do_something", &json!(-1)).is_err());
+	assert!(metadata.encode_call("TemplateModule", "do_nothing", &json!(null)).is_err());
+}
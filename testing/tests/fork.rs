@@ -0,0 +1,103 @@
+use node_template::{
+	chain_spec::{authority_keys_from_seed, get_account_id_from_seed, testnet_genesis, ChainSpec},
+	fork::fork_config,
+};
+use node_template_runtime::{Aura, Balances, Grandpa, Sudo, WASM_BINARY};
+use sc_service::{ChainSpec as ChainSpecT, ChainType};
+use sp_core::{sr25519, storage::well_known_keys};
+use sp_runtime::{BuildStorage, Storage};
+use std::io::Write;
+
+fn staging_spec() -> ChainSpec {
+	ChainSpec::from_genesis(
+		"Staging",
+		"staging",
+		ChainType::Live,
+		|| {
+			testnet_genesis(
+				WASM_BINARY.unwrap(),
+				vec![authority_keys_from_seed("Charlie"), authority_keys_from_seed("Dave")],
+				get_account_id_from_seed::<sr25519::Public>("Charlie"),
+				vec![get_account_id_from_seed::<sr25519::Public>("Eve")],
+				false,
+			)
+		},
+		vec![],
+		None,
+		None,
+		None,
+		None,
+	)
+}
+
+fn write_state(contents: &str) -> tempfile::NamedTempFile {
+	let mut file = tempfile::NamedTempFile::new().unwrap();
+	file.write_all(contents.as_bytes()).unwrap();
+	file
+}
+
+fn assert_run_by_alice(storage: Storage) {
+	let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+	let eve = get_account_id_from_seed::<sr25519::Public>("Eve");
+	let (aura, grandpa) = authority_keys_from_seed("Alice");
+
+	sp_io::TestExternalities::new(storage).execute_with(|| {
+		assert_eq!(Sudo::key(), alice);
+		assert_eq!(Aura::authorities().to_vec(), vec![aura]);
+		assert_eq!(Grandpa::grandpa_authorities(), vec![(grandpa, 1)]);
+		assert_eq!(Balances::free_balance(&alice), 1 << 60);
+		// Everything else is kept.
+		assert_eq!(Balances::free_balance(&eve), 1 << 60);
+		assert_eq!(Balances::total_issuance(), 2 << 60);
+		assert_eq!(
+			sp_io::storage::get(well_known_keys::CODE).as_deref(),
+			Some(WASM_BINARY.unwrap())
+		);
+	});
+}
+
+#[test]
+fn forks_an_exported_chain_spec() {
+	let state = write_state(&staging_spec().as_json(true).unwrap());
+
+	let fork = fork_config(state.path()).unwrap();
+	assert!(fork.id().starts_with("fork-"));
+	// Forking the same state again reuses the database of the fork.
+	assert_eq!(fork_config(state.path()).unwrap().id(), fork.id());
+	assert_eq!(fork.name(), "Fork of Staging");
+	assert_eq!(fork.chain_type(), ChainType::Development);
+
+	assert_run_by_alice(fork.build_storage().unwrap());
+}
+
+#[test]
+fn forks_a_raw_storage_dump() {
+	let top = staging_spec().build_storage().unwrap().top;
+	let mut dump = top
+		.into_iter()
+		.map(|(key, value)| {
+			(
+				format!("0x{}", hex::encode(key)),
+				serde_json::json!(format!("0x{}", hex::encode(value))),
+			)
+		})
+		.collect::<serde_json::Map<_, _>>();
+	let state = write_state(&serde_json::Value::Object(dump.clone()).to_string());
+
+	let fork = fork_config(state.path()).unwrap();
+	assert_run_by_alice(fork.build_storage().unwrap());
+
+	// The id depends on the state only, not on how it was exported.
+	let spec = write_state(&staging_spec().as_json(true).unwrap());
+	assert_eq!(fork_config(spec.path()).unwrap().id(), fork.id());
+	// Forks of different states do not share a database.
+	dump.insert("0x00".into(), serde_json::json!("0x00"));
+	let other = write_state(&serde_json::Value::Object(dump).to_string());
+	assert_ne!(fork_config(other.path()).unwrap().id(), fork.id());
+}
+
+#[test]
+fn rejects_other_files() {
+	let state = write_state("[1, 2, 3]");
+	assert!(fork_config(state.path()).is_err());
+}
@@ -0,0 +1,85 @@
+use node_template_runtime::{
+	chain_extension::{indices::*, INDICES},
+	AccountId, Address, Balances, BalancesCall, Call, Executive, Indices, Origin,
+};
+use node_template_testing::{
+	contracts::{deploy_wat, read_extension, EXTENSION_PROXY},
+	ext::{apply_signed, signed_extrinsic, ExtBuilder},
+	Keyring,
+};
+use sp_runtime::MultiAddress;
+
+const ALICE_INDEX: u32 = 0;
+const BOB_INDEX: u32 = 1;
+
+#[test]
+fn endowed_accounts_have_indices_on_dev_chains() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(Indices::lookup_index(ALICE_INDEX), Some(Keyring::Alice.to_account_id()));
+		assert_eq!(Indices::lookup_index(BOB_INDEX), Some(Keyring::Bob.to_account_id()));
+	});
+}
+
+#[test]
+fn transfers_take_indices() {
+	ExtBuilder::default().build().execute_with(|| {
+		let bob = Keyring::Bob.to_account_id();
+		let before = Balances::free_balance(&bob);
+
+		let transfer =
+			BalancesCall::transfer { dest: MultiAddress::Index(BOB_INDEX), value: 1_000 };
+		assert_eq!(apply_signed(Call::Balances(transfer), Keyring::Alice), Ok(Ok(())));
+		assert_eq!(Balances::free_balance(&bob), before + 1_000);
+
+		let unknown = BalancesCall::transfer { dest: MultiAddress::Index(99), value: 1_000 };
+		assert!(apply_signed(Call::Balances(unknown), Keyring::Alice).unwrap().is_err());
+	});
+}
+
+#[test]
+fn signers_may_be_given_by_index() {
+	ExtBuilder::default().build().execute_with(|| {
+		let ferdie = Keyring::Ferdie.to_account_id();
+		// The signature does not cover the address of the signer, only its account.
+		let mut extrinsic = signed_extrinsic(
+			Call::Balances(BalancesCall::transfer { dest: ferdie.clone().into(), value: 1_000 }),
+			Keyring::Bob,
+		);
+		extrinsic.signature.as_mut().unwrap().0 = MultiAddress::Index(BOB_INDEX);
+		assert_eq!(Executive::apply_extrinsic(extrinsic), Ok(Ok(())));
+		assert_eq!(Balances::free_balance(&ferdie), 1_000);
+	});
+}
+
+#[test]
+fn contracts_resolve_indices() {
+	ExtBuilder::default().build().execute_with(|| {
+		let proxy = deploy_wat(EXTENSION_PROXY, Keyring::Alice, 0);
+		let alice = Keyring::Alice.to_account_id();
+		let func_id = |function: u16| (INDICES as u32) << 16 | function as u32;
+
+		let by_index: Option<AccountId> =
+			read_extension(&proxy, Keyring::Alice, func_id(LOOKUP_INDEX), ALICE_INDEX);
+		assert_eq!(by_index, Some(alice.clone()));
+		let unknown: Option<AccountId> =
+			read_extension(&proxy, Keyring::Alice, func_id(LOOKUP_INDEX), 99u32);
+		assert_eq!(unknown, None);
+
+		let addresses: [Address; 2] = [MultiAddress::Index(ALICE_INDEX), alice.clone().into()];
+		for address in addresses {
+			let account: Option<AccountId> =
+				read_extension(&proxy, Keyring::Alice, func_id(LOOKUP_ADDRESS), address);
+			assert_eq!(account, Some(alice.clone()));
+		}
+		let raw: Address = MultiAddress::Raw(vec![0; 100]);
+		let account: Option<AccountId> =
+			read_extension(&proxy, Keyring::Alice, func_id(LOOKUP_ADDRESS), raw);
+		assert_eq!(account, None);
+
+		// Indices claimed later resolve as well.
+		Indices::claim(Origin::signed(alice.clone()), 7).unwrap();
+		let claimed: Option<AccountId> =
+			read_extension(&proxy, Keyring::Alice, func_id(LOOKUP_INDEX), 7u32);
+		assert_eq!(claimed, Some(alice));
+	});
+}
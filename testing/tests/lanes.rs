@@ -0,0 +1,88 @@
+use node_template::{
+	extrinsic::{create_signed, ExtrinsicParams},
+	lanes::{excess, lane_of, LaneLimit},
+};
+use node_template_runtime::{
+	lanes::Lane, pallet_template, BalancesCall, Call, Executive, System, VERSION,
+};
+use node_template_testing::{ext::ExtBuilder, Keyring, TestNode};
+use sc_transaction_pool_api::{InPoolTransaction, TransactionPool};
+use sp_runtime::{generic::Era, transaction_validity::TransactionSource};
+
+fn do_something(something: u32) -> Call {
+	Call::TemplateModule(pallet_template::Call::do_something { something })
+}
+
+fn transfer(value: u128) -> Call {
+	Call::Balances(BalancesCall::transfer { dest: Keyring::Ferdie.to_account_id().into(), value })
+}
+
+#[test]
+fn lanes_order_the_pool() {
+	let node = TestNode::new();
+	node.submit(transfer(1_000), Keyring::Alice).unwrap();
+	node.submit(do_something(1), Keyring::Bob).unwrap();
+
+	let ready = node.transaction_pool().ready().collect::<Vec<_>>();
+	let lanes = ready.iter().map(|tx| lane_of(tx.data())).collect::<Vec<_>>();
+	assert_eq!(lanes, vec![Lane::Template, Lane::Default]);
+	assert!(*ready[0].priority() >= Lane::Template.priority());
+	assert!(*ready[1].priority() < Lane::Template.priority());
+	assert_eq!(*ready[0].longevity(), Lane::Template.longevity());
+}
+
+#[test]
+fn tips_do_not_leave_the_lane() {
+	ExtBuilder::default().build().execute_with(|| {
+		let genesis_hash = System::block_hash(0);
+		// Half of the balance of Alice, its fee priority is far beyond the distance of two lanes.
+		let params = ExtrinsicParams {
+			nonce: 0,
+			tip: 1 << 59,
+			sponsor: None,
+			era: Era::Immortal,
+			era_block_hash: genesis_hash,
+			genesis_hash,
+			spec_version: VERSION.spec_version,
+			transaction_version: VERSION.transaction_version,
+		};
+		let xt = create_signed(transfer(1_000), &Keyring::Alice.pair(), &params);
+		let validity =
+			Executive::validate_transaction(TransactionSource::External, xt, genesis_hash).unwrap();
+
+		assert!(validity.priority > Lane::Default.priority());
+		assert!(validity.priority < Lane::Template.priority());
+	});
+}
+
+#[test]
+fn parses_lane_limits() {
+	assert_eq!(
+		"template=100:20".parse(),
+		Ok(LaneLimit { lane: Lane::Template, ready: 100, future: 20 })
+	);
+	assert_eq!("oracle=50".parse(), Ok(LaneLimit { lane: Lane::Oracle, ready: 50, future: 5 }));
+	assert!("unknown=1".parse::<LaneLimit>().is_err());
+	assert!("template".parse::<LaneLimit>().is_err());
+	assert!("template=1:x".parse::<LaneLimit>().is_err());
+}
+
+#[test]
+fn finds_transactions_beyond_lane_limits() {
+	let node = TestNode::new();
+	node.submit(do_something(1), Keyring::Alice).unwrap();
+	node.submit(do_something(2), Keyring::Bob).unwrap();
+	node.submit(transfer(1_000), Keyring::Charlie).unwrap();
+
+	let ready = node
+		.transaction_pool()
+		.ready()
+		.map(|tx| (*tx.hash(), tx.data().clone()))
+		.collect::<Vec<_>>();
+	let limited = |lane| (lane == Lane::Template).then(|| 1);
+	let removed = excess(ready.iter().map(|(hash, xt)| (*hash, xt)), limited);
+
+	let template = ready.iter().filter(|(_, xt)| lane_of(xt) == Lane::Template).collect::<Vec<_>>();
+	assert_eq!(template.len(), 2);
+	assert_eq!(removed, vec![template[1].0]);
+}
@@ -0,0 +1,90 @@
+use futures::executor::block_on;
+use node_template::managed_tx::{ManagedStatus, TxManager};
+use node_template_runtime::{pallet_template, Call};
+use node_template_testing::{Keyring, TestNode};
+use sp_api::ProvideRuntimeApi;
+use sp_core::crypto::key_types;
+use sp_keystore::{testing::KeyStore, SyncCryptoStore};
+use sp_runtime::generic::BlockId;
+use std::{
+	sync::{mpsc, Arc},
+	thread,
+	time::Duration,
+};
+use substrate_frame_rpc_system::AccountNonceApi;
+
+fn do_something(something: u32) -> Call {
+	Call::TemplateModule(pallet_template::Call::do_something { something })
+}
+
+#[test]
+fn allocates_nonces_and_tracks_to_finality() {
+	let node = TestNode::new();
+	let keystore = Arc::new(KeyStore::new());
+	SyncCryptoStore::sr25519_generate_new(&*keystore, key_types::ACCOUNT, Some("//Alice")).unwrap();
+	let manager =
+		Arc::new(TxManager::new(node.client().clone(), node.transaction_pool().clone(), keystore));
+	assert!(manager.has_key(&Keyring::Alice.to_account_id()));
+	assert!(!manager.has_key(&Keyring::Bob.to_account_id()));
+
+	let (sender, statuses) = mpsc::channel();
+	let next = || statuses.recv_timeout(Duration::from_secs(10)).unwrap();
+	for something in [1, 2] {
+		let (manager, sender) = (manager.clone(), sender.clone());
+		thread::spawn(move || {
+			let alice = Keyring::Alice.to_account_id();
+			block_on(manager.submit_and_track(do_something(something), alice, |status| {
+				sender.send(status).is_ok()
+			}))
+		});
+	}
+
+	let mut nonces = [next(), next()]
+		.iter()
+		.map(|status| match status {
+			ManagedStatus::Submitted { nonce, attempt: 1, .. } => *nonce,
+			other => panic!("Unexpected status {:?}", other),
+		})
+		.collect::<Vec<_>>();
+	nonces.sort_unstable();
+	assert_eq!(nonces, vec![0, 1]);
+
+	let hash = node.seal_and_finalize_block().unwrap();
+	let mut finalized = 0;
+	while finalized < 2 {
+		match next() {
+			ManagedStatus::InBlock { block } => assert_eq!(block, hash),
+			ManagedStatus::Finalized { block } => {
+				assert_eq!(block, hash);
+				finalized += 1;
+			},
+			other => panic!("Unexpected status {:?}", other),
+		}
+	}
+	let nonce = node
+		.client()
+		.runtime_api()
+		.account_nonce(&BlockId::Hash(hash), Keyring::Alice.to_account_id())
+		.unwrap();
+	assert_eq!(nonce, 2);
+}
+
+#[test]
+fn rejected_transactions_fail() {
+	let node = TestNode::new();
+	let keystore = Arc::new(KeyStore::new());
+	SyncCryptoStore::sr25519_generate_new(&*keystore, key_types::ACCOUNT, Some("//Ferdie"))
+		.unwrap();
+	let manager = TxManager::new(node.client().clone(), node.transaction_pool().clone(), keystore);
+	// Ferdie has no funds on the dev chain, so the pool rejects the transaction.
+	let mut statuses = Vec::new();
+	block_on(manager.submit_and_track(
+		do_something(1),
+		Keyring::Ferdie.to_account_id(),
+		|status| {
+			statuses.push(status);
+			true
+		},
+	));
+	assert!(matches!(&statuses[..], [ManagedStatus::Failed { .. }]));
+}
@@ -0,0 +1,87 @@
+use codec::Encode;
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed, RuntimeMetadataV14};
+use node_template::{
+	decode::Metadata,
+	metadata_diff::{runtime_version, Category, ChangeKind, MetadataDiff},
+};
+use node_template_runtime::{Runtime, VERSION};
+
+fn native() -> Metadata {
+	Metadata::from_bytes(&Runtime::metadata().encode()).unwrap()
+}
+
+/// The native metadata with `f` applied.
+fn modified(f: impl FnOnce(&mut RuntimeMetadataV14)) -> Metadata {
+	let mut prefixed: RuntimeMetadataPrefixed = Runtime::metadata();
+	match &mut prefixed.1 {
+		RuntimeMetadata::V14(metadata) => f(metadata),
+		_ => unreachable!("the runtime has V14 metadata"),
+	}
+	Metadata::from_bytes(&prefixed.encode()).unwrap()
+}
+
+fn without_template(metadata: &mut RuntimeMetadataV14) {
+	metadata.pallets.retain(|pallet| pallet.name != "TemplateModule");
+}
+
+#[test]
+fn same_runtime_has_no_changes() {
+	let diff = MetadataDiff::new(&native(), &native());
+	assert!(diff.changes.is_empty());
+	assert!(!diff.requires_transaction_version_bump());
+}
+
+#[test]
+fn removed_pallet_breaks_signers() {
+	let diff = MetadataDiff::new(&native(), &modified(without_template));
+
+	let removed = |category, item: &str| {
+		diff.changes.iter().any(|change| {
+			change.kind == ChangeKind::Removed && change.category == category && change.item == item
+		})
+	};
+	assert!(removed(Category::Pallet, "TemplateModule"));
+	assert!(removed(Category::Call, "TemplateModule::do_something"));
+	assert!(removed(Category::Event, "TemplateModule::SomethingStored"));
+	assert!(removed(Category::Storage, "TemplateModule::Something"));
+	assert!(diff.requires_transaction_version_bump());
+	assert!(diff.breaking_changes().all(|change| change.category == Category::Call));
+}
+
+#[test]
+fn added_pallet_does_not_break_signers() {
+	let diff = MetadataDiff::new(&modified(without_template), &native());
+	assert!(diff.changes.iter().all(|change| change.kind == ChangeKind::Added));
+	assert!(!diff.requires_transaction_version_bump());
+}
+
+#[test]
+fn moved_pallet_with_calls_breaks_signers() {
+	let moved = modified(|metadata| {
+		let balances = metadata.pallets.iter_mut().find(|p| p.name == "Balances").unwrap();
+		balances.index = 200;
+	});
+	let diff = MetadataDiff::new(&native(), &moved);
+
+	assert_eq!(diff.changes.len(), 1);
+	assert_eq!(diff.changes[0].category, Category::Pallet);
+	assert_eq!(diff.changes[0].item, "Balances");
+	assert!(diff.requires_transaction_version_bump());
+}
+
+#[test]
+fn changed_signed_extensions_break_signers() {
+	let reordered = modified(|metadata| metadata.extrinsic.signed_extensions.swap(0, 1));
+	let diff = MetadataDiff::new(&native(), &reordered);
+
+	assert_eq!(diff.changes.len(), 1);
+	assert_eq!(diff.changes[0].category, Category::SignedExtension);
+	assert!(diff.requires_transaction_version_bump());
+}
+
+#[test]
+fn reads_the_runtime_version() {
+	let version = runtime_version(&native()).unwrap();
+	assert_eq!(version.spec_version, VERSION.spec_version);
+	assert_eq!(version.transaction_version, VERSION.transaction_version);
+}
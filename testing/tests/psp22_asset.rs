@@ -0,0 +1,123 @@
+use codec::{Decode, Encode};
+use node_template_runtime::{AccountId, Assets, Balance, Origin};
+use node_template_testing::{
+	contracts::{call, deploy, PSP22_ASSET},
+	ext::ExtBuilder,
+	Keyring,
+};
+
+const ASSET: u32 = 1;
+
+/// The selectors of the constructor and the messages of the token.
+const NEW: [u8; 4] = [0x9b, 0xae, 0x9d, 0x5e];
+const BALANCE_OF: [u8; 4] = [0x65, 0x68, 0x38, 0x2f];
+const ALLOWANCE: [u8; 4] = [0x4d, 0x47, 0xd9, 0x21];
+const TRANSFER: [u8; 4] = [0xdb, 0x20, 0xf9, 0xf5];
+const TRANSFER_FROM: [u8; 4] = [0x54, 0xb3, 0xc7, 0x6e];
+const APPROVE: [u8; 4] = [0xb2, 0x0f, 0x1b, 0xbd];
+
+/// The first variants of the `PSP22Error` of the contract, with the same encoding.
+#[derive(Debug, PartialEq, Eq, Decode)]
+enum PSP22Error {
+	Custom(String),
+	InsufficientBalance,
+	InsufficientAllowance,
+}
+
+/// Call the message `selector` of `token` by `caller` with `args`, decoding the output as `T`.
+fn message<T: Decode>(
+	token: &AccountId,
+	caller: Keyring,
+	selector: [u8; 4],
+	args: impl Encode,
+) -> T {
+	let mut input = selector.to_vec();
+	args.encode_to(&mut input);
+	let output = call(token, caller, input).expect("the token does not trap");
+	T::decode(&mut &output.data.0[..]).expect("the message returns a `T`")
+}
+
+/// Create `ASSET`, mint 500 to Bob, deploy a token for it and let the token transfer 300 of Bob.
+fn setup() -> AccountId {
+	let alice = Keyring::Alice.to_account_id();
+	let bob = Keyring::Bob.to_account_id();
+	Assets::force_create(Origin::root(), ASSET, alice.clone().into(), true, 1).unwrap();
+	Assets::mint(Origin::signed(alice), ASSET, bob.clone().into(), 500).unwrap();
+
+	let token = deploy(PSP22_ASSET.to_vec(), Keyring::Alice, (NEW, ASSET).encode(), 0);
+	Assets::approve_transfer(Origin::signed(bob), ASSET, token.clone().into(), 300).unwrap();
+	token
+}
+
+fn balance_of(token: &AccountId, owner: Keyring) -> Balance {
+	message(token, Keyring::Alice, BALANCE_OF, owner.to_account_id())
+}
+
+fn allowance(token: &AccountId, owner: Keyring, spender: Keyring) -> Balance {
+	message(token, Keyring::Alice, ALLOWANCE, (owner.to_account_id(), spender.to_account_id()))
+}
+
+#[test]
+fn holders_transfer_through_the_token() {
+	ExtBuilder::default().build().execute_with(|| {
+		let token = setup();
+
+		let transfer = |caller, to: Keyring, value: Balance| {
+			message::<Result<(), PSP22Error>>(
+				&token,
+				caller,
+				TRANSFER,
+				(to.to_account_id(), value, Vec::<u8>::new()),
+			)
+		};
+		assert_eq!(transfer(Keyring::Bob, Keyring::Charlie, 100), Ok(()));
+		assert_eq!(Assets::balance(ASSET, Keyring::Bob.to_account_id()), 400);
+		assert_eq!(balance_of(&token, Keyring::Bob), 400);
+		assert_eq!(balance_of(&token, Keyring::Charlie), 100);
+
+		assert_eq!(
+			transfer(Keyring::Bob, Keyring::Charlie, 500),
+			Err(PSP22Error::InsufficientBalance)
+		);
+		// Charlie did not approve the token in `pallet_assets`.
+		assert_eq!(
+			transfer(Keyring::Charlie, Keyring::Bob, 50),
+			Err(PSP22Error::Custom("ContractNotApproved".into()))
+		);
+		assert_eq!(balance_of(&token, Keyring::Charlie), 100);
+	});
+}
+
+#[test]
+fn spenders_transfer_their_allowance() {
+	ExtBuilder::default().build().execute_with(|| {
+		let token = setup();
+
+		let approve: Result<(), PSP22Error> =
+			message(&token, Keyring::Bob, APPROVE, (Keyring::Dave.to_account_id(), 50 as Balance));
+		assert_eq!(approve, Ok(()));
+		assert_eq!(allowance(&token, Keyring::Bob, Keyring::Dave), 50);
+
+		let transfer_from = |value: Balance| {
+			message::<Result<(), PSP22Error>>(
+				&token,
+				Keyring::Dave,
+				TRANSFER_FROM,
+				(
+					Keyring::Bob.to_account_id(),
+					Keyring::Eve.to_account_id(),
+					value,
+					Vec::<u8>::new(),
+				),
+			)
+		};
+		assert_eq!(transfer_from(30), Ok(()));
+		assert_eq!(Assets::balance(ASSET, Keyring::Bob.to_account_id()), 470);
+		assert_eq!(Assets::balance(ASSET, Keyring::Eve.to_account_id()), 30);
+		assert_eq!(allowance(&token, Keyring::Bob, Keyring::Dave), 20);
+
+		assert_eq!(transfer_from(30), Err(PSP22Error::InsufficientAllowance));
+		assert_eq!(Assets::balance(ASSET, Keyring::Eve.to_account_id()), 30);
+		assert_eq!(allowance(&token, Keyring::Bob, Keyring::Dave), 20);
+	});
+}
@@ -0,0 +1,64 @@
+use node_template_runtime::{
+	pallet_template, Aura, Balances, BalancesCall, Call, Sudo, System, TemplateModule, Timestamp,
+	SLOT_DURATION,
+};
+use node_template_testing::{
+	ext::{apply_signed, run_to_block, ExtBuilder},
+	Keyring,
+};
+
+#[test]
+fn genesis_matches_the_dev_chain() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(System::block_number(), 1);
+		assert_eq!(Balances::free_balance(Keyring::Alice.to_account_id()), 1 << 60);
+		assert_eq!(Balances::free_balance(Keyring::Charlie.to_account_id()), 0);
+		assert_eq!(Sudo::key(), Keyring::Alice.to_account_id());
+		assert_eq!(Aura::authorities().len(), 1);
+	});
+}
+
+#[test]
+fn builder_overrides_genesis() {
+	ExtBuilder::default()
+		.authorities(&["Alice", "Bob"])
+		.sudo(Keyring::Bob.to_account_id())
+		.endowed_accounts(vec![Keyring::Charlie.to_account_id()])
+		.build()
+		.execute_with(|| {
+			assert_eq!(Aura::authorities().len(), 2);
+			assert_eq!(Sudo::key(), Keyring::Bob.to_account_id());
+			assert_eq!(Balances::free_balance(Keyring::Alice.to_account_id()), 0);
+			assert_eq!(Balances::free_balance(Keyring::Charlie.to_account_id()), 1 << 60);
+		});
+}
+
+#[test]
+fn signed_transfer_pays_fees() {
+	ExtBuilder::default().build().execute_with(|| {
+		let alice = Keyring::Alice.to_account_id();
+		let bob = Keyring::Bob.to_account_id();
+
+		let call =
+			Call::Balances(BalancesCall::transfer { dest: bob.clone().into(), value: 1_000 });
+		assert_eq!(apply_signed(call, Keyring::Alice), Ok(Ok(())));
+
+		assert_eq!(Balances::free_balance(&bob), (1 << 60) + 1_000);
+		assert!(Balances::free_balance(&alice) < (1 << 60) - 1_000);
+		assert_eq!(System::account_nonce(&alice), 1);
+	});
+}
+
+#[test]
+fn template_value_survives_blocks() {
+	ExtBuilder::default().build().execute_with(|| {
+		let call = Call::TemplateModule(pallet_template::Call::do_something { something: 7 });
+		assert_eq!(apply_signed(call, Keyring::Bob), Ok(Ok(())));
+
+		run_to_block(3);
+
+		assert_eq!(System::block_number(), 3);
+		assert_eq!(Timestamp::now(), 3 * SLOT_DURATION);
+		assert_eq!(TemplateModule::something(Keyring::Bob.to_account_id()), Some(7));
+	});
+}
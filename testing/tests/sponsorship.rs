@@ -0,0 +1,55 @@
+use node_template_runtime::{pallet_template, Balances, BalancesCall, Call, Sponsorship, System};
+use node_template_testing::{
+	ext::{apply_signed, apply_sponsored, ExtBuilder},
+	Keyring,
+};
+use pallet_sponsorship::{InvalidSponsorship, Policy};
+use sp_runtime::transaction_validity::TransactionValidityError;
+
+fn do_something(something: u32) -> Call {
+	Call::TemplateModule(pallet_template::Call::do_something { something })
+}
+
+fn set_policy(allowance: u128) -> Call {
+	Call::Sponsorship(pallet_sponsorship::Call::set_policy {
+		policy: Policy { allowance, calls: vec![], expires: 100 },
+	})
+}
+
+#[test]
+fn sponsor_pays_template_calls() {
+	ExtBuilder::default().build().execute_with(|| {
+		let alice = Keyring::Alice.to_account_id();
+		let bob = Keyring::Bob.to_account_id();
+		assert_eq!(apply_signed(set_policy(1 << 40), Keyring::Alice), Ok(Ok(())));
+
+		let alice_before = Balances::free_balance(&alice);
+		let bob_before = Balances::total_balance(&bob);
+		assert_eq!(apply_sponsored(do_something(7), Keyring::Bob, Keyring::Alice), Ok(Ok(())));
+
+		// The storage deposit is only reserved, the sponsor paid all fees.
+		assert_eq!(Balances::total_balance(&bob), bob_before);
+		let paid = alice_before - Balances::free_balance(&alice);
+		assert!(paid > 0);
+		assert_eq!(Sponsorship::spent(&alice, &bob), paid);
+		assert_eq!(System::account_nonce(&bob), 1);
+	});
+}
+
+#[test]
+fn sponsorship_is_limited_to_sponsorable_calls_and_allowances() {
+	ExtBuilder::default().build().execute_with(|| {
+		let alice = Keyring::Alice.to_account_id();
+		assert_eq!(apply_signed(set_policy(1), Keyring::Alice), Ok(Ok(())));
+
+		let transfer = Call::Balances(BalancesCall::transfer { dest: alice.into(), value: 1 });
+		assert_eq!(
+			apply_sponsored(transfer, Keyring::Bob, Keyring::Alice),
+			Err(TransactionValidityError::from(InvalidSponsorship::NotSponsorable))
+		);
+		assert_eq!(
+			apply_sponsored(do_something(7), Keyring::Bob, Keyring::Alice),
+			Err(TransactionValidityError::from(InvalidSponsorship::AllowanceExceeded))
+		);
+	});
+}
@@ -0,0 +1,67 @@
+use node_template_runtime::{pallet_template, BalancesCall, Call, Event};
+use node_template_testing::{Keyring, TestNode};
+
+#[test]
+fn do_something_is_included_and_finalized() {
+	let node = TestNode::new();
+
+	let mut watcher = node
+		.submit(
+			Call::TemplateModule(pallet_template::Call::do_something { something: 42 }),
+			Keyring::Alice,
+		)
+		.unwrap();
+	let hash = node.seal_and_finalize_block().unwrap();
+
+	assert_eq!(watcher.wait_for_inclusion(), Ok(hash));
+	assert_eq!(watcher.wait_for_finality(), Ok(hash));
+	let alice = Keyring::Alice.to_account_id();
+	assert_eq!(node.storage_map::<_, u32>("TemplateModule", "Something", &alice), Some(42));
+	assert!(node.events().iter().any(|record| record.event ==
+		Event::TemplateModule(pallet_template::Event::SomethingStored(42, alice))));
+}
+
+#[test]
+fn failing_call_is_included_with_error() {
+	let node = TestNode::new();
+
+	let mut watcher = node
+		.submit(Call::TemplateModule(pallet_template::Call::cause_error {}), Keyring::Alice)
+		.unwrap();
+	let hash = node.seal_block().unwrap();
+
+	assert_eq!(watcher.wait_for_inclusion(), Ok(hash));
+	assert!(node.events().iter().any(|record| matches!(
+		record.event,
+		Event::System(frame_system::Event::ExtrinsicFailed(..))
+	)));
+	let alice = Keyring::Alice.to_account_id();
+	assert_eq!(node.storage_map::<_, u32>("TemplateModule", "Something", &alice), None);
+}
+
+#[test]
+fn several_transfers_in_one_block() {
+	let node = TestNode::new();
+	let bob = Keyring::Bob.to_account_id();
+	let initial = node.free_balance(&bob);
+
+	let mut watchers = vec![
+		node.submit(
+			Call::Balances(BalancesCall::transfer { dest: bob.clone().into(), value: 1_000 }),
+			Keyring::Alice,
+		)
+		.unwrap(),
+		node.submit(
+			Call::Balances(BalancesCall::transfer { dest: bob.clone().into(), value: 2_000 }),
+			Keyring::Alice,
+		)
+		.unwrap(),
+	];
+	let hash = node.seal_block().unwrap();
+	node.finalize_block(hash).unwrap();
+
+	for watcher in &mut watchers {
+		assert_eq!(watcher.wait_for_finality(), Ok(hash));
+	}
+	assert_eq!(node.free_balance(&bob), initial + 3_000);
+}